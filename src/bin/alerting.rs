@@ -1,11 +1,58 @@
+#[path = "alerting/ack.rs"]
+mod ack;
 #[path = "alerting/app.rs"]
 mod app;
+#[path = "alerting/autostart.rs"]
+mod autostart;
+#[path = "alerting/bench.rs"]
+mod bench;
 #[path = "alerting/cli.rs"]
 mod cli;
+#[path = "alerting/comment.rs"]
+mod comment;
+#[path = "alerting/graphs.rs"]
+mod graphs;
+#[path = "alerting/heartbeat.rs"]
+mod heartbeat;
+#[path = "alerting/history.rs"]
+mod history;
+#[path = "alerting/mute.rs"]
+mod mute;
+#[path = "alerting/network.rs"]
+mod network;
 #[path = "alerting/notifier/mod.rs"]
 mod notifier;
+#[path = "alerting/pid_file.rs"]
+mod pid_file;
+#[path = "alerting/poll_diff.rs"]
+mod poll_diff;
+#[cfg(feature = "native-prompt")]
+#[path = "alerting/prompt.rs"]
+mod prompt;
+#[path = "alerting/proxies.rs"]
+mod proxies;
+#[path = "alerting/rank.rs"]
+mod rank;
 #[path = "alerting/rate_limit.rs"]
 mod rate_limit;
+#[path = "alerting/resume.rs"]
+mod resume;
+#[path = "alerting/services.rs"]
+mod services;
+#[path = "alerting/simulate.rs"]
+mod simulate;
+#[path = "alerting/state.rs"]
+mod state;
+#[path = "alerting/status_file.rs"]
+mod status_file;
+#[path = "alerting/suppress.rs"]
+mod suppress;
+#[path = "alerting/token.rs"]
+mod token;
+#[path = "alerting/updates.rs"]
+mod updates;
+#[path = "alerting/watchdog.rs"]
+mod watchdog;
 
 use std::error::Error as StdError;
 
@@ -22,7 +69,8 @@ async fn main() -> std::process::ExitCode {
 }
 
 fn report_error(err: &alerting::error::Error) {
-    eprintln!("Error: {err}");
+    tracing::error!(error_code = err.code(), error = %err, "alerting stopped");
+    eprintln!("Error [{}]: {err}", err.code());
     let mut source: Option<&dyn StdError> = err.source();
     while let Some(cause) = source {
         eprintln!("  caused by: {cause}");
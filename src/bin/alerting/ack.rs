@@ -0,0 +1,263 @@
+//! `alerting ack`: acknowledge problems from the command line, by event id,
+//! by host or by severity and age.
+
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alerting::Result;
+use alerting::error::{ConfigError, Error as AlertError};
+use alerting::types::{AckFilter, Severity};
+use alerting::zbx_client::{ProblemQuery, ZbxClient};
+
+use super::history::{Entry, History, Outcome, unix_now};
+
+pub struct AckOptions<'a> {
+    pub event_ids: &'a [String],
+    pub host: Option<&'a str>,
+    /// Acknowledge every problem of `host` rather than refusing when it has
+    /// more than one.
+    pub all: bool,
+    pub severities: &'a [Severity],
+    pub older_than: Option<Duration>,
+    pub message: Option<&'a str>,
+    /// List what would be acknowledged without acknowledging it.
+    pub dry_run: bool,
+}
+
+type Compare = fn(&Severity, &Severity) -> bool;
+
+/// Comparison prefixes of a `--severity` value, longest first.
+const BOUNDS: [(&str, Compare); 5] = [
+    ("<=", PartialOrd::le),
+    (">=", PartialOrd::ge),
+    ("<", PartialOrd::lt),
+    (">", PartialOrd::gt),
+    ("=", PartialEq::eq),
+];
+
+/// `--severity` value: one severity (`high`) or a bound (`<=warning`,
+/// `>=high`, `<average`, `>info`).
+#[derive(Clone, Debug)]
+pub struct SeverityFilter(pub Vec<Severity>);
+
+impl FromStr for SeverityFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        let (keep, name) = BOUNDS
+            .iter()
+            .find_map(|&(prefix, keep)| s.strip_prefix(prefix).map(|name| (keep, name)))
+            .unwrap_or((PartialEq::eq as Compare, s));
+        let bound = Severity::from_str(name.trim())?;
+        let severities: Vec<Severity> = Severity::ALL
+            .into_iter()
+            .filter(|sev| keep(sev, &bound))
+            .collect();
+        if severities.is_empty() {
+            return Err(format!("no severity matches {s:?}"));
+        }
+        Ok(Self(severities))
+    }
+}
+
+/// Event to acknowledge, with the problem name and severity when it was
+/// looked up.
+struct Target {
+    event_id: String,
+    name: Option<String>,
+    severity: Option<Severity>,
+}
+
+impl Target {
+    fn label(&self) -> String {
+        self.name.as_ref().map_or_else(
+            || format!("event {}", self.event_id),
+            |name| format!("event {} ({name})", self.event_id),
+        )
+    }
+}
+
+pub async fn run(client: &ZbxClient, history: &History, options: &AckOptions<'_>) -> Result<()> {
+    let targets = if options.event_ids.is_empty() {
+        matching_targets(client, options).await?
+    } else {
+        options
+            .event_ids
+            .iter()
+            .map(|event_id| Target {
+                event_id: event_id.clone(),
+                name: None,
+                severity: None,
+            })
+            .collect()
+    };
+    if targets.is_empty() {
+        println!("no matching unacknowledged problem");
+        return Ok(());
+    }
+    if options.dry_run {
+        for target in &targets {
+            println!("would acknowledge {}", target.label());
+        }
+        return Ok(());
+    }
+
+    let event_ids: Vec<&str> = targets.iter().map(|t| t.event_id.as_str()).collect();
+    client
+        .ack_events(&event_ids, options.message.map(str::to_string))
+        .await?;
+    for target in targets {
+        println!("acknowledged {}", target.label());
+        history.append(&Entry {
+            at: unix_now(),
+            outcome: Outcome::Acked,
+            event_id: target.event_id,
+            host: options.host.map(str::to_string),
+            severity: target.severity,
+            name: target.name,
+        });
+    }
+    Ok(())
+}
+
+/// Unacknowledged problems matching the host, severity and age filters.
+/// Several problems picked by `--host` alone are only returned with `--all`,
+/// so a typo-sized command cannot silence a whole host by surprise.
+async fn matching_targets(client: &ZbxClient, options: &AckOptions<'_>) -> Result<Vec<Target>> {
+    let host_ids = match options.host {
+        Some(host) => {
+            let ids = client.host_ids_by_name(host).await?;
+            if ids.is_empty() {
+                return Err(usage_error("--host", format!("no host named {host:?}")));
+            }
+            ids
+        }
+        None => Vec::new(),
+    };
+    let query = ProblemQuery {
+        ack: AckFilter::Unacked,
+        event_ids: Vec::new(),
+        host_ids,
+        severities: options.severities.to_vec(),
+        started_before: options.older_than.map(started_before),
+    };
+    let problems = client.find_problems(&query).await?;
+
+    let host_only = options.severities.is_empty() && options.older_than.is_none();
+    if let Some(host) = options.host
+        && host_only
+        && problems.len() > 1
+        && !options.all
+    {
+        let listing: Vec<String> = problems
+            .iter()
+            .map(|problem| format!("  {} {}", problem.event_id, problem.name))
+            .collect();
+        return Err(usage_error(
+            "--host",
+            format!(
+                "{host} has {} unacknowledged problems, pass --all to acknowledge them all:\n{}",
+                problems.len(),
+                listing.join("\n")
+            ),
+        ));
+    }
+    Ok(problems
+        .into_iter()
+        .map(|problem| Target {
+            event_id: problem.event_id,
+            name: Some(problem.name),
+            severity: Some(problem.severity),
+        })
+        .collect())
+}
+
+/// Unix time `age` ago.
+fn started_before(age: Duration) -> i64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .saturating_sub(age);
+    i64::try_from(now.as_secs()).unwrap_or(i64::MAX)
+}
+
+fn usage_error(field: &'static str, message: String) -> AlertError {
+    AlertError::from(ConfigError::InvalidField { field, message })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AckOptions, SeverityFilter, matching_targets};
+    use alerting::types::Severity;
+    use alerting::zbx_client::ZbxClient;
+    use secrecy::SecretString;
+    use serde_json::json;
+    use std::str::FromStr;
+    use std::time::Duration;
+    use url::Url;
+    use wiremock::matchers::{body_string_contains, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn severity_filter_accepts_bounds() {
+        let parse = |s: &str| SeverityFilter::from_str(s).map(|filter| filter.0);
+        assert_eq!(
+            parse("<=warning"),
+            Ok(vec![Severity::Info, Severity::Warning])
+        );
+        assert_eq!(parse(">high"), Ok(vec![Severity::Disaster]));
+        assert_eq!(parse("average"), Ok(vec![Severity::Average]));
+        assert!(parse("<info").is_err());
+        assert!(parse(">=urgent").is_err());
+    }
+
+    #[tokio::test]
+    async fn host_with_several_problems_needs_all() -> Result<(), Box<dyn std::error::Error>> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("host.get"))
+            .and(body_string_contains("\"host\":[\"db01\"]"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(
+                json!({ "jsonrpc": "2.0", "result": [{ "hostid": "10084" }], "id": 1 }),
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("problem.get"))
+            .and(body_string_contains("\"hostids\":[\"10084\"]"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "result": [
+                    { "eventid": "1", "clock": "1700000000", "severity": "4", "name": "Disk full" },
+                    { "eventid": "2", "clock": "1700000100", "severity": "2", "name": "Swap low" }
+                ],
+                "id": 1
+            })))
+            .mount(&server)
+            .await;
+        let client = ZbxClient::new(
+            Url::parse(&server.uri())?,
+            SecretString::from("token"),
+            Duration::from_secs(2),
+            Duration::from_secs(1),
+            true,
+        )?;
+        let mut options = AckOptions {
+            event_ids: &[],
+            host: Some("db01"),
+            all: false,
+            severities: &[],
+            older_than: None,
+            message: None,
+            dry_run: false,
+        };
+
+        assert!(matching_targets(&client, &options).await.is_err());
+        options.all = true;
+        let targets = matching_targets(&client, &options).await?;
+        let ids: Vec<&str> = targets.iter().map(|t| t.event_id.as_str()).collect();
+        assert_eq!(ids, ["1", "2"]);
+        Ok(())
+    }
+}
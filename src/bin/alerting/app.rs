@@ -1,96 +1,270 @@
+use std::collections::{BTreeSet, HashMap};
 use std::num::NonZeroUsize;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use alerting::Result;
-use alerting::config::Config;
+use alerting::config::{Config, EnvKind, LoadOptions, PollSummary};
 use alerting::error::{ConfigError, Error as AlertError};
 use alerting::telemetry::init_tracing;
-use alerting::zbx_client::{Problem, ZbxClient};
+use alerting::types::{AckFilter, DetailLevel, SymptomMode};
+use alerting::zbx_client::{Frontend, HostMeta, HttpPool, Problem, ProblemQuery, ZbxClient};
 use async_channel::{Sender, TrySendError, bounded};
 use lru::LruCache;
 use tokio::signal;
+use tokio::sync::{Notify, watch};
+use tokio::task::JoinHandle;
 use tokio::time::sleep;
-use tracing::{info, warn};
+use tracing::{Instrument, debug, error, field, info, info_span, warn};
+use uuid::Uuid;
 
-use super::cli::Cli;
-use super::notifier::{NotificationItem, run_notifier};
-use super::rate_limit::LeakyBucket;
+use super::ack::AckOptions;
+use super::autostart::Launch;
+use super::bench::BenchOptions;
+use super::cli::{Cli, Command, ConfigCommand, HistoryCommand};
+use super::graphs::GraphCache;
+use super::heartbeat::PollStatus;
+use super::history::{History, Outcome, unix_now};
+use super::notifier::{Notice, NotificationItem, distinct_hosts, run_notifier};
+use super::pid_file::PidFile;
+use super::poll_diff::{ActiveProblems, PollDiff};
+use super::proxies::ProxyHosts;
+use super::rate_limit::{LeakyBucket, LogSampler, StormDetector, StormPhase};
+use super::services::ServiceCache;
+use super::simulate::Simulator;
+use super::state::NotificationIds;
+use super::status_file::StatusFile;
+use super::updates::UpdateWatch;
 
-const DEFAULT_CONFIG: &str = "config.toml";
+/// First configuration file found in the standard locations, else
+/// `config.toml` in the working directory, which may not exist either: the
+/// configuration then comes from the environment alone.
+fn default_config() -> PathBuf {
+    let candidates = alerting::paths::config_candidates();
+    if let Some(path) = candidates.iter().find(|path| path.is_file()) {
+        info!(path = %path.display(), "using configuration file");
+        return path.clone();
+    }
+    info!(
+        searched = ?candidates,
+        "no configuration file found, using environment and defaults"
+    );
+    PathBuf::from(alerting::paths::CONFIG_FILE)
+}
 
 pub async fn run(cli: Cli) -> Result<()> {
     init_tracing(cli.log_filter.as_deref(), cli.json_logs)?;
 
-    let config_path = cli.config.unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG));
-    let mut config = Config::from_env_and_file(&config_path)?;
+    match &cli.command {
+        Some(Command::Config { action }) => match action {
+            ConfigCommand::Show => show_config(&load_config(&cli)?),
+            ConfigCommand::Env => {
+                print_env_variables();
+                Ok(())
+            }
+        },
+        Some(Command::Bench {
+            iterations,
+            problems,
+            latency,
+        }) => {
+            super::bench::run(&BenchOptions {
+                iterations: *iterations,
+                problems: *problems,
+                latency: *latency,
+            })
+            .await
+        }
+        Some(Command::Ack {
+            event_ids,
+            host,
+            all,
+            severity,
+            older_than,
+            message,
+            dry_run,
+        }) => {
+            let config = load_config(&cli)?;
+            let client = build_client(&cli, &config)?;
+            let options = AckOptions {
+                event_ids,
+                host: host.as_deref(),
+                all: *all,
+                severities: severity.as_ref().map_or(&[], |filter| filter.0.as_slice()),
+                older_than: *older_than,
+                message: message.as_deref(),
+                dry_run: *dry_run,
+            };
+            let history = History::new(config.history_file.clone());
+            super::ack::run(&client, &history, &options).await
+        }
+        Some(Command::Comment { event_id, message }) => {
+            let config = load_config(&cli)?;
+            let client = build_client(&cli, &config)?;
+            super::comment::run(&client, event_id, message).await
+        }
+        Some(Command::Rank {
+            event_id,
+            rank,
+            cause_event_id,
+        }) => {
+            let config = load_config(&cli)?;
+            let client = build_client(&cli, &config)?;
+            super::rank::run(&client, event_id, *rank, cause_event_id.as_deref()).await
+        }
+        Some(Command::Suppress {
+            event_id,
+            until,
+            message,
+        }) => {
+            let config = load_config(&cli)?;
+            let client = build_client(&cli, &config)?;
+            super::suppress::run(
+                &client,
+                event_id,
+                *until,
+                message.as_deref(),
+                &config.display,
+            )
+            .await
+        }
+        Some(command @ (Command::Mute { .. } | Command::Unmute | Command::Status)) => {
+            run_mute(&load_config(&cli)?, command)
+        }
+        Some(Command::History { action, since }) => {
+            run_history(&load_config(&cli)?, action.as_ref(), *since)
+        }
+        Some(Command::InstallAutostart) => {
+            let config_path = cli.config.clone().unwrap_or_else(default_config);
+            super::autostart::install(&Launch::current(&config_path, cli.profile.as_deref())?)
+        }
+        Some(Command::UninstallAutostart) => super::autostart::uninstall(),
+        #[cfg(feature = "native-prompt")]
+        Some(Command::Prompt { title, text }) => super::prompt::run(title, text),
+        None => {
+            let config = load_config(&cli)?;
+            run_daemon(cli, config).await
+        }
+    }
+}
 
-    if let Some(interval) = cli.interval {
-        config.poll_interval = interval;
+/// `history` and its `export` and `prune` actions.
+fn run_history(config: &Config, action: Option<&HistoryCommand>, since: Duration) -> Result<()> {
+    let path = config.history_file.as_deref();
+    match action {
+        Some(HistoryCommand::Export {
+            format,
+            since,
+            output,
+        }) => super::history::export(path, *since, *format, output.as_deref(), &config.display),
+        Some(HistoryCommand::Prune) => super::history::run_prune(path, &config.history_retention),
+        None => super::history::run(path, since, &config.display),
     }
-    if let Some(max_notif) = cli.max_notif {
-        if !(1..=100).contains(&max_notif) {
-            return Err(AlertError::from(ConfigError::InvalidField {
-                field: "cli.max_notif",
-                message: "value must be between 1 and 100".to_string(),
-            }));
+}
+
+/// `mute`, `unmute` and `status`, all about the mute in `app.state_file`.
+fn run_mute(config: &Config, command: &Command) -> Result<()> {
+    let state_file = config.state_file.as_deref();
+    let floor = config.notify.mute_floor;
+    match command {
+        Command::Mute { duration } => {
+            super::mute::run(state_file, *duration, floor, &config.display)
+        }
+        Command::Unmute => super::mute::unmute(state_file),
+        _ => {
+            super::mute::status(state_file, floor, &config.display);
+            Ok(())
         }
-        config.max_notif = max_notif;
     }
+}
 
-    #[cfg(target_os = "windows")]
-    if let Some(mut body) = cli.test_toast {
-        let summary = "Test Alerting";
-        if body.trim().is_empty() {
-            body = "Toast de test déclenché par --test-toast".to_string();
-        }
-        super::notifier::send_test_toast(
-            summary,
-            &body,
-            &config.notify.appname,
-            config.notify.icon.as_deref(),
-            &config.notify.open_label,
-        )?;
-        info!("notification de test envoyée, arrêt du programme");
-        return Ok(());
+async fn run_daemon(cli: Cli, config: Config) -> Result<()> {
+    #[cfg(all(target_os = "windows", feature = "windows-toasts"))]
+    if let Some(body) = cli.test_toast {
+        return send_test_toast(body, &config);
     }
 
-    let client = ZbxClient::new(
-        config.base_url.clone(),
-        config.token.clone(),
-        config.http_request_timeout,
-        config.http_connect_timeout,
-        cli.insecure,
-    )?;
+    let client = build_client(&cli, &config)?;
+    if !cli.simulate && cli.replay.is_none() {
+        preflight(&client).await?;
+    }
+    let shutdown = watch_shutdown();
+    let _pid_file = cli.pid_file.as_deref().map(PidFile::create).transpose()?;
+    if let Some(path) = &config.history_file {
+        super::history::prune_on_startup(path, &config.history_retention);
+    }
 
+    let mut state = PollState::new(&config);
     let (tx, rx) = bounded(config.queue_capacity);
-    let notifier_client = client.clone();
     let notifier = tokio::spawn(run_notifier(
         rx,
         config.notify.clone(),
-        notifier_client,
+        config.display.clone(),
+        (!cli.simulate).then(|| client.clone()),
+        NotificationIds::load(config.state_file.clone()),
+        state.history.clone(),
         cli.dry_run,
     ));
-
-    let dedup_capacity = NonZeroUsize::new(config.dedup_cache_size)
-        .unwrap_or_else(|| unreachable!("dedup cache size validated to be > 0"));
-    let mut dedup = LruCache::new(dedup_capacity);
-    let mut bucket = LeakyBucket::new(config.rate_limit.max_events, config.rate_limit.per);
+    live_caches(&cli, &config, &mut state)?;
+    let mut simulator = simulator(&cli);
+    let (status_tx, status_rx) = watch::channel(None);
+    let monitors = spawn_monitors(&config, &status_rx, &tx);
+    let resumed = super::resume::watch();
+    let reconnected = super::network::watch();
+    let mut catch_up = false;
+    let mut iteration: u64 = 0;
 
     loop {
         let iteration_start = Instant::now();
+        iteration += 1;
+        let span = info_span!("poll", iteration, duration_ms = field::Empty);
+        let poll = poll_once(
+            &client,
+            simulator.as_mut(),
+            &config,
+            &mut state,
+            catch_up,
+            &tx,
+        )
+        .instrument(span.clone());
         tokio::select! {
             biased;
-            _ = signal::ctrl_c() => {
+            () = shutdown.notified() => {
                 info!("shutdown signal received, stopping loop");
                 break;
             }
-            res = poll_once(&client, &config, &mut dedup, &mut bucket, &tx) => {
-                res?;
+            res = poll => {
+                if let Some(status_file) = &mut state.status_file {
+                    status_file.update(
+                        res.as_ref().err(),
+                        &state.active,
+                        tx.len(),
+                        &client.call_stats(),
+                    );
+                }
+                match res {
+                    Ok(()) => {
+                        status_tx.send_replace(Some(PollStatus {
+                            at: Instant::now(),
+                            active: state.active.len(),
+                        }));
+                    }
+                    // Under the watchdog a failed poll is retried at the next
+                    // interval; the watchdog tells the user when it lasts.
+                    Err(err) if config.watchdog_deadline.is_some() && !cli.once => {
+                        error!(error_code = err.code(), error = %err, "poll failed");
+                    }
+                    Err(err) => return Err(err),
+                }
             }
         }
+        catch_up = false;
+        let duration_ms = u64::try_from(iteration_start.elapsed().as_millis()).unwrap_or(u64::MAX);
+        span.record("duration_ms", duration_ms);
+        debug!(parent: &span, duration_ms, "poll iteration finished");
 
-        if cli.once {
+        if cli.once || client.replay_exhausted() {
             break;
         }
 
@@ -104,38 +278,400 @@ pub async fn run(cli: Cli) -> Result<()> {
             continue;
         }
 
-        tokio::select! {
-            _ = signal::ctrl_c() => {
-                info!("shutdown signal received, stopping loop");
-                break;
-            }
-            () = sleep(sleep_dur) => {}
+        match wait_next_poll(sleep_dur, &shutdown, &resumed, &reconnected).await {
+            Wake::Shutdown => break,
+            Wake::Resumed => catch_up = true,
+            Wake::Due => {}
         }
     }
 
+    stop_tasks(monitors, &tx, notifier).await;
+    log_call_stats(&client);
+
+    Ok(())
+}
+
+/// Stop the monitors, then let the notifier drain its queue.
+async fn stop_tasks(monitors: Vec<JoinHandle<()>>, tx: &Sender<Notice>, notifier: JoinHandle<()>) {
+    for monitor in monitors {
+        monitor.abort();
+    }
     tx.close();
     if let Err(err) = notifier.await {
         warn!(error = %err, "notifier task terminated unexpectedly");
     }
+}
+
+/// Why the wait between two polls ended.
+enum Wake {
+    Due,
+    /// Back from suspend: problems found next are batched into a digest.
+    Resumed,
+    Shutdown,
+}
+
+/// Spawn the signal listener and return the handle notified on Ctrl-C, or
+/// on SIGTERM from a service manager on Unix. A signal that arrives while
+/// nothing waits is kept for the next wait.
+fn watch_shutdown() -> Arc<Notify> {
+    let shutdown = Arc::new(Notify::new());
+    let notify = Arc::clone(&shutdown);
+    tokio::spawn(async move {
+        #[cfg(unix)]
+        match signal::unix::signal(signal::unix::SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+                notify.notify_one();
+                return;
+            }
+            Err(err) => warn!(error = %err, "failed to listen for SIGTERM"),
+        }
+        if signal::ctrl_c().await.is_ok() {
+            notify.notify_one();
+        }
+    });
+    shutdown
+}
+
+async fn wait_next_poll(
+    delay: Duration,
+    shutdown: &Notify,
+    resumed: &Notify,
+    reconnected: &Notify,
+) -> Wake {
+    tokio::select! {
+        () = shutdown.notified() => {
+            info!("shutdown signal received, stopping loop");
+            Wake::Shutdown
+        }
+        () = sleep(delay) => Wake::Due,
+        () = resumed.notified() => {
+            info!("resumed from suspend, polling immediately");
+            Wake::Resumed
+        }
+        () = reconnected.notified() => {
+            info!("network reconnected, polling immediately");
+            Wake::Due
+        }
+    }
+}
+
+/// Heartbeat and watchdog tasks, both fed by the status of the last
+/// successful poll.
+fn spawn_monitors(
+    config: &Config,
+    status: &watch::Receiver<Option<PollStatus>>,
+    tx: &Sender<Notice>,
+) -> Vec<JoinHandle<()>> {
+    let heartbeat = (config.notify.heartbeat.clone())
+        .map(|heartbeat| super::heartbeat::spawn(heartbeat, status.clone(), tx.clone()));
+    let watchdog = config
+        .watchdog_deadline
+        .map(|deadline| super::watchdog::spawn(deadline, status.clone(), tx.clone()));
+    heartbeat.into_iter().chain(watchdog).collect()
+}
+
+/// Synthetic problem source replacing Zabbix under `--simulate`.
+fn simulator(cli: &Cli) -> Option<Simulator> {
+    cli.simulate.then(|| {
+        Simulator::new(
+            cli.sim_rate,
+            cli.sim_severities.clone(),
+            cli.sim_hosts.clone(),
+        )
+    })
+}
+
+#[cfg(all(target_os = "windows", feature = "windows-toasts"))]
+fn send_test_toast(mut body: String, config: &Config) -> Result<()> {
+    let summary = "Test Alerting";
+    if body.trim().is_empty() {
+        body = "Toast de test déclenché par --test-toast".to_string();
+    }
+    super::notifier::send_test_toast(
+        summary,
+        &body,
+        &config.notify.appname,
+        config.notify.icon.as_deref(),
+        &config.notify.open_label,
+    )?;
+    info!("notification de test envoyée, arrêt du programme");
+    Ok(())
+}
+
+fn load_config(cli: &Cli) -> Result<Config> {
+    let config_path = cli.config.clone().unwrap_or_else(default_config);
+    let load = |token| {
+        Config::load(
+            &config_path,
+            LoadOptions {
+                profile: cli.profile.as_deref(),
+                format: cli.config_format,
+                dotenv: true,
+                token,
+                // Simulated problems need no server, nor the keyring.
+                offline: cli.simulate,
+            },
+        )
+    };
+    let mut config = match load(None) {
+        Err(AlertError::Config(ConfigError::MissingField {
+            field: "zabbix.token",
+        })) if let Some(token) = super::token::stored().or_else(super::token::prompt) => {
+            load(Some(&token))?
+        }
+        loaded => loaded?,
+    };
+
+    if let Some(interval) = cli.interval {
+        config.poll_interval = interval;
+    }
+    if let Some(max_notif) = cli.max_notif {
+        if !(1..=100).contains(&max_notif) {
+            return Err(AlertError::from(ConfigError::InvalidField {
+                field: "cli.max_notif",
+                message: "value must be between 1 and 100".to_string(),
+            }));
+        }
+        config.max_notif = max_notif;
+    }
+    Ok(config)
+}
+
+fn build_client(cli: &Cli, config: &Config) -> Result<ZbxClient> {
+    let client = ZbxClient::new(
+        config.base_url.clone(),
+        config.token.clone(),
+        config.http_request_timeout,
+        config.http_connect_timeout,
+        cli.insecure,
+    )?
+    .with_fallback_urls(&config.fallback_urls)?
+    .with_http_pool(&HttpPool {
+        idle_timeout: config.http_pool_idle_timeout,
+        max_idle_per_host: config.http_pool_max_idle_per_host.unwrap_or(usize::MAX),
+        tcp_keepalive: config.http_tcp_keepalive,
+    })?
+    .with_request_limits(config.max_requests_per_second, config.max_in_flight)
+    .with_host_miss_ttl(config.host_miss_ttl)
+    .with_event_filter(&config.event_sources, &config.event_objects);
+    // The cause button needs to know which problems are symptoms.
+    let client = if config.symptoms == SymptomMode::Show && !config.notify.rank_action {
+        client
+    } else {
+        client.with_cause_events()
+    };
+    let client = if config.notify.detail == DetailLevel::Debug {
+        client.with_event_tags()
+    } else {
+        client
+    };
+    let client = if cli.log_rpc || config.log_rpc {
+        client.with_rpc_logging()
+    } else {
+        client
+    };
+    Ok(match (&cli.record, &cli.replay) {
+        (Some(dir), _) => client.record_to(dir),
+        (_, Some(dir)) => client.replay_from(dir),
+        _ => client,
+    })
+}
+
+/// Trigger graphs and comment updates, for live polls only: simulated and
+/// replayed problems have neither.
+fn live_caches(cli: &Cli, config: &Config, state: &mut PollState) -> Result<()> {
+    if cli.simulate || cli.replay.is_some() {
+        return Ok(());
+    }
+    if config.notify.comment_updates {
+        state.updates = Some(UpdateWatch::new());
+    }
+    let Some(graph) = &config.graph else {
+        return Ok(());
+    };
+    let frontend = Frontend::new(
+        config.base_url.clone(),
+        graph.user.clone(),
+        graph.password.clone(),
+        config.http_request_timeout,
+        config.http_connect_timeout,
+        cli.insecure,
+    )?;
+    state.graphs = Some(GraphCache::new(frontend, graph.period));
+    Ok(())
+}
+
+/// Fail fast on an unreachable API or unusable token instead of on the first
+/// poll, and warn up front when acknowledging from a toast would be refused.
+async fn preflight(client: &ZbxClient) -> Result<()> {
+    let report = client.preflight().await?;
+    info!(api_version = %report.api_version, "preflight checks passed");
+    match report.can_acknowledge {
+        Some(false) => {
+            warn!("token lacks write access: acknowledging from notifications will fail");
+        }
+        None => debug!("could not determine whether the token may acknowledge events"),
+        Some(true) => {}
+    }
+    Ok(())
+}
+
+fn log_call_stats(client: &ZbxClient) {
+    for stats in client.call_stats() {
+        info!(
+            method = %stats.method,
+            calls = stats.calls,
+            errors = stats.errors,
+            error_rate = stats.error_rate(),
+            p50_ms = stats.quantile_ms(0.5),
+            p95_ms = stats.quantile_ms(0.95),
+            max_ms = stats.max_ms,
+            "zabbix call latency"
+        );
+    }
+}
 
+fn show_config(config: &Config) -> Result<()> {
+    let rendered = serde_json::to_string_pretty(config)
+        .map_err(|err| AlertError::from(ConfigError::Other(err.to_string())))?;
+    println!("{rendered}");
     Ok(())
 }
 
+fn print_env_variables() {
+    for variable in alerting::config::env_variables() {
+        match variable.kind {
+            EnvKind::Value => println!("{}", variable.name()),
+            EnvKind::List => println!("{}  (comma-separated)", variable.name()),
+            EnvKind::Command => println!("{}  (command line or JSON array)", variable.name()),
+            EnvKind::Table => println!("{}__<NAME>__…", variable.name()),
+        }
+    }
+}
+
+/// Items kept for the closing digest of an alert storm; later ones are only
+/// counted.
+const MAX_HELD: usize = 500;
+
+/// State carried from one poll to the next.
+struct PollState {
+    dedup: LruCache<(String, i64), ()>,
+    /// When each event was last queued, for `app.notify_cooldown`.
+    queued_at: LruCache<String, Instant>,
+    bucket: LeakyBucket,
+    storm: Option<StormDetector>,
+    /// Items held back while an alert storm rages, for the closing digest.
+    held: Vec<NotificationItem>,
+    /// Problems of the storm beyond `MAX_HELD`, counted only.
+    held_over: usize,
+    /// Held problems resolved since the storm started, for its progress.
+    held_resolved: usize,
+    services: Option<ServiceCache>,
+    /// Hosts to fetch problems of, with `zabbix.proxy_ids`.
+    proxy_hosts: Option<ProxyHosts>,
+    /// Set by the daemon only (`live_caches`).
+    graphs: Option<GraphCache>,
+    history: History,
+    /// Problems still active at the last live poll.
+    active: ActiveProblems,
+    /// Set by the daemon only, with `notify.comment_updates`.
+    updates: Option<UpdateWatch>,
+    status_file: Option<StatusFile>,
+}
+
+impl PollState {
+    /// Whether `problem` is news: not a change already seen, nor one made
+    /// less than `cooldown` after the event was last queued. News is
+    /// remembered as seen.
+    fn is_news(&mut self, problem: &Problem, cooldown: Option<Duration>) -> bool {
+        let key = (problem.event_id.clone(), problem.last_change);
+        if self.dedup.contains(&key) {
+            debug_dup(problem);
+            return false;
+        }
+        let cooling_down = cooldown
+            .zip(self.queued_at.peek(&problem.event_id))
+            .is_some_and(|(cooldown, queued)| queued.elapsed() < cooldown);
+        if cooling_down {
+            // Left out of the dedup cache: the latest change is shown at the
+            // first poll after the cooldown.
+            debug!(event_id = %problem.event_id, "problem changed during its cooldown");
+            return false;
+        }
+        self.dedup.put(key, ());
+        true
+    }
+
+    /// Follow the problems about to be shown, with `notify.comment_updates`.
+    fn watch_updates(&mut self, items: &[NotificationItem]) {
+        let Some(updates) = &mut self.updates else {
+            return;
+        };
+        for item in items {
+            updates.watch(
+                &item.problem.event_id,
+                &item.problem.name,
+                item.host.as_ref().map(|h| h.display_name.as_str()),
+                item.open_url.as_deref(),
+                item.problem.last_change.max(item.problem.clock),
+            );
+        }
+    }
+
+    fn new(config: &Config) -> Self {
+        let dedup_capacity = NonZeroUsize::new(config.dedup_cache_size)
+            .unwrap_or_else(|| unreachable!("dedup cache size validated to be > 0"));
+        Self {
+            dedup: LruCache::new(dedup_capacity),
+            queued_at: LruCache::new(dedup_capacity),
+            bucket: LeakyBucket::new(config.rate_limit.max_events, config.rate_limit.per),
+            storm: config
+                .storm
+                .as_ref()
+                .map(|storm| StormDetector::new(storm.threshold, storm.window)),
+            held: Vec::new(),
+            held_over: 0,
+            held_resolved: 0,
+            services: config.service_impact.map(ServiceCache::new),
+            proxy_hosts: (!config.proxy_ids.is_empty())
+                .then(|| ProxyHosts::new(config.proxy_ids.clone())),
+            graphs: None,
+            history: History::new(config.history_file.clone()),
+            active: ActiveProblems::default(),
+            updates: None,
+            status_file: config.status_file.clone().map(StatusFile::new),
+        }
+    }
+}
+
+/// Poll once and queue notifications for new problems.
+///
+/// With `catch_up` set (first poll after a resume), several new problems are
+/// queued as one digest instead of a burst of toasts.
 async fn poll_once(
     client: &ZbxClient,
+    simulator: Option<&mut Simulator>,
     config: &Config,
-    dedup: &mut LruCache<(String, i64), ()>,
-    bucket: &mut LeakyBucket,
-    tx: &Sender<NotificationItem>,
+    state: &mut PollState,
+    catch_up: bool,
+    tx: &Sender<Notice>,
 ) -> Result<()> {
-    let problems = client
-        .active_problems(config.limit, config.ack_filter)
-        .await?;
-
-    let event_ids: Vec<String> = problems.iter().map(|p| p.event_id.clone()).collect();
-    let hosts = client.resolve_hosts(&event_ids, config.concurrency).await?;
-
-    let mut rows: Vec<_> = problems.into_iter().zip(hosts.into_iter()).collect();
+    let live = simulator.is_none();
+    let mut rows = match simulator {
+        Some(simulator) => simulator.next_batch(),
+        None => fetch_rows(client, config, state.proxy_hosts.as_mut()).await?,
+    };
+    if live {
+        let diff = diff_poll(client, state, &rows).await;
+        queue_diff_notices(client, config, state, &diff, tx).await;
+        if config.notify.poll_summary == PollSummary::Only {
+            return Ok(());
+        }
+    }
 
     rows.sort_unstable_by(|(a, _), (b, _)| {
         u8::from(a.acknowledged)
@@ -143,66 +679,656 @@ async fn poll_once(
             .then(b.severity.cmp(&a.severity))
             .then(b.clock.cmp(&a.clock))
     });
+    let per_host = count_per_host(&rows);
+    let unacked_per_host = count_per_host(rows.iter().filter(|(problem, _)| !problem.acknowledged));
     if rows.len() > config.max_notif {
         rows.truncate(config.max_notif);
     }
 
+    let muted = config
+        .state_file
+        .as_deref()
+        .and_then(|path| super::state::muted_until(path, unix_now()));
+    let mut pending = Vec::new();
     for (problem, host) in rows {
         if problem.acknowledged && !config.notify.notify_acked {
             continue;
         }
-
-        let key = (problem.event_id.clone(), problem.last_change);
-        if dedup.contains(&key) {
-            debug_dup(&problem);
+        if config.symptoms == SymptomMode::Skip && problem.cause_event_id.is_some() {
             continue;
         }
-        dedup.put(key, ());
 
-        let now = Instant::now();
-        if !bucket.try_acquire(now) {
-            warn!(event_id = %problem.event_id, "dropping notification due to rate limit");
+        if !state.is_news(&problem, config.notify_cooldown) {
+            continue;
+        }
+        if muted.is_some() && problem.severity < config.notify.mute_floor {
+            debug!(event_id = %problem.event_id, muted_until = ?muted, "problem muted");
+            state
+                .history
+                .record(Outcome::Muted, &problem, host.as_ref());
             continue;
         }
-
-        let latency = compute_latency_ms(problem.clock);
-        let host_label = host
-            .as_ref()
-            .map_or("<unknown>", |h| h.display_name.as_str());
-
-        info!(
-            event_id = %problem.event_id,
-            host = host_label,
-            severity = ?problem.severity,
-            ack_state = problem.acknowledged,
-            latency_ms = latency.unwrap_or_default(),
-            "queueing notification"
-        );
 
         let open_url = config
             .open_url_fmt
             .as_deref()
             .map(|fmt| fmt.replace("{eventid}", problem.event_id.as_str()));
-
+        let host_url = config
+            .host_url_fmt
+            .as_deref()
+            .zip(host.as_ref())
+            .and_then(|(fmt, host)| render_host_url(fmt, host));
+        let others_on_host = host
+            .as_ref()
+            .and_then(|host| per_host.get(host_key(host)))
+            .map_or(0, |count| count.saturating_sub(1));
+        let unacked_on_host = host
+            .as_ref()
+            .and_then(|host| unacked_per_host.get(host_key(host)))
+            .copied()
+            .unwrap_or_default();
+        state
+            .queued_at
+            .put(problem.event_id.clone(), Instant::now());
+        let correlation_id = Uuid::now_v7().to_string();
+        let span = info_span!(
+            "notification",
+            correlation_id = %correlation_id,
+            event_id = %problem.event_id
+        );
         let item = NotificationItem {
             problem,
             host,
             open_url,
+            host_url,
+            others_on_host,
+            unacked_on_host,
+            services: Vec::new(),
+            graph: None,
+            trigger_expression: None,
+            correlation_id,
+            span,
         };
 
-        match tx.try_send(item) {
-            Ok(()) => {}
-            Err(TrySendError::Full(item)) => {
+        pending.push(item);
+    }
+
+    if live && !pending.is_empty() {
+        enrich(client, config, state, &mut pending).await;
+        state.watch_updates(&pending);
+    }
+
+    let shown = dispatch(pending, config, state, catch_up, tx);
+    queue_shown(client, config, state, shown, tx).await;
+    Ok(())
+}
+
+/// Action Center keeps every toast until it is dismissed, so toasts of
+/// resolved problems are removed from it like resident toasts on Linux.
+const CLOSE_RESOLVED: bool = cfg!(all(target_os = "windows", feature = "windows-toasts"));
+
+/// Queue what the notifier shows about the poll's changes rather than
+/// about new problems.
+async fn queue_diff_notices(
+    client: &ZbxClient,
+    config: &Config,
+    state: &mut PollState,
+    diff: &PollDiff,
+    tx: &Sender<Notice>,
+) {
+    let resolved = config.notify.resident || CLOSE_RESOLVED;
+    if resolved || config.notify.close_on_ack {
+        queue_close(diff, resolved, tx);
+    }
+    queue_storm_progress(state, diff, tx);
+    if config.notify.poll_summary != PollSummary::Off {
+        queue_poll_summary(diff, state.active.len(), tx);
+    }
+    if let Some(updates) = &mut state.updates {
+        follow_updates(client, updates, diff, tx).await;
+    }
+}
+
+/// Queue a toast per comment or acknowledgement others added to problems
+/// already shown, and stop following the resolved ones.
+async fn follow_updates(
+    client: &ZbxClient,
+    updates: &mut UpdateWatch,
+    diff: &PollDiff,
+    tx: &Sender<Notice>,
+) {
+    updates.forget(diff.resolved.iter().map(|(p, _)| p.event_id.as_str()));
+    for news in updates.check(client).await {
+        if let Err(TrySendError::Full(_)) = tx.try_send(Notice::EventUpdate(Box::new(news))) {
+            warn!("notification queue full; problem update dropped");
+        }
+    }
+}
+
+/// Compare this poll with the last one, log the delta and record
+/// resolutions. Problems missing from the poll are looked up by event id
+/// first: one merely hidden by `ack_filter`, `limit` or the proxy filter is
+/// still active, and may have changed.
+async fn diff_poll(
+    client: &ZbxClient,
+    state: &mut PollState,
+    rows: &[(Problem, Option<HostMeta>)],
+) -> PollDiff {
+    let (mut diff, vanished) = state.active.update(rows);
+    if !vanished.is_empty() {
+        let query = ProblemQuery {
+            ack: AckFilter::All,
+            event_ids: vanished.iter().map(|(p, _)| p.event_id.clone()).collect(),
+            host_ids: Vec::new(),
+            severities: Vec::new(),
+            started_before: None,
+        };
+        match client.find_problems(&query).await {
+            Ok(problems) => {
+                let mut current: HashMap<String, Problem> = problems
+                    .into_iter()
+                    .map(|p| (p.event_id.clone(), p))
+                    .collect();
+                for row in vanished {
+                    match current.remove(&row.0.event_id) {
+                        Some(problem) => state.active.still_active(row, problem, &mut diff),
+                        None => diff.resolved.push(row),
+                    }
+                }
+            }
+            Err(err) => {
                 warn!(
-                    "notification queue full; dropping event {}",
-                    item.problem.event_id
+                    error_code = err.code(),
+                    error = %err,
+                    "failed to check which problems were resolved"
                 );
+                // Try again at the next poll rather than guess.
+                for row in vanished {
+                    let problem = row.0.clone();
+                    state.active.still_active(row, problem, &mut diff);
+                }
             }
-            Err(TrySendError::Closed(_)) => break,
         }
     }
+    diff.log(state.active.len());
+    for (problem, host) in &diff.resolved {
+        state
+            .history
+            .record(Outcome::Resolved, problem, host.as_ref());
+    }
+    diff
+}
+
+/// Close the toasts of problems acknowledged since the last poll, by this
+/// daemon or in the web UI, and with `resolved` of those resolved meanwhile:
+/// resident toasts would otherwise stay in the notification list.
+fn queue_close(diff: &PollDiff, resolved: bool, tx: &Sender<Notice>) {
+    let event_ids: Vec<String> = diff
+        .resolved
+        .iter()
+        .filter(|_| resolved)
+        .map(|(problem, _)| problem)
+        .chain(
+            diff.changed
+                .iter()
+                .filter(|change| !change.before.acknowledged && change.after.0.acknowledged)
+                .map(|change| &change.after.0),
+        )
+        .map(|problem| problem.event_id.clone())
+        .collect();
+    if event_ids.is_empty() {
+        return;
+    }
+    if let Err(TrySendError::Full(_)) = tx.try_send(Notice::Close(event_ids)) {
+        warn!("notification queue full; notifications left open");
+    }
+}
 
-    Ok(())
+/// One toast counting what this poll changed (`notify.poll_summary`).
+fn queue_poll_summary(diff: &PollDiff, active: usize, tx: &Sender<Notice>) {
+    let counts = diff.counts();
+    if counts.is_empty() {
+        return;
+    }
+    if let Err(TrySendError::Full(_)) = tx.try_send(Notice::PollSummary { counts, active }) {
+        warn!("notification queue full; poll summary dropped");
+    }
+}
+
+/// Add the optional details that cost extra calls, once per poll with new
+/// problems.
+async fn enrich(
+    client: &ZbxClient,
+    config: &Config,
+    state: &mut PollState,
+    pending: &mut [NotificationItem],
+) {
+    if let Some(services) = state.services.as_mut() {
+        let event_ids: Vec<&str> = pending
+            .iter()
+            .map(|item| item.problem.event_id.as_str())
+            .collect();
+        services.refresh(client, &event_ids).await;
+        for item in pending.iter_mut() {
+            item.services = services.for_event(&item.problem.event_id);
+        }
+    }
+    if config.notify.detail == DetailLevel::Debug {
+        let trigger_ids: Vec<String> = pending
+            .iter()
+            .filter_map(|item| item.problem.trigger_id.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        match client.trigger_expressions(&trigger_ids).await {
+            Ok(expressions) => {
+                for item in pending.iter_mut() {
+                    item.trigger_expression = item
+                        .problem
+                        .trigger_id
+                        .as_ref()
+                        .and_then(|id| expressions.get(id).cloned());
+                }
+            }
+            Err(err) => {
+                warn!(
+                    error_code = err.code(),
+                    error = %err,
+                    "failed to look up trigger expressions"
+                );
+            }
+        }
+    }
+}
+
+/// Sort the items found by one poll: held back during an alert storm, as a
+/// digest when catching up, per host or one by one otherwise. Returns the
+/// toasts that passed the rate limit, for [`queue_shown`].
+fn dispatch(
+    pending: Vec<NotificationItem>,
+    config: &Config,
+    state: &mut PollState,
+    catch_up: bool,
+    tx: &Sender<Notice>,
+) -> Vec<Notice> {
+    let phase = state.storm.as_mut().map_or(StormPhase::Calm, |storm| {
+        storm.observe(Instant::now(), pending.len())
+    });
+    match phase {
+        StormPhase::Started | StormPhase::Raging => {
+            let changed = phase == StormPhase::Started || !pending.is_empty();
+            let room = MAX_HELD.saturating_sub(state.held.len());
+            if pending.len() > room {
+                state.held_over += pending.len() - room;
+                debug!(
+                    max = MAX_HELD,
+                    over = state.held_over,
+                    "alert storm digest full; counting further problems only"
+                );
+            }
+            state.held.extend(pending.into_iter().take(room));
+            if changed {
+                queue_storm(state, tx);
+            }
+            return Vec::new();
+        }
+        StormPhase::Subsided => {
+            let held = std::mem::take(&mut state.held);
+            let unlisted = std::mem::take(&mut state.held_over);
+            state.held_resolved = 0;
+            info!(
+                count = held.len() + unlisted,
+                "alert storm subsided, queueing digest"
+            );
+            if let Err(TrySendError::Full(Notice::StormOver(held, _))) =
+                tx.try_send(Notice::StormOver(held, unlisted))
+            {
+                warn!("notification queue full; dropping alert storm digest");
+                record_dropped(&state.history, Outcome::QueueFull, &held);
+            }
+        }
+        StormPhase::Calm => {}
+    }
+
+    let bucket = &mut state.bucket;
+    let history = &state.history;
+    if catch_up {
+        queue_digest(pending, bucket, history, tx)
+            .into_iter()
+            .collect()
+    } else if config.notify.batch_by_host {
+        batch_by_host(pending)
+            .into_iter()
+            .filter_map(|batch| match <[NotificationItem; 1]>::try_from(batch) {
+                Ok([item]) => admit_item(item, bucket, history),
+                Err(batch) => admit_host_batch(batch, bucket, history),
+            })
+            .collect()
+    } else {
+        pending
+            .into_iter()
+            .filter_map(|item| admit_item(item, bucket, history))
+            .collect()
+    }
+}
+
+/// Attach trigger graphs to the single toasts about to be shown, then queue
+/// every toast in order. Graphs are only downloaded for toasts that passed
+/// the storm check and the rate limit.
+async fn queue_shown(
+    client: &ZbxClient,
+    config: &Config,
+    state: &mut PollState,
+    mut shown: Vec<Notice>,
+    tx: &Sender<Notice>,
+) {
+    if let Some(graphs) = state.graphs.as_mut() {
+        let singles = shown
+            .iter_mut()
+            .filter_map(|notice| match notice {
+                Notice::Problem(item) => Some(&mut **item),
+                _ => None,
+            })
+            .collect();
+        graphs.attach(client, singles, config.concurrency).await;
+    }
+    for notice in shown {
+        if !queue_notice(notice, &state.history, tx) {
+            break;
+        }
+    }
+}
+
+/// Show or refresh the sticky storm toast. It bypasses the rate limit: it is
+/// the one toast left while the flood lasts.
+fn queue_storm(state: &PollState, tx: &Sender<Notice>) {
+    let hosts = distinct_hosts(&state.held);
+    let problems = state.held.len() + state.held_over;
+    let resolved = state.held_resolved;
+    warn!(
+        problems,
+        hosts, resolved, "alert storm: suppressing individual notifications"
+    );
+    let notice = Notice::Storm {
+        problems,
+        hosts,
+        resolved,
+    };
+    if let Err(TrySendError::Full(_)) = tx.try_send(notice) {
+        warn!("notification queue full; dropping alert storm notification");
+    }
+}
+
+/// Refresh the storm toast when problems it holds back were resolved, so
+/// its progress moves.
+fn queue_storm_progress(state: &mut PollState, diff: &PollDiff, tx: &Sender<Notice>) {
+    let resolved = diff
+        .resolved
+        .iter()
+        .filter(|(problem, _)| {
+            state
+                .held
+                .iter()
+                .any(|item| item.problem.event_id == problem.event_id)
+        })
+        .count();
+    if resolved > 0 {
+        state.held_resolved += resolved;
+        queue_storm(state, tx);
+    }
+}
+
+/// Queue problems found while suspended as one digest. A single problem is
+/// returned as a plain notification instead, once past the rate limit.
+fn queue_digest(
+    mut pending: Vec<NotificationItem>,
+    bucket: &mut LeakyBucket,
+    history: &History,
+    tx: &Sender<Notice>,
+) -> Option<Notice> {
+    if pending.len() > 1 {
+        if bucket.try_acquire(Instant::now()) {
+            info!(count = pending.len(), "queueing catch-up digest");
+            if let Err(TrySendError::Full(Notice::Digest(pending))) =
+                tx.try_send(Notice::Digest(pending))
+            {
+                warn!("notification queue full; dropping catch-up digest");
+                record_dropped(history, Outcome::QueueFull, &pending);
+            }
+        } else {
+            warn!("dropping catch-up digest due to rate limit");
+            record_dropped(history, Outcome::RateLimited, &pending);
+        }
+        None
+    } else {
+        admit_item(pending.pop()?, bucket, history)
+    }
+}
+
+fn record_dropped(history: &History, outcome: Outcome, items: &[NotificationItem]) {
+    for item in items {
+        history.record(outcome, &item.problem, item.host.as_ref());
+    }
+}
+
+/// Active problems per host in this poll, counted before `max_notif` trims
+/// the list so the toast can mention the ones it does not show.
+fn count_per_host<'a>(
+    rows: impl IntoIterator<Item = &'a (Problem, Option<HostMeta>)>,
+) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for host in rows.into_iter().filter_map(|(_, host)| host.as_ref()) {
+        *counts.entry(host_key(host).to_string()).or_default() += 1;
+    }
+    counts
+}
+
+fn host_key(host: &HostMeta) -> &str {
+    host.host.as_deref().unwrap_or(&host.display_name)
+}
+
+/// Fill `{hostid}` and `{host}` (URL-encoded) in a host dashboard template.
+/// `None` when the template needs a value Zabbix did not return.
+fn render_host_url(fmt: &str, meta: &HostMeta) -> Option<String> {
+    let mut url = fmt.to_owned();
+    if url.contains("{hostid}") {
+        url = url.replace("{hostid}", meta.host_id.as_deref()?);
+    }
+    if url.contains("{host}") {
+        let name: String =
+            url::form_urlencoded::byte_serialize(meta.host.as_deref()?.as_bytes()).collect();
+        url = url.replace("{host}", &name);
+    }
+    Some(url)
+}
+
+/// Split items per host, keeping the poll order (most severe first) both
+/// between hosts and within each host.
+fn batch_by_host(items: Vec<NotificationItem>) -> Vec<Vec<NotificationItem>> {
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut batches: Vec<Vec<NotificationItem>> = Vec::new();
+    for item in items {
+        let key = item.host.as_ref().map_or("<unknown>", host_key).to_string();
+        if let Some(&at) = index.get(&key) {
+            batches[at].push(item);
+        } else {
+            index.insert(key, batches.len());
+            batches.push(vec![item]);
+        }
+    }
+    batches
+}
+
+/// One toast for several problems of the same host, unless the rate limit
+/// drops it.
+fn admit_host_batch(
+    items: Vec<NotificationItem>,
+    bucket: &mut LeakyBucket,
+    history: &History,
+) -> Option<Notice> {
+    if !bucket.try_acquire(Instant::now()) {
+        let host_label = batch_host(&items);
+        if let Some(suppressed) = RATE_LIMIT_LOG.sample() {
+            warn!(
+                host = %host_label,
+                count = items.len(),
+                suppressed,
+                "dropping host batch due to rate limit"
+            );
+        }
+        record_dropped(history, Outcome::RateLimited, &items);
+        return None;
+    }
+    Some(Notice::HostBatch(items))
+}
+
+fn batch_host(items: &[NotificationItem]) -> &str {
+    items
+        .first()
+        .and_then(|item| item.host.as_ref())
+        .map_or("<unknown>", |h| h.display_name.as_str())
+}
+
+/// A single notification, unless the rate limit drops it.
+fn admit_item(
+    item: NotificationItem,
+    bucket: &mut LeakyBucket,
+    history: &History,
+) -> Option<Notice> {
+    if !bucket.try_acquire(Instant::now()) {
+        if let Some(suppressed) = RATE_LIMIT_LOG.sample() {
+            warn!(
+                event_id = %item.problem.event_id,
+                suppressed,
+                "dropping notification due to rate limit"
+            );
+        }
+        history.record(Outcome::RateLimited, &item.problem, item.host.as_ref());
+        return None;
+    }
+    Some(Notice::Problem(Box::new(item)))
+}
+
+/// Queue a toast admitted by [`dispatch`]. Returns `false` once the notifier
+/// has gone away.
+fn queue_notice(notice: Notice, history: &History, tx: &Sender<Notice>) -> bool {
+    match notice {
+        Notice::Problem(item) => queue_item(item, history, tx),
+        Notice::HostBatch(items) => queue_host_batch(items, history, tx),
+        notice => !matches!(tx.try_send(notice), Err(TrySendError::Closed(_))),
+    }
+}
+
+fn queue_host_batch(items: Vec<NotificationItem>, history: &History, tx: &Sender<Notice>) -> bool {
+    let host_label = batch_host(&items).to_string();
+    info!(host = %host_label, count = items.len(), "queueing host batch");
+    match tx.try_send(Notice::HostBatch(items)) {
+        Err(TrySendError::Closed(_)) => false,
+        Err(TrySendError::Full(Notice::HostBatch(items))) => {
+            if let Some(suppressed) = QUEUE_FULL_LOG.sample() {
+                warn!(
+                    suppressed,
+                    "notification queue full; dropping batch for host {host_label}"
+                );
+            }
+            record_dropped(history, Outcome::QueueFull, &items);
+            true
+        }
+        _ => true,
+    }
+}
+
+fn queue_item(item: Box<NotificationItem>, history: &History, tx: &Sender<Notice>) -> bool {
+    let problem = &item.problem;
+    let latency = compute_latency_ms(problem.clock);
+    let host_label = item
+        .host
+        .as_ref()
+        .map_or("<unknown>", |h| h.display_name.as_str());
+
+    info!(
+        event_id = %problem.event_id,
+        correlation_id = %item.correlation_id,
+        host = host_label,
+        severity = ?problem.severity,
+        ack_state = problem.acknowledged,
+        latency_ms = latency.unwrap_or_default(),
+        "queueing notification"
+    );
+
+    match tx.try_send(Notice::Problem(item)) {
+        Err(TrySendError::Closed(_)) => false,
+        Err(TrySendError::Full(Notice::Problem(item))) => {
+            if let Some(suppressed) = QUEUE_FULL_LOG.sample() {
+                warn!(
+                    suppressed,
+                    "notification queue full; dropping event {}", item.problem.event_id
+                );
+            }
+            history.record(Outcome::QueueFull, &item.problem, item.host.as_ref());
+            true
+        }
+        _ => true,
+    }
+}
+
+async fn fetch_rows(
+    client: &ZbxClient,
+    config: &Config,
+    proxy_hosts: Option<&mut ProxyHosts>,
+) -> Result<Vec<(Problem, Option<HostMeta>)>> {
+    client.begin_poll();
+    let problems = match proxy_hosts {
+        Some(proxy_hosts) => {
+            let host_ids = proxy_hosts.host_ids(client).await?;
+            client
+                .active_problems_on_hosts(
+                    host_ids,
+                    config.limit,
+                    config.max_pages,
+                    config.ack_filter,
+                )
+                .await?
+        }
+        None => {
+            client
+                .active_problems_paged(config.limit, config.max_pages, config.ack_filter)
+                .await?
+        }
+    };
+
+    let event_ids: Vec<String> = problems.iter().map(|p| p.event_id.clone()).collect();
+    let hosts = client.resolve_hosts(&event_ids, config.concurrency).await?;
+    let mut rows: Vec<_> = problems.into_iter().zip(hosts).collect();
+
+    if config.host_availability {
+        match client.host_availability(&unique_host_ids(&rows)).await {
+            Ok(availability) => {
+                for host in rows.iter_mut().filter_map(|(_, host)| host.as_mut()) {
+                    host.availability = host
+                        .host_id
+                        .as_ref()
+                        .and_then(|id| availability.get(id))
+                        .copied();
+                }
+            }
+            Err(err) => warn!(
+                error_code = err.code(),
+                error = %err,
+                "failed to look up host availability"
+            ),
+        }
+    }
+
+    Ok(rows)
+}
+
+fn unique_host_ids(rows: &[(Problem, Option<HostMeta>)]) -> Vec<String> {
+    rows.iter()
+        .filter_map(|(_, host)| host.as_ref()?.host_id.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
 }
 
 fn compute_latency_ms(clock: i64) -> Option<u128> {
@@ -217,135 +1343,597 @@ fn compute_latency_ms(clock: i64) -> Option<u128> {
         .map(|d| d.as_millis())
 }
 
+/// A flood-prone log line is emitted at most once per this window.
+const LOG_SAMPLE_WINDOW: Duration = Duration::from_mins(1);
+static DUPLICATE_LOG: LogSampler = LogSampler::new(LOG_SAMPLE_WINDOW);
+static RATE_LIMIT_LOG: LogSampler = LogSampler::new(LOG_SAMPLE_WINDOW);
+static QUEUE_FULL_LOG: LogSampler = LogSampler::new(LOG_SAMPLE_WINDOW);
+
 fn debug_dup(problem: &Problem) {
-    tracing::debug!(
-        event_id = %problem.event_id,
-        last_change = problem.last_change,
-        "duplicate notification skipped"
-    );
+    if let Some(suppressed) = DUPLICATE_LOG.sample() {
+        tracing::debug!(
+            event_id = %problem.event_id,
+            last_change = problem.last_change,
+            suppressed,
+            "duplicate notification skipped"
+        );
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::super::notifier::NotificationItem;
-    use super::super::rate_limit::LeakyBucket;
-    use super::poll_once;
-    use alerting::config::{Config, NotifySettings, RateLimit};
-    use alerting::types::AckFilter;
-    use alerting::zbx_client::ZbxClient;
+    use super::super::cli::Cli;
+    use super::super::notifier::Notice;
+    use super::super::poll_diff::{Change, PollDiff};
+    use super::super::simulate::Simulator;
+    use super::super::state::set_muted_until;
+    use super::{
+        MAX_HELD, PollState, build_client, diff_poll, fetch_rows, poll_once, queue_close,
+        queue_storm_progress, render_host_url, unix_now,
+    };
+    use alerting::config::{
+        AckPrompt, Config, DisplaySettings, HistoryRetention, NotifySettings, PollSummary,
+        RateLimit, StormMode, ToastGrouping,
+    };
+    use alerting::types::{AckFilter, DetailLevel, EventSource, Severity, SymptomMode};
+    use alerting::zbx_client::{HostMeta, Problem, ZbxClient};
     use async_channel::bounded;
-    use lru::LruCache;
+    use clap::Parser;
     use secrecy::SecretString;
-    use std::num::NonZeroUsize;
-    use std::time::Duration;
+    use std::collections::BTreeMap;
+    use std::time::{Duration, Instant};
     use url::Url;
     use wiremock::matchers::{body_string_contains, method};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
-    #[tokio::test]
-    async fn poll_once_skips_duplicate_events() -> Result<(), Box<dyn std::error::Error>> {
-        let server = MockServer::start().await;
-
-        Mock::given(method("POST"))
-            .and(body_string_contains("problem.get"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "jsonrpc": "2.0",
-                "result": [
-                    {
-                        "eventid": "77",
-                        "clock": "1700000000",
-                        "lastchange": "1700000001",
-                        "severity": "3",
-                        "name": "Duplicate",
-                        "acknowledged": "0"
-                    },
-                    {
-                        "eventid": "77",
-                        "clock": "1700000000",
-                        "lastchange": "1700000001",
-                        "severity": "3",
-                        "name": "Duplicate",
-                        "acknowledged": "0"
-                    }
-                ],
-                "id": 1
-            })))
-            .mount(&server)
-            .await;
-
-        Mock::given(method("POST"))
-            .and(body_string_contains("event.get"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
-                "jsonrpc": "2.0",
-                "result": [
-                    {
-                        "hosts": [
-                            { "host": "srv", "name": "Srv", "status": "0" }
-                        ]
-                    }
-                ],
-                "id": 1
-            })))
-            .mount(&server)
-            .await;
-
-        let base_url = Url::parse(&server.uri())?;
-
-        let config = Config {
+    fn test_config(base_url: Url) -> Config {
+        Config {
             base_url,
+            fallback_urls: Vec::new(),
             token: SecretString::from("token"),
             limit: 10,
+            max_pages: 1,
             concurrency: 2,
             ack_filter: AckFilter::All,
+            proxy_ids: Vec::new(),
+            host_availability: false,
+            symptoms: SymptomMode::Show,
+            event_sources: vec![EventSource::Trigger],
+            event_objects: Vec::new(),
+            service_impact: None,
+            graph: None,
             max_notif: 10,
             queue_capacity: 4,
             dedup_cache_size: 8,
+            notify_cooldown: None,
             rate_limit: RateLimit {
                 max_events: 10,
-                per: Duration::from_secs(60),
+                per: Duration::from_mins(1),
             },
+            storm: None,
             poll_interval: Duration::from_millis(10),
             open_url_fmt: None,
+            host_url_fmt: None,
+            state_file: None,
+            history_file: None,
+            status_file: None,
+            history_retention: HistoryRetention {
+                max_age: None,
+                max_rows: None,
+            },
+            watchdog_deadline: None,
             notify: NotifySettings {
                 appname: "test".into(),
                 sticky: false,
                 timeout: None,
                 default_timeout: false,
+                resident: false,
+                close_on_ack: true,
+                comment_updates: false,
                 icon: None,
+                acked_icon: None,
+                sound_file: None,
                 open_label: "Open".into(),
                 notify_acked: true,
+                schedule: None,
+                hero_image: None,
+                attribution: None,
+                group_by: ToastGrouping::Host,
+                batch_by_host: false,
+                poll_summary: PollSummary::Off,
+                severity_emoji: false,
+                desktop_entry: None,
+                prompt_command: Vec::new(),
+                ack_prompt: AckPrompt::Always,
+                confirm_ack: false,
+                confirm_command: Vec::new(),
+                rank_action: false,
+                comment_action: false,
+                host_ack_action: false,
+                ack_min_severity: Severity::Info,
+                mute_floor: Severity::Disaster,
+                mute_action: None,
+                max_body_lines: None,
+                max_body_chars: None,
+                summary_prefix: String::new(),
+                summary_suffix: String::new(),
+                severities: BTreeMap::new(),
+                detail: DetailLevel::Normal,
+                heartbeat: None,
+                kdeconnect: None,
             },
+            display: DisplaySettings::default(),
             http_connect_timeout: Duration::from_millis(100),
             http_request_timeout: Duration::from_millis(200),
-        };
+            http_pool_idle_timeout: Duration::from_secs(30),
+            http_pool_max_idle_per_host: None,
+            http_tcp_keepalive: None,
+            max_requests_per_second: None,
+            max_in_flight: None,
+            host_miss_ttl: Duration::ZERO,
+            log_rpc: false,
+        }
+    }
 
-        let client = ZbxClient::new(
+    /// Client of the mock server, or of nothing, behind `config`.
+    fn client(config: &Config) -> alerting::Result<ZbxClient> {
+        ZbxClient::new(
             config.base_url.clone(),
             config.token.clone(),
             config.http_request_timeout,
             config.http_connect_timeout,
             true,
-        )?;
+        )
+    }
 
-        let (tx, rx) = bounded::<NotificationItem>(4);
-        let cache_size = NonZeroUsize::new(config.dedup_cache_size).ok_or_else(|| {
-            std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                "dedup cache size must be > 0",
-            )
-        })?;
-        let mut dedup = LruCache::new(cache_size);
-        let mut bucket = LeakyBucket::new(10, Duration::from_secs(60));
+    /// JSON-RPC answer carrying `result`.
+    fn rpc(result: &serde_json::Value) -> ResponseTemplate {
+        ResponseTemplate::new(200)
+            .set_body_json(serde_json::json!({ "jsonrpc": "2.0", "result": result, "id": 1 }))
+    }
 
-        poll_once(&client, &config, &mut dedup, &mut bucket, &tx).await?;
+    /// `problem.get` answered with `problems`; mount it, bounded or not.
+    fn problem_get(problems: &serde_json::Value) -> Mock {
+        Mock::given(method("POST"))
+            .and(body_string_contains("problem.get"))
+            .respond_with(rpc(problems))
+    }
+
+    /// `event.get` host lookups answered with `events`.
+    fn event_get(events: &serde_json::Value) -> Mock {
+        Mock::given(method("POST"))
+            .and(body_string_contains("event.get"))
+            .respond_with(rpc(events))
+    }
 
+    /// Notices queued by one poll of `simulator`, or of the server behind
+    /// `config` without one, keeping `state` across calls.
+    async fn poll_with(
+        config: &Config,
+        state: &mut PollState,
+        simulator: Option<&mut Simulator>,
+        catch_up: bool,
+    ) -> Result<Vec<Notice>, Box<dyn std::error::Error>> {
+        let (tx, rx) = bounded::<Notice>(64);
+        poll_once(&client(config)?, simulator, config, state, catch_up, &tx).await?;
         tx.close();
-        let mut items = Vec::new();
-        while let Ok(item) = rx.try_recv() {
-            items.push(item);
+        Ok(std::iter::from_fn(|| rx.try_recv().ok()).collect())
+    }
+
+    /// Notices queued by one poll of `simulator` from a fresh state.
+    async fn poll(
+        config: &Config,
+        mut simulator: Simulator,
+    ) -> Result<Vec<Notice>, Box<dyn std::error::Error>> {
+        poll_with(
+            config,
+            &mut PollState::new(config),
+            Some(&mut simulator),
+            false,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn rank_action_fetches_causes_with_default_symptoms()
+    -> Result<(), Box<dyn std::error::Error>> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("problem.get"))
+            .and(body_string_contains("cause_eventid"))
+            .respond_with(rpc(&serde_json::json!([{
+                "eventid": "12",
+                "clock": "1700000000",
+                "severity": "4",
+                "name": "Ping loss",
+                "cause_eventid": "11"
+            }])))
+            .mount(&server)
+            .await;
+        let mut config = test_config(Url::parse(&server.uri())?);
+        assert_eq!(config.symptoms, SymptomMode::Show);
+        config.notify.rank_action = true;
+        let cli = Cli::try_parse_from(["alerting", "--insecure"])?;
+
+        let client = build_client(&cli, &config)?;
+        let problems = client.active_problems(10, AckFilter::All).await?;
+
+        assert_eq!(problems[0].cause_event_id.as_deref(), Some("11"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn proxy_filter_asks_for_the_problems_of_its_hosts()
+    -> Result<(), Box<dyn std::error::Error>> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("\"proxyids\":[\"10500\"]"))
+            .respond_with(rpc(&serde_json::json!([{ "hostid": "10084" }])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("problem.get"))
+            .and(body_string_contains("\"hostids\":[\"10084\"]"))
+            .respond_with(rpc(&serde_json::json!([{
+                "eventid": "12",
+                "clock": "1700000000",
+                "severity": "4",
+                "name": "Disk full"
+            }])))
+            .expect(2)
+            .mount(&server)
+            .await;
+        event_get(&serde_json::json!([{
+            "hosts": [{ "hostid": "10084", "host": "db01", "name": "db01", "status": "0" }]
+        }]))
+        .mount(&server)
+        .await;
+        let mut config = test_config(Url::parse(&server.uri())?);
+        config.proxy_ids = vec!["10500".to_string()];
+        let client = client(&config)?;
+        let mut state = PollState::new(&config);
+
+        // The proxy's hosts are looked up once, not every poll.
+        for _ in 0..2 {
+            let rows = fetch_rows(&client, &config, state.proxy_hosts.as_mut()).await?;
+            assert_eq!(rows.len(), 1);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn host_url_fills_id_and_encoded_name() {
+        let db = HostMeta {
+            host_id: Some("10084".into()),
+            host: Some("db 01".into()),
+            display_name: "DB 01".into(),
+            status: None,
+            availability: None,
+        };
+        assert_eq!(
+            render_host_url("https://zbx/host?id={hostid}&name={host}", &db).as_deref(),
+            Some("https://zbx/host?id=10084&name=db+01")
+        );
+
+        let unknown = HostMeta {
+            host_id: None,
+            ..db
+        };
+        assert_eq!(
+            render_host_url("https://zbx/host?id={hostid}", &unknown),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn vanished_problems_are_checked_before_counting_as_resolved()
+    -> Result<(), Box<dyn std::error::Error>> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("\"eventids\":["))
+            .respond_with(rpc(&serde_json::json!([
+                { "eventid": "2", "clock": "1700000000", "severity": "3", "name": "Swap low" }
+            ])))
+            .mount(&server)
+            .await;
+        let path =
+            std::env::temp_dir().join(format!("alerting-history-{}.jsonl", uuid::Uuid::new_v4()));
+        let mut config = test_config(Url::parse(&server.uri())?);
+        config.history_file = Some(path.clone());
+        let client = client(&config)?;
+        let problem = |event_id: &str| Problem {
+            event_id: event_id.to_string(),
+            clock: 1_700_000_000,
+            last_change: 1_700_000_000,
+            name: format!("problem {event_id}"),
+            severity: Severity::Average,
+            acknowledged: false,
+            cause_event_id: None,
+            trigger_id: None,
+            tags: Vec::new(),
+        };
+        let mut state = PollState::new(&config);
+
+        diff_poll(
+            &client,
+            &mut state,
+            &[(problem("1"), None), (problem("2"), None)],
+        )
+        .await;
+        let diff = diff_poll(&client, &mut state, &[]).await;
+
+        let written = std::fs::read_to_string(&path)?;
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<&str> = written.lines().collect();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("\"outcome\":\"resolved\""));
+        assert!(lines[0].contains("\"event_id\":\"1\""));
+        assert_eq!(diff.resolved.len(), 1);
+        assert_eq!(state.active.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn resolved_and_newly_acked_toasts_are_closed() {
+        let problem = |event_id: &str, acknowledged: bool| Problem {
+            event_id: event_id.to_string(),
+            clock: 1_700_000_000,
+            last_change: 1_700_000_000,
+            name: format!("problem {event_id}"),
+            severity: Severity::High,
+            acknowledged,
+            cause_event_id: None,
+            trigger_id: None,
+            tags: Vec::new(),
+        };
+        let diff = PollDiff {
+            appeared: vec![(problem("1", false), None)],
+            resolved: vec![(problem("2", false), None)],
+            changed: vec![
+                Change {
+                    before: problem("3", false),
+                    after: (problem("3", true), None),
+                },
+                Change {
+                    before: problem("4", true),
+                    after: (problem("4", false), None),
+                },
+            ],
+        };
+        let (tx, rx) = bounded(4);
+
+        queue_close(&diff, true, &tx);
+        queue_close(&PollDiff::default(), true, &tx);
+        queue_close(&diff, false, &tx);
+
+        match rx.try_recv() {
+            Ok(Notice::Close(event_ids)) => assert_eq!(event_ids, ["2", "3"]),
+            _ => panic!("expected a close notice"),
+        }
+        match rx.try_recv() {
+            Ok(Notice::Close(event_ids)) => assert_eq!(event_ids, ["3"]),
+            _ => panic!("expected a close notice for the ack"),
+        }
+        assert!(rx.is_empty());
+    }
+
+    #[tokio::test]
+    async fn poll_once_skips_duplicate_events() -> Result<(), Box<dyn std::error::Error>> {
+        let server = MockServer::start().await;
+        let duplicate = serde_json::json!({
+            "eventid": "77",
+            "clock": "1700000000",
+            "lastchange": "1700000001",
+            "severity": "3",
+            "name": "Duplicate",
+            "acknowledged": "0"
+        });
+        problem_get(&serde_json::json!([duplicate, duplicate]))
+            .mount(&server)
+            .await;
+        event_get(&serde_json::json!([{
+            "hosts": [{ "host": "srv", "name": "Srv", "status": "0" }]
+        }]))
+        .mount(&server)
+        .await;
+        let config = test_config(Url::parse(&server.uri())?);
+
+        let notices = poll_with(&config, &mut PollState::new(&config), None, false).await?;
+
+        match notices.as_slice() {
+            [Notice::Problem(item)] => assert_eq!(item.problem.event_id, "77"),
+            _ => panic!("expected a single problem"),
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn poll_once_holds_changes_during_cooldown() -> Result<(), Box<dyn std::error::Error>> {
+        let server = MockServer::start().await;
+        let flapping = |last_change: &str| {
+            problem_get(&serde_json::json!([{
+                "eventid": "88",
+                "clock": "1700000000",
+                "lastchange": last_change,
+                "severity": "4",
+                "name": "Flapping",
+                "acknowledged": "0"
+            }]))
+        };
+        flapping("1700000001").up_to_n_times(1).mount(&server).await;
+        flapping("1700000002").mount(&server).await;
+        event_get(&serde_json::json!([])).mount(&server).await;
+
+        let mut config = test_config(Url::parse(&server.uri())?);
+        config.notify_cooldown = Some(Duration::from_mins(10));
+        let mut state = PollState::new(&config);
+
+        let mut shown = Vec::new();
+        for poll in 0..3 {
+            if poll == 2 {
+                // The cooldown is over.
+                let earlier = Instant::now()
+                    .checked_sub(Duration::from_mins(11))
+                    .ok_or("clock too young")?;
+                state.queued_at.put("88".to_string(), earlier);
+            }
+            for notice in poll_with(&config, &mut state, None, false).await? {
+                if let Notice::Problem(item) = notice {
+                    shown.push((poll, item.problem.last_change));
+                }
+            }
+        }
+        assert_eq!(shown, [(0, 1_700_000_001), (2, 1_700_000_002)]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn poll_once_counts_other_problems_on_host() -> Result<(), Box<dyn std::error::Error>> {
+        let config = test_config(Url::parse("https://zabbix.invalid")?);
+        let simulator = Simulator::new(3, Vec::new(), vec!["lab".to_string()]);
+
+        let notices = poll(&config, simulator).await?;
+
+        assert_eq!(notices.len(), 3);
+        for notice in notices {
+            let Notice::Problem(item) = notice else {
+                panic!("expected problems only");
+            };
+            assert_eq!(item.others_on_host, 2);
+            assert_eq!(item.unacked_on_host, 3);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn poll_once_drops_muted_severities() -> Result<(), Box<dyn std::error::Error>> {
+        let mut config = test_config(Url::parse("https://zabbix.invalid")?);
+        let path =
+            std::env::temp_dir().join(format!("alerting-state-{}.json", uuid::Uuid::new_v4()));
+        set_muted_until(&path, Some(unix_now() + 3600))?;
+        config.state_file = Some(path.clone());
+        config.notify.mute_floor = Severity::High;
+
+        for (severity, expected) in [(Severity::Warning, 0), (Severity::High, 3)] {
+            let notices = poll(&config, Simulator::new(3, vec![severity], Vec::new())).await?;
+            assert_eq!(notices.len(), expected, "{severity}");
+        }
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn poll_once_batches_problems_per_host() -> Result<(), Box<dyn std::error::Error>> {
+        let mut config = test_config(Url::parse("https://zabbix.invalid")?);
+        config.notify.batch_by_host = true;
+        let simulator = Simulator::new(3, Vec::new(), vec!["lab".to_string()]);
+
+        match poll(&config, simulator).await?.as_slice() {
+            [Notice::HostBatch(items)] => assert_eq!(items.len(), 3),
+            _ => panic!("expected a single host batch"),
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn poll_once_holds_items_during_storm() -> Result<(), Box<dyn std::error::Error>> {
+        let mut config = test_config(Url::parse("https://zabbix.invalid")?);
+        config.storm = Some(StormMode {
+            threshold: 2,
+            window: Duration::from_mins(1),
+        });
+        let mut simulator = Simulator::new(3, Vec::new(), vec!["lab".to_string()]);
+        let mut state = PollState::new(&config);
+
+        let notices = poll_with(&config, &mut state, Some(&mut simulator), false).await?;
+
+        match notices.as_slice() {
+            [
+                Notice::Storm {
+                    problems, hosts, ..
+                },
+            ] => assert_eq!((*problems, *hosts), (3, 1)),
+            _ => panic!("expected a storm notification"),
+        }
+        assert_eq!(state.held.len(), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn storm_progress_counts_resolved_held_problems() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let mut config = test_config(Url::parse("https://zabbix.invalid")?);
+        config.storm = Some(StormMode {
+            threshold: 2,
+            window: Duration::from_mins(1),
+        });
+        let mut simulator = Simulator::new(3, Vec::new(), vec!["lab".to_string()]);
+        let mut state = PollState::new(&config);
+        poll_with(&config, &mut state, Some(&mut simulator), false).await?;
+
+        let held = state.held[0].problem.clone();
+        let diff = PollDiff {
+            resolved: vec![(held, None)],
+            ..PollDiff::default()
+        };
+        let (tx, rx) = bounded::<Notice>(4);
+        queue_storm_progress(&mut state, &diff, &tx);
+        queue_storm_progress(&mut state, &PollDiff::default(), &tx);
+
+        match rx.try_recv() {
+            Ok(Notice::Storm {
+                problems, resolved, ..
+            }) => assert_eq!((resolved, problems), (1, 3)),
+            _ => panic!("expected a storm refresh"),
+        }
+        assert!(rx.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn storm_keeps_at_most_max_held_items() -> Result<(), Box<dyn std::error::Error>> {
+        let mut config = test_config(Url::parse("https://zabbix.invalid")?);
+        config.max_notif = 100;
+        config.storm = Some(StormMode {
+            threshold: 2,
+            window: Duration::from_mins(1),
+        });
+        let mut simulator = Simulator::new(100, Vec::new(), vec!["lab".to_string()]);
+        let mut state = PollState::new(&config);
+
+        let mut last = None;
+        for _ in 0..6 {
+            let notices = poll_with(&config, &mut state, Some(&mut simulator), false).await?;
+            last = notices.into_iter().last().or(last);
+        }
+
+        assert_eq!(state.held.len(), MAX_HELD);
+        assert_eq!(state.held_over, 600 - MAX_HELD);
+        match last {
+            Some(Notice::Storm { problems, .. }) => assert_eq!(problems, 600),
+            _ => panic!("expected a storm notification"),
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn poll_once_batches_catch_up_into_digest() -> Result<(), Box<dyn std::error::Error>> {
+        let config = test_config(Url::parse("https://zabbix.invalid")?);
+        let mut simulator = Simulator::new(3, Vec::new(), Vec::new());
+
+        let notices = poll_with(
+            &config,
+            &mut PollState::new(&config),
+            Some(&mut simulator),
+            true,
+        )
+        .await?;
+
+        match notices.as_slice() {
+            [Notice::Digest(items)] => assert_eq!(items.len(), 3),
+            _ => panic!("expected a single catch-up digest"),
         }
-        assert_eq!(items.len(), 1);
-        assert_eq!(items[0].problem.event_id, "77");
         Ok(())
     }
 }
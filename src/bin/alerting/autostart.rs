@@ -0,0 +1,270 @@
+//! `install-autostart` / `uninstall-autostart`: start the daemon at login.
+//!
+//! Linux gets a user systemd unit derived from the packaged one; Windows gets
+//! a shortcut in the user's Startup folder.
+
+use std::path::{Path, PathBuf};
+
+use alerting::Result;
+use alerting::error::Error as AlertError;
+
+/// What the autostart entry should launch.
+#[cfg_attr(not(any(target_os = "linux", target_os = "windows")), allow(dead_code))]
+pub struct Launch {
+    pub exe: PathBuf,
+    pub config: Option<PathBuf>,
+    pub profile: Option<String>,
+}
+
+impl Launch {
+    /// The running binary, with the configuration file resolved to an
+    /// absolute path when it exists.
+    pub fn current(config: &Path, profile: Option<&str>) -> Result<Self> {
+        let exe = std::env::current_exe()
+            .map_err(|err| autostart_error("cannot locate the alerting binary", &err))?;
+        Ok(Self {
+            exe,
+            config: config.canonicalize().ok(),
+            profile: profile.map(str::to_string),
+        })
+    }
+}
+
+fn autostart_error(context: &str, err: &dyn std::fmt::Display) -> AlertError {
+    AlertError::Autostart(format!("{context}: {err}"))
+}
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn run(program: &str, args: &[&str]) -> Result<()> {
+    let status = std::process::Command::new(program)
+        .args(args)
+        .status()
+        .map_err(|err| autostart_error(&format!("failed to run {program}"), &err))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(AlertError::Autostart(format!(
+            "`{program} {}` exited with {status}",
+            args.join(" ")
+        )))
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use systemd::{install, uninstall};
+
+#[cfg(target_os = "windows")]
+pub use startup_folder::{install, uninstall};
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub fn install(_launch: &Launch) -> Result<()> {
+    Err(unsupported())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub fn uninstall() -> Result<()> {
+    Err(unsupported())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn unsupported() -> AlertError {
+    AlertError::Autostart("autostart is only supported on Linux and Windows".to_string())
+}
+
+#[cfg(target_os = "linux")]
+mod systemd {
+    use std::env;
+    use std::path::PathBuf;
+
+    use alerting::Result;
+    use alerting::error::Error as AlertError;
+
+    use super::{Launch, autostart_error, run};
+
+    const UNIT_NAME: &str = "alerting.service";
+    const UNIT_TEMPLATE: &str = include_str!("../../../packaging/systemd/user/alerting.service");
+    const DEFAULT_CONFIG_ARG: &str = "%h/.config/alerting/config.toml";
+
+    fn unit_path() -> Result<PathBuf> {
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .ok_or_else(|| {
+                AlertError::Autostart("neither XDG_CONFIG_HOME nor HOME is set".to_string())
+            })?;
+        Ok(config_home.join("systemd/user").join(UNIT_NAME))
+    }
+
+    /// Quote a word for a unit file `ExecStart=` line.
+    fn quote(word: &str) -> String {
+        let escaped = word
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('%', "%%");
+        format!("\"{escaped}\"")
+    }
+
+    pub(super) fn render_unit(launch: &Launch) -> String {
+        let mut exec = format!(
+            "ExecStart={} --config {}",
+            quote(&launch.exe.to_string_lossy()),
+            launch.config.as_ref().map_or_else(
+                || DEFAULT_CONFIG_ARG.to_string(),
+                |path| quote(&path.to_string_lossy())
+            )
+        );
+        if let Some(profile) = &launch.profile {
+            exec.push_str(" --profile ");
+            exec.push_str(&quote(profile));
+        }
+        UNIT_TEMPLATE
+            .lines()
+            .map(|line| {
+                if line.starts_with("ExecStart=") {
+                    exec.as_str()
+                } else {
+                    line
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+            + "\n"
+    }
+
+    pub fn install(launch: &Launch) -> Result<()> {
+        let path = unit_path()?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|err| {
+                autostart_error(&format!("cannot create {}", dir.display()), &err)
+            })?;
+        }
+        std::fs::write(&path, render_unit(launch))
+            .map_err(|err| autostart_error(&format!("cannot write {}", path.display()), &err))?;
+        run("systemctl", &["--user", "daemon-reload"])?;
+        run("systemctl", &["--user", "enable", "--now", UNIT_NAME])?;
+        println!("installed {} and started {UNIT_NAME}", path.display());
+        Ok(())
+    }
+
+    pub fn uninstall() -> Result<()> {
+        let path = unit_path()?;
+        if !path.exists() {
+            println!("no autostart unit at {}", path.display());
+            return Ok(());
+        }
+        run("systemctl", &["--user", "disable", "--now", UNIT_NAME])?;
+        std::fs::remove_file(&path)
+            .map_err(|err| autostart_error(&format!("cannot remove {}", path.display()), &err))?;
+        run("systemctl", &["--user", "daemon-reload"])?;
+        println!("stopped {UNIT_NAME} and removed {}", path.display());
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod startup_folder {
+    use std::env;
+    use std::path::PathBuf;
+
+    use alerting::Result;
+    use alerting::error::Error as AlertError;
+
+    use super::{Launch, autostart_error, run};
+
+    fn shortcut_path() -> Result<PathBuf> {
+        let appdata = env::var_os("APPDATA")
+            .ok_or_else(|| AlertError::Autostart("APPDATA is not set".to_string()))?;
+        Ok(PathBuf::from(appdata)
+            .join(r"Microsoft\Windows\Start Menu\Programs\Startup")
+            .join("Alerting.lnk"))
+    }
+
+    /// Quote a string for a single-quoted `PowerShell` literal.
+    fn ps_quote(value: &str) -> String {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+
+    fn arguments(launch: &Launch) -> String {
+        let mut args = Vec::new();
+        if let Some(config) = &launch.config {
+            args.push(format!("--config \"{}\"", config.display()));
+        }
+        if let Some(profile) = &launch.profile {
+            args.push(format!("--profile \"{profile}\""));
+        }
+        args.join(" ")
+    }
+
+    pub fn install(launch: &Launch) -> Result<()> {
+        let path = shortcut_path()?;
+        let workdir = launch
+            .config
+            .as_ref()
+            .and_then(|config| config.parent())
+            .or_else(|| launch.exe.parent())
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default();
+        // WScript.Shell is the only way to write a .lnk without raw COM calls.
+        let script = format!(
+            "$s = (New-Object -ComObject WScript.Shell).CreateShortcut({}); \
+             $s.TargetPath = {}; $s.Arguments = {}; $s.WorkingDirectory = {}; \
+             $s.WindowStyle = 7; $s.Save()",
+            ps_quote(&path.display().to_string()),
+            ps_quote(&launch.exe.display().to_string()),
+            ps_quote(&arguments(launch)),
+            ps_quote(&workdir),
+        );
+        run(
+            "powershell",
+            &["-NoProfile", "-NonInteractive", "-Command", &script],
+        )?;
+        println!(
+            "installed {}; Alerting will start at next login",
+            path.display()
+        );
+        Ok(())
+    }
+
+    pub fn uninstall() -> Result<()> {
+        let path = shortcut_path()?;
+        if !path.exists() {
+            println!("no autostart shortcut at {}", path.display());
+            return Ok(());
+        }
+        std::fs::remove_file(&path)
+            .map_err(|err| autostart_error(&format!("cannot remove {}", path.display()), &err))?;
+        println!("removed {}", path.display());
+        Ok(())
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::Launch;
+    use super::systemd::render_unit;
+
+    #[test]
+    fn unit_launches_current_binary_with_config() {
+        let unit = render_unit(&Launch {
+            exe: PathBuf::from("/opt/alerting 1/alerting"),
+            config: Some(PathBuf::from("/etc/alerting/100%.toml")),
+            profile: Some("work".to_string()),
+        });
+        assert!(unit.contains(
+            "ExecStart=\"/opt/alerting 1/alerting\" --config \"/etc/alerting/100%%.toml\" --profile \"work\"\n"
+        ));
+        assert!(unit.contains("WantedBy=default.target"));
+
+        let unit = render_unit(&Launch {
+            exe: PathBuf::from("/usr/bin/alerting"),
+            config: None,
+            profile: None,
+        });
+        assert!(unit.contains(
+            "ExecStart=\"/usr/bin/alerting\" --config %h/.config/alerting/config.toml\n"
+        ));
+    }
+}
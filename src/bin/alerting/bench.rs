@@ -0,0 +1,205 @@
+//! `alerting bench`: timings against an in-process mock Zabbix server, to
+//! guide the choice of `concurrency` and HTTP timeouts.
+
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use alerting::Result;
+use alerting::error::{ConfigError, Error as AlertError};
+use alerting::types::AckFilter;
+use alerting::zbx_client::ZbxClient;
+use secrecy::SecretString;
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use url::Url;
+
+const CONCURRENCY_LEVELS: &[usize] = &[1, 2, 4, 8, 16];
+
+pub struct BenchOptions {
+    pub iterations: usize,
+    pub problems: usize,
+    pub latency: Duration,
+}
+
+pub async fn run(options: &BenchOptions) -> Result<()> {
+    let problems_body = problems_body(options.problems);
+    let addr = spawn_mock(problems_body.clone(), options.latency).await?;
+    let base = Url::parse(&format!("http://{addr}/api_jsonrpc.php"))
+        .map_err(|err| AlertError::from(ConfigError::Other(err.to_string())))?;
+    let client = ZbxClient::new(
+        base,
+        SecretString::from("bench"),
+        Duration::from_secs(10),
+        Duration::from_secs(2),
+        true,
+    )?;
+    let iterations = options.iterations.max(1);
+
+    println!(
+        "mock server: {} problems, {} ms latency per request",
+        options.problems,
+        options.latency.as_millis()
+    );
+
+    let mut samples = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let started = Instant::now();
+        let problems = client.active_problems(1_000, AckFilter::All).await?;
+        let ids: Vec<String> = problems.into_iter().map(|p| p.event_id).collect();
+        client.resolve_hosts(&ids, 4).await?;
+        samples.push(started.elapsed());
+    }
+    samples.sort_unstable();
+    println!(
+        "poll (problem.get + hosts, concurrency 4): p50 {} ms, p95 {} ms, max {} ms",
+        percentile(&samples, 50).as_millis(),
+        percentile(&samples, 95).as_millis(),
+        samples.last().copied().unwrap_or_default().as_millis()
+    );
+
+    let ids: Vec<String> = (1..=options.problems).map(|i| i.to_string()).collect();
+    for &concurrency in CONCURRENCY_LEVELS {
+        let started = Instant::now();
+        client.resolve_hosts(&ids, concurrency).await?;
+        let elapsed = started.elapsed();
+        println!(
+            "host resolution, concurrency {concurrency:>2}: {:.0} events/s ({} ms for {})",
+            per_second(ids.len(), elapsed),
+            elapsed.as_millis(),
+            ids.len()
+        );
+    }
+
+    let started = Instant::now();
+    for _ in 0..iterations {
+        let value: Value = serde_json::from_slice(&problems_body)
+            .map_err(|err| AlertError::from(ConfigError::Other(err.to_string())))?;
+        let _ = serde_json::to_vec(&value);
+    }
+    let elapsed = started.elapsed();
+    println!(
+        "JSON decode + encode of problem.get ({} bytes): {} µs per round trip",
+        problems_body.len(),
+        elapsed.as_micros() / u128::try_from(iterations).unwrap_or(1)
+    );
+    Ok(())
+}
+
+fn percentile(sorted: &[Duration], pct: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let idx = (sorted.len() * pct / 100).min(sorted.len() - 1);
+    sorted[idx]
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn per_second(count: usize, elapsed: Duration) -> f64 {
+    count as f64 / elapsed.as_secs_f64().max(f64::EPSILON)
+}
+
+fn problems_body(count: usize) -> Vec<u8> {
+    let problems: Vec<Value> = (1..=count)
+        .map(|i| {
+            json!({
+                "eventid": i.to_string(),
+                "clock": "1700000000",
+                "lastchange": "1700000000",
+                "severity": "4",
+                "name": format!("Benchmark problem {i}"),
+                "acknowledged": "0"
+            })
+        })
+        .collect();
+    json!({ "jsonrpc": "2.0", "result": problems, "id": 1 })
+        .to_string()
+        .into_bytes()
+}
+
+async fn spawn_mock(problems_body: Vec<u8>, latency: Duration) -> Result<SocketAddr> {
+    let io_err = |err: std::io::Error| AlertError::from(ConfigError::Other(err.to_string()));
+    let listener = TcpListener::bind("127.0.0.1:0").await.map_err(io_err)?;
+    let addr = listener.local_addr().map_err(io_err)?;
+    let hosts_body = json!({
+        "jsonrpc": "2.0",
+        "result": [{ "hosts": [{ "host": "bench", "name": "Bench host", "status": "0" }] }],
+        "id": 1
+    })
+    .to_string()
+    .into_bytes();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let problems = problems_body.clone();
+            let hosts = hosts_body.clone();
+            tokio::spawn(async move {
+                let _ = serve(stream, &problems, &hosts, latency).await;
+            });
+        }
+    });
+    Ok(addr)
+}
+
+/// Minimal keep-alive HTTP/1.1 loop answering JSON-RPC calls by method.
+async fn serve(
+    stream: TcpStream,
+    problems: &[u8],
+    hosts: &[u8],
+    latency: Duration,
+) -> std::io::Result<()> {
+    let mut stream = BufReader::new(stream);
+    loop {
+        let mut content_length = 0;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if stream.read_line(&mut line).await? == 0 {
+                return Ok(());
+            }
+            let header = line.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':')
+                && name.eq_ignore_ascii_case("content-length")
+            {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+        let mut request = vec![0; content_length];
+        stream.read_exact(&mut request).await?;
+
+        let request = String::from_utf8_lossy(&request);
+        let body: &[u8] = if request.contains("problem.get") {
+            problems
+        } else if request.contains("event.get") {
+            hosts
+        } else {
+            br#"{"jsonrpc":"2.0","result":{},"id":1}"#
+        };
+        tokio::time::sleep(latency).await;
+
+        let mut response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(body);
+        stream.get_mut().write_all(&response).await?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::percentile;
+    use std::time::Duration;
+
+    #[test]
+    fn percentile_picks_sorted_sample() {
+        let samples: Vec<Duration> = (1..=10).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&samples, 50), Duration::from_millis(6));
+        assert_eq!(percentile(&samples, 95), Duration::from_millis(10));
+        assert_eq!(percentile(&[], 95), Duration::ZERO);
+    }
+}
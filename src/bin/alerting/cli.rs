@@ -1,17 +1,35 @@
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::time::Duration;
 
-use clap::{ArgAction, Parser};
+use alerting::config::ConfigFormat;
+use alerting::types::Severity;
+use clap::{ArgAction, Parser, Subcommand};
 use humantime::parse_duration;
 
+use super::ack::SeverityFilter;
+use super::history::ExportFormat;
+use super::rank::RankKind;
+
 #[allow(clippy::struct_excessive_bools)]
 #[derive(Parser, Debug)]
 #[command(author, version, about = "Alerting bridge for Zabbix", long_about = None)]
 pub struct Cli {
-    /// Chemin du fichier de configuration TOML.
-    #[arg(long, value_name = "PATH")]
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Chemin du fichier de configuration (TOML, YAML ou JSON).
+    #[arg(long, value_name = "PATH", global = true)]
     pub config: Option<PathBuf>,
 
+    /// Force le format du fichier de configuration (toml, yaml, json) au lieu de l'extension.
+    #[arg(long, value_name = "FORMAT", value_parser = ConfigFormat::from_str, global = true)]
+    pub config_format: Option<ConfigFormat>,
+
+    /// Profil nommé de la configuration (`[profiles.<nom>]`), sinon `ALERTING_PROFILE`.
+    #[arg(long, value_name = "NOM", global = true)]
+    pub profile: Option<String>,
+
     /// Exécute une seule itération de poll/push puis quitte.
     #[arg(long, action = ArgAction::SetTrue)]
     pub once: bool,
@@ -32,6 +50,44 @@ pub struct Cli {
     #[arg(long, action = ArgAction::SetTrue)]
     pub dry_run: bool,
 
+    /// Enregistre les réponses JSON-RPC brutes de chaque poll dans ce dossier.
+    #[arg(long, value_name = "DIR", conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+
+    /// Logue les requêtes et réponses JSON-RPC complètes au niveau debug,
+    /// jeton et mots de passe masqués.
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub log_rpc: bool,
+
+    /// Rejoue les réponses enregistrées avec `--record`, sans accès réseau.
+    #[arg(long, value_name = "DIR")]
+    pub replay: Option<PathBuf>,
+
+    /// Génère des problèmes synthétiques au lieu d'interroger Zabbix.
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with_all = ["record", "replay"])]
+    pub simulate: bool,
+
+    /// Nombre de problèmes simulés par poll.
+    #[arg(long, value_name = "N", default_value_t = 1, requires = "simulate")]
+    pub sim_rate: u32,
+
+    /// Sévérités simulées, séparées par des virgules (défaut : toutes).
+    #[arg(long, value_name = "LISTE", value_delimiter = ',', value_parser = Severity::from_str, requires = "simulate")]
+    pub sim_severities: Vec<Severity>,
+
+    /// Hôtes simulés, séparés par des virgules.
+    #[arg(
+        long,
+        value_name = "LISTE",
+        value_delimiter = ',',
+        requires = "simulate"
+    )]
+    pub sim_hosts: Vec<String>,
+
+    /// Écrit le PID du démon dans ce fichier, supprimé à l'arrêt.
+    #[arg(long, value_name = "PATH")]
+    pub pid_file: Option<PathBuf>,
+
     /// Utilise un layer JSON pour les logs (`--features json-logs`).
     #[arg(long, action = ArgAction::SetTrue)]
     pub json_logs: bool,
@@ -41,11 +97,151 @@ pub struct Cli {
     pub log_filter: Option<String>,
 
     /// Envoie une notification de test (Windows uniquement) et quitte.
-    #[cfg(target_os = "windows")]
+    #[cfg(all(target_os = "windows", feature = "windows-toasts"))]
     #[arg(long, value_name = "TEXTE")]
     pub test_toast: Option<String>,
 }
 
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Inspection de la configuration.
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+    /// Mesure latence de poll, débit de résolution d'hôtes et coût JSON contre un serveur simulé.
+    Bench {
+        /// Nombre d'itérations par mesure.
+        #[arg(long, default_value_t = 20)]
+        iterations: usize,
+        /// Nombre de problèmes renvoyés par le serveur simulé.
+        #[arg(long, default_value_t = 50)]
+        problems: usize,
+        /// Latence simulée par requête (ex. "5ms").
+        #[arg(long, value_parser = parse_duration, default_value = "5ms")]
+        latency: Duration,
+    },
+    /// Acquitte des problèmes par identifiant d'événement ou par hôte.
+    Ack {
+        /// Identifiants des événements à acquitter.
+        #[arg(
+            value_name = "EVENTID",
+            required_unless_present_any = ["host", "severity", "older_than"],
+            conflicts_with_all = ["host", "severity", "older_than"]
+        )]
+        event_ids: Vec<String>,
+        /// Acquitte les problèmes non acquittés de cet hôte (nom technique ou visible).
+        #[arg(long, value_name = "NOM")]
+        host: Option<String>,
+        /// Avec --host, acquitte tous ses problèmes même s'il en a plusieurs.
+        #[arg(long, action = ArgAction::SetTrue, requires = "host")]
+        all: bool,
+        /// Sévérités à acquitter : une sévérité (« high ») ou une borne (« <=warning », « >=high »).
+        #[arg(long, value_name = "FILTRE", value_parser = SeverityFilter::from_str)]
+        severity: Option<SeverityFilter>,
+        /// N'acquitte que les problèmes apparus avant cette durée (ex. "1d").
+        #[arg(long, value_name = "DURÉE", value_parser = parse_duration)]
+        older_than: Option<Duration>,
+        /// Message joint à l'acquittement.
+        #[arg(long, short, value_name = "TEXTE")]
+        message: Option<String>,
+        /// Liste les problèmes qui seraient acquittés, sans les acquitter.
+        #[arg(long, action = ArgAction::SetTrue)]
+        dry_run: bool,
+    },
+    /// Ajoute un message à un événement sans l'acquitter.
+    Comment {
+        /// Identifiant de l'événement à commenter.
+        #[arg(value_name = "EVENTID")]
+        event_id: String,
+        /// Message ajouté à l'événement.
+        #[arg(value_name = "MESSAGE")]
+        message: String,
+    },
+    /// Classe un événement comme cause, ou comme symptôme d'une autre (Zabbix 6.4+).
+    Rank {
+        /// Identifiant de l'événement à reclasser.
+        #[arg(value_name = "EVENTID")]
+        event_id: String,
+        /// Nouveau rang : cause ou symptom.
+        #[arg(value_name = "RANG", value_parser = RankKind::from_str)]
+        rank: RankKind,
+        /// Événement cause, requis pour un symptôme.
+        #[arg(value_name = "CAUSE_EVENTID")]
+        cause_event_id: Option<String>,
+    },
+    /// Supprime un problème dans Zabbix, pour une durée ou jusqu'à levée de la suppression.
+    Suppress {
+        /// Identifiant de l'événement à supprimer.
+        #[arg(value_name = "EVENTID")]
+        event_id: String,
+        /// Durée de la suppression (ex. "2h") ; sans elle, jusqu'à levée manuelle.
+        #[arg(long, value_name = "DURÉE", value_parser = parse_duration)]
+        until: Option<Duration>,
+        /// Message joint à la suppression.
+        #[arg(long, short, value_name = "TEXTE")]
+        message: Option<String>,
+    },
+    /// Coupe les notifications sous `notify.mute_floor` pendant une durée (ex. "1h").
+    Mute {
+        /// Durée de la coupure.
+        #[arg(value_name = "DURÉE", value_parser = parse_duration)]
+        duration: Duration,
+    },
+    /// Lève la coupure posée par `mute` avant son terme.
+    Unmute,
+    /// Affiche l'état du démon enregistré dans `app.state_file` (coupure en cours).
+    Status,
+    /// Affiche l'historique des notifications (envoyées, abandonnées, acquittées, résolues).
+    #[command(args_conflicts_with_subcommands = true)]
+    History {
+        #[command(subcommand)]
+        action: Option<HistoryCommand>,
+        /// Période affichée, jusqu'à maintenant (ex. "7d").
+        #[arg(long, value_name = "DURÉE", value_parser = parse_duration, default_value = "24h")]
+        since: Duration,
+    },
+    /// Lance le démon à l'ouverture de session (unité systemd utilisateur ou raccourci de démarrage).
+    InstallAutostart,
+    /// Supprime le lancement automatique installé par `install-autostart`.
+    UninstallAutostart,
+    /// Boîte de saisie intégrée pour le message d'acquittement (usage interne).
+    #[cfg(feature = "native-prompt")]
+    #[command(hide = true)]
+    Prompt {
+        #[arg(long)]
+        title: String,
+        #[arg(long)]
+        text: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Affiche la configuration effective (défauts + fichier + env + CLI), secrets masqués.
+    Show,
+    /// Liste les variables `ALERTING__<SECTION>__<CHAMP>`, une par réglage du fichier.
+    Env,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HistoryCommand {
+    /// Exporte l'historique en CSV ou JSON, pour les post-mortems et revues de bruit.
+    Export {
+        /// Format de sortie : csv ou json.
+        #[arg(long, value_name = "FORMAT", value_parser = ExportFormat::from_str, default_value = "csv")]
+        format: ExportFormat,
+        /// Période exportée, jusqu'à maintenant (ex. "30d").
+        #[arg(long, value_name = "DURÉE", value_parser = parse_duration, default_value = "7d")]
+        since: Duration,
+        /// Fichier de sortie (défaut : sortie standard).
+        #[arg(long, short, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// Applique la rétention (`history_max_age`, `history_max_rows`) sans attendre le redémarrage.
+    Prune,
+}
+
 impl Cli {
     pub fn parse_args() -> Self {
         Self::parse()
@@ -0,0 +1,17 @@
+//! `alerting comment`: add a message to an event without acknowledging it.
+
+use alerting::Result;
+use alerting::error::{ConfigError, Error as AlertError};
+use alerting::zbx_client::ZbxClient;
+
+pub async fn run(client: &ZbxClient, event_id: &str, message: &str) -> Result<()> {
+    if message.trim().is_empty() {
+        return Err(AlertError::from(ConfigError::InvalidField {
+            field: "MESSAGE",
+            message: "the comment cannot be empty".to_string(),
+        }));
+    }
+    client.comment_event(event_id, message).await?;
+    println!("comment added to event {event_id}");
+    Ok(())
+}
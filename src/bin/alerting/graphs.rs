@@ -0,0 +1,133 @@
+//! Trigger graphs downloaded from the frontend and attached to toasts.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use alerting::zbx_client::{Frontend, Problem, ZbxClient};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::{Instrument, debug, warn};
+
+use super::notifier::NotificationItem;
+
+/// Graph files kept on disk; older ones are deleted, by which time their
+/// toasts have long been read or replaced.
+const MAX_FILES: usize = 64;
+
+/// Downloads trigger graphs into the per-user cache directory. Without one
+/// toasts go out without graph: a shared temporary directory could be
+/// tampered with by other local users.
+pub struct GraphCache {
+    frontend: Arc<Frontend>,
+    period: Duration,
+    dir: Option<PathBuf>,
+    files: VecDeque<PathBuf>,
+}
+
+impl GraphCache {
+    pub fn new(frontend: Frontend, period: Duration) -> Self {
+        let dir = alerting::paths::cache_dir().map(|dir| dir.join("graphs"));
+        if dir.is_none() {
+            warn!("no cache directory; toasts will have no graph");
+        }
+        Self {
+            frontend: Arc::new(frontend),
+            period,
+            dir,
+            files: VecDeque::new(),
+        }
+    }
+
+    /// Attach the graph of each item's trigger, `concurrency` downloads at
+    /// a time. Items whose trigger has no graph, and failed downloads, get
+    /// none; failures are logged and never hold the toasts back.
+    pub async fn attach(
+        &mut self,
+        client: &ZbxClient,
+        mut items: Vec<&mut NotificationItem>,
+        concurrency: usize,
+    ) {
+        if self.dir.is_none() {
+            return;
+        }
+        let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+        let mut tasks: JoinSet<(usize, Option<Vec<u8>>)> = JoinSet::new();
+        for (idx, item) in items.iter().enumerate() {
+            let client = client.clone();
+            let frontend = Arc::clone(&self.frontend);
+            let semaphore = Arc::clone(&semaphore);
+            let problem = item.problem.clone();
+            let period = self.period;
+            tasks.spawn(
+                async move {
+                    let Ok(_permit) = semaphore.acquire_owned().await else {
+                        return (idx, None);
+                    };
+                    (idx, download(&client, &frontend, &problem, period).await)
+                }
+                .instrument(item.span.clone()),
+            );
+        }
+        while let Some(res) = tasks.join_next().await {
+            match res {
+                Ok((idx, Some(png))) => {
+                    if let Some(item) = items.get_mut(idx) {
+                        item.graph = self.save(&item.problem.event_id, &png);
+                    }
+                }
+                Ok((_, None)) => {}
+                Err(err) => warn!(error = %err, "graph download task failed"),
+            }
+        }
+    }
+
+    fn save(&mut self, event_id: &str, png: &[u8]) -> Option<PathBuf> {
+        let path = self.dir.as_ref()?.join(format!("{event_id}.png"));
+        if let Err(err) = alerting::paths::write_new(&path, png) {
+            warn!(error = %err, path = %path.display(), "failed to save graph image");
+            return None;
+        }
+        self.remember(path.clone());
+        Some(path)
+    }
+
+    fn remember(&mut self, path: PathBuf) {
+        self.files.retain(|known| *known != path);
+        self.files.push_back(path);
+        while self.files.len() > MAX_FILES {
+            if let Some(old) = self.files.pop_front() {
+                let _ = std::fs::remove_file(old);
+            }
+        }
+    }
+}
+
+/// The graph of the trigger behind `problem`, as a PNG.
+async fn download(
+    client: &ZbxClient,
+    frontend: &Frontend,
+    problem: &Problem,
+    period: Duration,
+) -> Option<Vec<u8>> {
+    let trigger_id = problem.trigger_id.as_deref()?;
+    let graph_id = match client.trigger_graph(trigger_id).await {
+        Ok(Some(graph_id)) => graph_id,
+        Ok(None) => {
+            debug!(trigger_id, "trigger has no graph");
+            return None;
+        }
+        Err(err) => {
+            warn!(error_code = err.code(), error = %err, "failed to look up trigger graph");
+            return None;
+        }
+    };
+    match frontend.graph_png(&graph_id, period).await {
+        Ok(png) => Some(png),
+        Err(err) => {
+            warn!(error_code = err.code(), error = %err, graph_id, "failed to download graph");
+            None
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Scheduled "Alerting is running" notice, so a daemon that silently died is
+//! noticed by its missing heartbeat.
+
+use std::time::{Duration, Instant};
+
+use alerting::config::{Heartbeat, HeartbeatBackend};
+use async_channel::{Sender, TrySendError};
+use chrono::Local;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+use tracing::{info, warn};
+
+use super::notifier::Notice;
+
+/// Longest single sleep, so a wall clock moved by a suspend or a time change
+/// is caught up with soon.
+const MAX_SLEEP: Duration = Duration::from_mins(1);
+
+/// Outcome of the last successful poll, published by the poll loop.
+#[derive(Clone, Copy, Debug)]
+pub struct PollStatus {
+    pub at: Instant,
+    pub active: usize,
+}
+
+/// Send heartbeats on `heartbeat`'s schedule until the notifier queue closes.
+pub fn spawn(
+    heartbeat: Heartbeat,
+    status: watch::Receiver<Option<PollStatus>>,
+    tx: Sender<Notice>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut next = heartbeat.next_after(Local::now().naive_local());
+        while !tx.is_closed() {
+            let now = Local::now().naive_local();
+            if now < next {
+                let wait = (next - now).to_std().unwrap_or_default();
+                sleep(wait.min(MAX_SLEEP)).await;
+                continue;
+            }
+            let status = *status.borrow();
+            let last_poll = status.map(|status| status.at.elapsed());
+            let active = status.map_or(0, |status| status.active);
+            match heartbeat.backend {
+                HeartbeatBackend::Log => {
+                    info!(
+                        last_poll_secs = last_poll.map(|age| age.as_secs()),
+                        active,
+                        "heartbeat: {}",
+                        heartbeat_text(last_poll, active)
+                    );
+                }
+                HeartbeatBackend::Toast => {
+                    if let Err(TrySendError::Full(_)) =
+                        tx.try_send(Notice::Heartbeat { last_poll, active })
+                    {
+                        warn!("notification queue full; dropping heartbeat");
+                    }
+                }
+            }
+            // Once per slot, even when the schedule was missed by hours.
+            next = heartbeat.next_after(now);
+        }
+    })
+}
+
+/// "last poll 12s ago, 0 active problems".
+pub fn heartbeat_text(last_poll: Option<Duration>, active: usize) -> String {
+    let poll = last_poll.map_or_else(
+        || "no poll completed yet".to_string(),
+        |age| {
+            let age = Duration::from_secs(age.as_secs());
+            format!("last poll {} ago", humantime::format_duration(age))
+        },
+    );
+    let plural = if active == 1 { "" } else { "s" };
+    format!("{poll}, {active} active problem{plural}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::heartbeat_text;
+    use std::time::Duration;
+
+    #[test]
+    fn text_reports_poll_age_and_active_count() {
+        assert_eq!(
+            heartbeat_text(Some(Duration::from_millis(12_400)), 0),
+            "last poll 12s ago, 0 active problems"
+        );
+        assert_eq!(
+            heartbeat_text(None, 1),
+            "no poll completed yet, 1 active problem"
+        );
+    }
+}
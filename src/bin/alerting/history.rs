@@ -0,0 +1,458 @@
+//! Notification history kept in the optional `app.history_file`: one JSON
+//! line per problem notified, dropped, acknowledged or resolved.
+
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alerting::Result;
+use alerting::config::{DisplaySettings, HistoryRetention};
+use alerting::error::{ConfigError, Error as AlertError};
+use alerting::types::Severity;
+use alerting::zbx_client::{HostMeta, Problem};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// What happened to a problem.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Notified,
+    /// Dropped by `rate_limit_*`.
+    RateLimited,
+    /// Dropped because the notifier was `queue_capacity` toasts behind.
+    QueueFull,
+    /// Below `notify.mute_floor` while `alerting mute` was on.
+    Muted,
+    Acked,
+    Resolved,
+}
+
+impl Outcome {
+    /// Stored and exported name.
+    const fn key(self) -> &'static str {
+        match self {
+            Self::Notified => "notified",
+            Self::RateLimited => "rate_limited",
+            Self::QueueFull => "queue_full",
+            Self::Muted => "muted",
+            Self::Acked => "acked",
+            Self::Resolved => "resolved",
+        }
+    }
+
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Notified => "notified",
+            Self::RateLimited => "dropped (rate limit)",
+            Self::QueueFull => "dropped (queue full)",
+            Self::Muted => "dropped (muted)",
+            Self::Acked => "acked",
+            Self::Resolved => "resolved",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Entry {
+    /// Unix time of the outcome.
+    pub at: i64,
+    pub outcome: Outcome,
+    pub event_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl Entry {
+    pub fn new(outcome: Outcome, problem: &Problem, host: Option<&HostMeta>) -> Self {
+        Self {
+            at: unix_now(),
+            outcome,
+            event_id: problem.event_id.clone(),
+            host: host.map(|host| host.display_name.clone()),
+            severity: Some(problem.severity),
+            name: Some(problem.name.clone()),
+        }
+    }
+}
+
+/// Appends entries to the history file; does nothing without one.
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    path: Option<PathBuf>,
+}
+
+impl History {
+    pub const fn new(path: Option<PathBuf>) -> Self {
+        Self { path }
+    }
+
+    pub const fn is_enabled(&self) -> bool {
+        self.path.is_some()
+    }
+
+    pub fn record(&self, outcome: Outcome, problem: &Problem, host: Option<&HostMeta>) {
+        if self.is_enabled() {
+            self.append(&Entry::new(outcome, problem, host));
+        }
+    }
+
+    /// Write one entry. Failures are logged: the history never holds a toast
+    /// back.
+    pub fn append(&self, entry: &Entry) {
+        let Some(path) = self.path.as_deref() else {
+            return;
+        };
+        if let Err(err) = append_line(path, entry) {
+            warn!(path = %path.display(), error = %err, "failed to write history file");
+        }
+    }
+}
+
+fn append_line(path: &Path, entry: &Entry) -> std::io::Result<()> {
+    let mut line = serde_json::to_vec(entry)?;
+    line.push(b'\n');
+    alerting::paths::create_parent(path)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(&line)
+}
+
+/// Entries recorded at or after `since` (Unix time), oldest first. Lines that
+/// do not parse are skipped.
+fn read_since(path: &Path, since: i64) -> std::io::Result<Vec<Entry>> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut entries = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Entry>(&line) {
+            Ok(entry) if entry.at >= since => entries.push(entry),
+            Ok(_) => {}
+            Err(err) => warn!(error = %err, "skipping invalid history line"),
+        }
+    }
+    Ok(entries)
+}
+
+/// Entries of the configured history file over the last `since`.
+fn load(path: Option<&Path>, since: Duration) -> Result<Vec<Entry>> {
+    let path = configured(path)?;
+    read_since(path, cutoff(since)).map_err(|source| AlertError::History {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn configured(path: Option<&Path>) -> Result<&Path> {
+    path.ok_or_else(|| {
+        AlertError::from(ConfigError::InvalidField {
+            field: "app.history_file",
+            message: "no history file configured (set app.history_file or HISTORY_FILE)"
+                .to_string(),
+        })
+    })
+}
+
+/// Unix time `age` ago.
+fn cutoff(age: Duration) -> i64 {
+    unix_now().saturating_sub(i64::try_from(age.as_secs()).unwrap_or(i64::MAX))
+}
+
+/// Outcome of [`prune`].
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Pruned {
+    pub kept: usize,
+    pub removed: usize,
+}
+
+/// Drop entries older than `retention.max_age`, then the oldest ones beyond
+/// `retention.max_rows`, along with lines that do not parse. Kept lines are
+/// written back unchanged.
+pub fn prune(path: &Path, retention: &HistoryRetention) -> std::io::Result<Pruned> {
+    let raw = match std::fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Pruned::default()),
+        Err(err) => return Err(err),
+    };
+    let since = retention.max_age.map_or(i64::MIN, cutoff);
+    let lines: Vec<&str> = raw.lines().filter(|line| !line.trim().is_empty()).collect();
+    let mut kept: Vec<&str> = lines
+        .iter()
+        .copied()
+        .filter(|line| serde_json::from_str::<Entry>(line).is_ok_and(|entry| entry.at >= since))
+        .collect();
+    if let Some(max_rows) = retention.max_rows {
+        // Entries are appended as they happen: the oldest come first.
+        let excess = kept.len().saturating_sub(max_rows);
+        kept.drain(..excess);
+    }
+    let pruned = Pruned {
+        kept: kept.len(),
+        removed: lines.len() - kept.len(),
+    };
+    if pruned.removed > 0 {
+        let mut data = kept.join("\n");
+        if !data.is_empty() {
+            data.push('\n');
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, data)?;
+        std::fs::rename(tmp, path)?;
+    }
+    Ok(pruned)
+}
+
+/// Prune on daemon startup; failures are logged and do not stop the daemon.
+pub fn prune_on_startup(path: &Path, retention: &HistoryRetention) {
+    match prune(path, retention) {
+        Ok(Pruned { removed: 0, .. }) => {}
+        Ok(Pruned { kept, removed }) => {
+            info!(path = %path.display(), kept, removed, "pruned history file");
+        }
+        Err(err) => {
+            warn!(path = %path.display(), error = %err, "failed to prune history file");
+        }
+    }
+}
+
+/// `alerting history prune`: apply the retention policy now.
+pub fn run_prune(path: Option<&Path>, retention: &HistoryRetention) -> Result<()> {
+    let path = configured(path)?;
+    let Pruned { kept, removed } =
+        prune(path, retention).map_err(|source| AlertError::History {
+            path: path.to_path_buf(),
+            source,
+        })?;
+    println!("removed {removed} entries, kept {kept}");
+    Ok(())
+}
+
+/// `alerting history`: print what happened to problems over the last `since`.
+pub fn run(path: Option<&Path>, since: Duration, display: &DisplaySettings) -> Result<()> {
+    let entries = load(path, since)?;
+    if entries.is_empty() {
+        println!(
+            "nothing recorded in the last {}",
+            humantime::format_duration(since)
+        );
+        return Ok(());
+    }
+    for entry in &entries {
+        println!("{}", format_entry(entry, display));
+    }
+    Ok(())
+}
+
+/// Output of `alerting history export`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            other => Err(format!("unknown export format {other:?} (csv or json)")),
+        }
+    }
+}
+
+/// Exported entry: the stored fields plus a readable timestamp.
+#[derive(Serialize)]
+struct Exported<'a> {
+    time: String,
+    #[serde(flatten)]
+    entry: &'a Entry,
+}
+
+/// `alerting history export`: write the entries of the last `since` to
+/// `output`, or to stdout without one.
+pub fn export(
+    path: Option<&Path>,
+    since: Duration,
+    format: ExportFormat,
+    output: Option<&Path>,
+    display: &DisplaySettings,
+) -> Result<()> {
+    let entries = load(path, since)?;
+    let rendered = match format {
+        ExportFormat::Csv => to_csv(&entries, display),
+        ExportFormat::Json => {
+            let exported: Vec<Exported<'_>> = entries
+                .iter()
+                .map(|entry| Exported {
+                    time: display.rfc3339(entry.at),
+                    entry,
+                })
+                .collect();
+            let mut json = serde_json::to_string_pretty(&exported)
+                .map_err(|err| AlertError::from(ConfigError::Other(err.to_string())))?;
+            json.push('\n');
+            json
+        }
+    };
+    match output {
+        Some(output) => {
+            std::fs::write(output, rendered).map_err(|source| AlertError::History {
+                path: output.to_path_buf(),
+                source,
+            })?;
+            eprintln!("exported {} entries to {}", entries.len(), output.display());
+        }
+        None => print!("{rendered}"),
+    }
+    Ok(())
+}
+
+const CSV_HEADER: &str = "time,outcome,event_id,host,severity,name";
+
+fn to_csv(entries: &[Entry], display: &DisplaySettings) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push_str("\r\n");
+    for entry in entries {
+        let fields = [
+            display.rfc3339(entry.at),
+            entry.outcome.key().to_string(),
+            entry.event_id.clone(),
+            entry.host.clone().unwrap_or_default(),
+            entry
+                .severity
+                .map(|sev| sev.to_string().to_ascii_lowercase())
+                .unwrap_or_default(),
+            entry.name.clone().unwrap_or_default(),
+        ];
+        let quoted: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&quoted.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// Quote a field when it holds a separator, quote or line break (RFC 4180).
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn format_entry(entry: &Entry, display: &DisplaySettings) -> String {
+    let at = display.when(entry.at, unix_now(), "%Y-%m-%d %H:%M:%S");
+    let severity = entry
+        .severity
+        .map_or_else(String::new, |sev| sev.to_string());
+    let mut line = format!(
+        "{at}  {:<20}  {severity:<8}  {:<20}  event {}",
+        entry.outcome.as_str(),
+        entry.host.as_deref().unwrap_or("-"),
+        entry.event_id
+    );
+    if let Some(name) = &entry.name {
+        line.push_str(": ");
+        line.push_str(name);
+    }
+    line
+}
+
+pub fn unix_now() -> i64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    i64::try_from(now.as_secs()).unwrap_or(i64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entry, History, Outcome, Pruned, csv_field, prune, read_since, unix_now};
+    use alerting::config::HistoryRetention;
+    use alerting::types::Severity;
+    use std::time::Duration;
+
+    fn entry(at: i64, outcome: Outcome, event_id: &str) -> Entry {
+        Entry {
+            at,
+            outcome,
+            event_id: event_id.to_string(),
+            host: Some("db01".to_string()),
+            severity: Some(Severity::High),
+            name: Some("Disk full".to_string()),
+        }
+    }
+
+    #[test]
+    fn entries_are_read_back_since_cutoff() -> std::io::Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("alerting-history-{}.jsonl", uuid::Uuid::new_v4()));
+        let history = History::new(Some(path.clone()));
+        history.append(&entry(100, Outcome::Notified, "1"));
+        history.append(&entry(200, Outcome::RateLimited, "2"));
+        history.append(&entry(300, Outcome::Resolved, "1"));
+
+        let entries = read_since(&path, 200)?;
+        let _ = std::fs::remove_file(&path);
+        let seen: Vec<(Outcome, &str)> = entries
+            .iter()
+            .map(|entry| (entry.outcome, entry.event_id.as_str()))
+            .collect();
+        assert_eq!(
+            seen,
+            [(Outcome::RateLimited, "2"), (Outcome::Resolved, "1")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("Disk full"), "Disk full");
+        assert_eq!(csv_field("/var: 95%, /home"), "\"/var: 95%, /home\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn prune_applies_age_then_row_limits() -> std::io::Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("alerting-history-{}.jsonl", uuid::Uuid::new_v4()));
+        let now = unix_now();
+        let history = History::new(Some(path.clone()));
+        history.append(&entry(now - 7200, Outcome::Notified, "1"));
+        history.append(&entry(now - 60, Outcome::Notified, "2"));
+        history.append(&entry(now - 30, Outcome::Acked, "2"));
+        history.append(&entry(now, Outcome::Resolved, "2"));
+
+        let retention = HistoryRetention {
+            max_age: Some(Duration::from_hours(1)),
+            max_rows: Some(2),
+        };
+        let pruned = prune(&path, &retention)?;
+        let entries = read_since(&path, 0)?;
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(
+            pruned,
+            Pruned {
+                kept: 2,
+                removed: 2
+            }
+        );
+        let seen: Vec<Outcome> = entries.iter().map(|entry| entry.outcome).collect();
+        assert_eq!(seen, [Outcome::Acked, Outcome::Resolved]);
+        Ok(())
+    }
+}
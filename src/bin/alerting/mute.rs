@@ -0,0 +1,74 @@
+//! `alerting mute`, `unmute` and `status`: silence the severities below
+//! `notify.mute_floor` for a while. The mute lives in `app.state_file`,
+//! which the daemon reads at every poll.
+
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use alerting::Result;
+use alerting::config::DisplaySettings;
+use alerting::error::{ConfigError, Error as AlertError};
+use alerting::types::Severity;
+
+use super::history::unix_now;
+use super::state;
+use super::suppress::unix_after;
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+pub fn run(
+    state_file: Option<&Path>,
+    wait: Duration,
+    floor: Severity,
+    display: &DisplaySettings,
+) -> Result<()> {
+    let path = required(state_file)?;
+    let until = mute_for(path, wait).map_err(|source| AlertError::StateFile {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    println!(
+        "notifications below {floor} muted until {}",
+        display.when(until, unix_now(), TIME_FORMAT)
+    );
+    Ok(())
+}
+
+pub fn unmute(state_file: Option<&Path>) -> Result<()> {
+    let path = required(state_file)?;
+    state::set_muted_until(path, None).map_err(|source| AlertError::StateFile {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    println!("notifications unmuted");
+    Ok(())
+}
+
+pub fn status(state_file: Option<&Path>, floor: Severity, display: &DisplaySettings) {
+    let Some(path) = state_file else {
+        println!("mute: unavailable (app.state_file is not set)");
+        return;
+    };
+    match state::muted_until(path, unix_now()) {
+        Some(until) => println!(
+            "mute: below {floor} until {}",
+            display.when(until, unix_now(), TIME_FORMAT)
+        ),
+        None => println!("mute: off"),
+    }
+}
+
+fn required(state_file: Option<&Path>) -> Result<&Path> {
+    state_file.ok_or_else(|| {
+        AlertError::from(ConfigError::MissingField {
+            field: "app.state_file",
+        })
+    })
+}
+
+/// Mute for `wait` from now; returns the Unix time the mute ends.
+pub fn mute_for(path: &Path, wait: Duration) -> std::io::Result<i64> {
+    let until = unix_after(SystemTime::now(), wait);
+    state::set_muted_until(path, Some(until))?;
+    Ok(until)
+}
@@ -0,0 +1,107 @@
+//! Network connectivity change detection.
+//!
+//! After docking or a VPN reconnect the poll loop may still be sleeping for
+//! most of a poll interval. The handle returned by [`watch`] is notified when
+//! the machine regains full connectivity so the loop can poll immediately.
+//! On Linux it follows `NetworkManager`'s `StateChanged` signal; on Windows the
+//! `NetworkStatusChanged` event. Elsewhere, or when neither is available, it
+//! is never notified and polling keeps its regular pace.
+
+use std::sync::Arc;
+
+use tokio::sync::Notify;
+
+/// Spawn the connectivity watcher and return the handle notified on reconnect.
+pub fn watch() -> Arc<Notify> {
+    let reconnected = Arc::new(Notify::new());
+    #[cfg(target_os = "linux")]
+    tokio::spawn(network_manager::watch(Arc::clone(&reconnected)));
+    #[cfg(target_os = "windows")]
+    windows_events::watch(&reconnected);
+    reconnected
+}
+
+#[cfg(target_os = "linux")]
+mod network_manager {
+    use std::sync::Arc;
+
+    use futures_util::StreamExt;
+    use tokio::sync::Notify;
+    use tracing::{debug, info};
+
+    /// `NM_STATE_CONNECTED_GLOBAL`: full network connectivity.
+    const CONNECTED_GLOBAL: u32 = 70;
+
+    #[zbus::proxy(
+        interface = "org.freedesktop.NetworkManager",
+        default_service = "org.freedesktop.NetworkManager",
+        default_path = "/org/freedesktop/NetworkManager"
+    )]
+    trait NetworkManager {
+        #[zbus(signal)]
+        fn state_changed(&self, state: u32) -> zbus::Result<()>;
+
+        #[zbus(property, name = "State")]
+        fn current_state(&self) -> zbus::Result<u32>;
+    }
+
+    pub(super) async fn watch(reconnected: Arc<Notify>) {
+        if let Err(err) = listen(&reconnected).await {
+            debug!(error = %err, "NetworkManager unavailable, network changes ignored");
+        }
+    }
+
+    async fn listen(reconnected: &Notify) -> zbus::Result<()> {
+        let connection = zbus::Connection::system().await?;
+        let manager = NetworkManagerProxy::new(&connection).await?;
+        let mut signals = manager.receive_state_changed().await?;
+        let mut last = manager.current_state().await?;
+        debug!(state = last, "listening for NetworkManager state changes");
+        while let Some(signal) = signals.next().await {
+            let state = signal.args()?.state;
+            if state == CONNECTED_GLOBAL && last != CONNECTED_GLOBAL {
+                info!("network connectivity restored");
+                reconnected.notify_one();
+            }
+            last = state;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows_events {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use tokio::sync::Notify;
+    use tracing::{debug, info};
+    use windows::Networking::Connectivity::{
+        NetworkConnectivityLevel, NetworkInformation, NetworkStatusChangedEventHandler,
+    };
+
+    fn has_internet() -> bool {
+        NetworkInformation::GetInternetConnectionProfile()
+            .and_then(|profile| profile.GetNetworkConnectivityLevel())
+            .is_ok_and(|level| level == NetworkConnectivityLevel::InternetAccess)
+    }
+
+    pub(super) fn watch(reconnected: &Arc<Notify>) {
+        let reconnected = Arc::clone(reconnected);
+        let connected = AtomicBool::new(has_internet());
+        let handler = NetworkStatusChangedEventHandler::new(move |_| {
+            let now = has_internet();
+            if now && !connected.swap(now, Ordering::Relaxed) {
+                info!("network connectivity restored");
+                reconnected.notify_one();
+            } else {
+                connected.store(now, Ordering::Relaxed);
+            }
+            Ok(())
+        });
+        // The registration lives for the whole process, so the token is not kept.
+        if let Err(err) = NetworkInformation::NetworkStatusChanged(&handler) {
+            debug!(error = %err, "network status events unavailable, network changes ignored");
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Solid-colour images standing for `notify.severities.<severity>.color`:
+//! the toast icon on Linux, the hero strip on Windows. One small PNG per
+//! colour is written to the cache directory the first time it is needed.
+
+use std::path::PathBuf;
+
+use alerting::types::Color;
+use tracing::warn;
+
+/// Side of the square image; servers and the Action Center scale it.
+const SIDE: u32 = 32;
+
+/// Path of the image filled with `color`, or `None` without a cache
+/// directory or when it cannot be written; the failure is logged and the
+/// toast goes out without accent.
+pub(super) fn file(color: Color) -> Option<PathBuf> {
+    let path = alerting::paths::cache_dir()?
+        .join("accents")
+        .join(format!("{:02x}{:02x}{:02x}.png", color.r, color.g, color.b));
+    if path.is_file() {
+        return Some(path);
+    }
+    match png(color).and_then(|png| alerting::paths::write_new(&path, &png)) {
+        Ok(()) => Some(path),
+        Err(err) => {
+            warn!(error = %err, path = %path.display(), "failed to write accent image");
+            None
+        }
+    }
+}
+
+/// RGB PNG of `SIDE`×`SIDE` pixels of `color`.
+fn png(color: Color) -> std::io::Result<Vec<u8>> {
+    let pixels: Vec<u8> = (0..SIDE * SIDE)
+        .flat_map(|_| [color.r, color.g, color.b])
+        .collect();
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, SIDE, SIDE);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixels)?;
+    writer.finish()?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use alerting::types::Color;
+
+    use super::{SIDE, png};
+
+    #[test]
+    fn png_decodes_to_the_colour() -> Result<(), Box<dyn std::error::Error>> {
+        let image = png(Color {
+            r: 0xe9,
+            g: 0x76,
+            b: 0x59,
+        })?;
+        let mut reader = png::Decoder::new(std::io::Cursor::new(image)).read_info()?;
+        let mut pixels = vec![0; reader.output_buffer_size().unwrap_or_default()];
+        let info = reader.next_frame(&mut pixels)?;
+        assert_eq!((info.width, info.height), (SIDE, SIDE));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        assert_eq!(&pixels[..3], &[0xe9, 0x76, 0x59]);
+        Ok(())
+    }
+}
@@ -1,21 +1,133 @@
 use std::path::Path;
+use std::time::Duration;
 
 use alerting::error::NotifyError;
+use alerting::types::Urgency;
 
-use super::{AckAction, ToastTimeout, ToastUrgency};
+use super::super::state::NotificationIds;
+use super::{AckAction, BodyLimit, ToastTimeout};
 
+#[allow(clippy::struct_excessive_bools)]
+#[cfg_attr(
+    not(any(
+        all(target_os = "linux", feature = "linux-toasts"),
+        all(target_os = "windows", feature = "windows-toasts")
+    )),
+    allow(dead_code)
+)]
 pub(super) struct ToastParams<'a> {
     pub summary: &'a str,
     pub body: &'a str,
-    pub urgency: ToastUrgency,
+    /// Event name, emphasised where the backend supports it.
+    pub detail: Option<&'a str>,
+    /// Cap on the body as the backend assembles it; set by `show` from
+    /// `notify.max_body_lines` and `notify.max_body_chars`.
+    pub body_limit: BodyLimit,
+    pub urgency: Urgency,
     pub timeout: ToastTimeout,
     pub appname: &'a str,
     pub icon: Option<&'a Path>,
+    pub hero_image: Option<&'a Path>,
+    /// Severity colour image: replaces the icon on Linux, fills in for a
+    /// missing hero image on Windows.
+    pub accent: Option<&'a Path>,
+    /// Trigger graph; replaces the hero image on Linux, inline on Windows.
+    pub graph: Option<&'a Path>,
+    pub attribution: Option<&'a str>,
     pub open_url: Option<&'a str>,
     pub open_label: &'a str,
+    /// Host dashboard link, offered as a second action.
+    pub host_url: Option<&'a str>,
+    /// Action Center group (Windows only).
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub group: Option<&'a str>,
+    /// Identifier of the toast within its group; the event id.
+    pub tag: Option<&'a str>,
+    /// Backend ids of toasts already shown, keyed by tag.
+    pub ids: Option<&'a NotificationIds>,
+    /// Basename of the installed `.desktop` file (Linux only).
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub desktop_entry: Option<&'a str>,
+    /// Ack-message prompt command (Linux only).
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub prompt_command: &'a [String],
+    /// Ask for a message with `prompt_command` before acknowledging; when
+    /// `false` the ack is sent at once (Linux only).
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub ack_prompt: bool,
+    /// Yes/no command run before an ack when `notify.confirm_ack` is set
+    /// (Linux only).
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub confirm_command: Option<&'a [String]>,
+    /// Offer to turn the symptom into a cause (Linux only, with an ack action).
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub cause_action: bool,
+    /// Offer to comment the event without acknowledging it (Linux only, with
+    /// an ack action).
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub comment_action: bool,
+    /// Offer to acknowledge the host's unacknowledged problems, this many
+    /// (Linux only, with an ack action).
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub host_ack_action: Option<usize>,
+    /// Offer to mute the severities below `notify.mute_floor` for this long
+    /// (Linux only, with a state file).
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub mute_action: Option<Duration>,
+    /// Keep the toast in the notification server until closed (Linux only).
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub resident: bool,
+    /// Resolved and total problems, shown as a progress bar updated in
+    /// place while the toast is up (Windows only).
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub progress: Option<(usize, usize)>,
+    /// Force the alert sound on or off; `None` follows the urgency.
+    pub sound: Option<bool>,
+    /// Played instead of the system sound, unless `sound` is `Some(false)`.
+    pub sound_file: Option<&'a Path>,
 }
 
-#[cfg(target_os = "linux")]
+#[cfg(any(
+    all(target_os = "linux", feature = "linux-toasts"),
+    all(target_os = "windows", feature = "windows-toasts"),
+    all(target_os = "macos", feature = "macos-toasts")
+))]
+impl<'a> ToastParams<'a> {
+    /// Custom sound to play, unless the toast is muted.
+    fn custom_sound(&self) -> Option<&'a Path> {
+        self.sound_file.filter(|_| self.sound != Some(false))
+    }
+
+    /// Body and event name for backends showing them apart, with the body
+    /// limit applied to both together. The "…and N more" line goes with the
+    /// event name, which comes last.
+    #[cfg_attr(
+        not(any(
+            all(target_os = "windows", feature = "windows-toasts"),
+            all(target_os = "macos", feature = "macos-toasts")
+        )),
+        allow(dead_code)
+    )]
+    fn limited_text(&self) -> (String, Option<String>) {
+        let mut parts = vec![(self.body.to_string(), false)];
+        parts.extend(self.detail.map(|detail| (detail.to_string(), true)));
+        let (detail, body): (Vec<_>, Vec<_>) = self
+            .body_limit
+            .fit(parts, true)
+            .into_iter()
+            .partition(|(_, is_detail)| *is_detail);
+        let join = |lines: Vec<(String, bool)>| {
+            lines
+                .into_iter()
+                .map(|(line, _)| line)
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        (join(body), (!detail.is_empty()).then(|| join(detail)))
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "linux-toasts"))]
 pub(super) fn send_toast(
     params: &ToastParams<'_>,
     ack_action: Option<&AckAction>,
@@ -23,96 +135,496 @@ pub(super) fn send_toast(
     linux::send_toast(params, ack_action)
 }
 
-#[cfg(not(target_os = "linux"))]
+#[cfg(all(target_os = "windows", feature = "windows-toasts"))]
 pub(super) fn send_toast(
     params: &ToastParams<'_>,
     ack_action: Option<&AckAction>,
 ) -> std::result::Result<(), NotifyError> {
-    #[cfg(target_os = "windows")]
-    {
-        return windows::send_toast(params, ack_action);
-    }
+    windows::send_toast(params, ack_action)
+}
+
+#[cfg(all(target_os = "macos", feature = "macos-toasts"))]
+pub(super) fn send_toast(
+    params: &ToastParams<'_>,
+    ack_action: Option<&AckAction>,
+) -> std::result::Result<(), NotifyError> {
+    macos::send_toast(params, ack_action)
+}
+
+/// Built without a toast backend for this platform.
+#[cfg(not(any(
+    all(target_os = "linux", feature = "linux-toasts"),
+    all(target_os = "windows", feature = "windows-toasts"),
+    all(target_os = "macos", feature = "macos-toasts")
+)))]
+pub(super) const fn send_toast(
+    _params: &ToastParams<'_>,
+    _ack_action: Option<&AckAction>,
+) -> std::result::Result<(), NotifyError> {
+    Err(NotifyError::Backend)
+}
+
+/// Withdraw the toasts shown for `event_ids`.
+#[cfg(all(target_os = "linux", feature = "linux-toasts"))]
+pub(super) fn close_toasts(_appname: &str, ids: &NotificationIds, event_ids: &[String]) {
+    linux::close_toasts(ids, event_ids);
+}
 
-    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
-    {
-        let _ = (params, ack_action);
-        Err(NotifyError::Backend)
+/// Remove the toasts shown for `event_ids` from Action Center.
+#[cfg(all(target_os = "windows", feature = "windows-toasts"))]
+pub(super) fn close_toasts(appname: &str, ids: &NotificationIds, event_ids: &[String]) {
+    windows::close_toasts(appname, ids, event_ids);
+}
+
+/// macOS toasts are not tracked: nothing to withdraw.
+#[cfg(not(any(
+    all(target_os = "linux", feature = "linux-toasts"),
+    all(target_os = "windows", feature = "windows-toasts")
+)))]
+pub(super) const fn close_toasts(_appname: &str, _ids: &NotificationIds, _event_ids: &[String]) {}
+
+/// Label of the host dashboard action.
+#[cfg(any(
+    all(target_os = "linux", feature = "linux-toasts"),
+    all(target_os = "windows", feature = "windows-toasts"),
+    all(target_os = "macos", feature = "macos-toasts")
+))]
+const HOST_LABEL: &str = "Host dashboard";
+
+/// Escape text for inclusion in XML/markup content or attribute values.
+#[cfg(any(
+    all(target_os = "linux", feature = "linux-toasts"),
+    all(target_os = "windows", feature = "windows-toasts")
+))]
+fn escape_xml(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    for ch in raw.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(ch),
+        }
     }
+    out
 }
 
-#[cfg(target_os = "linux")]
+#[cfg(all(target_os = "linux", feature = "linux-toasts"))]
 mod linux {
     use alerting::error::NotifyError;
-    use notify_rust::{Notification, Timeout as LibTimeout, Urgency as LibUrgency};
-    use std::process::{Command, Stdio};
-    use tracing::trace;
+    use alerting::types::Urgency;
+    use notify_rust::{Hint, Notification, Timeout as LibTimeout, Urgency as LibUrgency};
+    use std::path::PathBuf;
+    use std::process::Command;
+    use std::sync::Mutex;
+    use std::time::Duration;
+    use tracing::{debug, info, trace, warn};
 
-    use super::super::{AckAction, ToastTimeout, ToastUrgency};
-    use super::ToastParams;
+    use super::super::super::state::NotificationIds;
+    use super::super::waiters::Slot;
+    use super::super::{AckAction, ToastTimeout};
+    use super::{HOST_LABEL, ToastParams, escape_xml};
 
     const ACK_KEY: &str = "ack";
     const OPEN_KEY: &str = "open";
+    const HOST_KEY: &str = "host";
+    const CAUSE_KEY: &str = "cause";
+    const CAUSE_LABEL: &str = "Marquer comme cause";
+    const COMMENT_KEY: &str = "comment";
+    const COMMENT_LABEL: &str = "Commenter";
+    const HOST_ACK_KEY: &str = "ack_host";
+    const MUTE_KEY: &str = "mute";
     const DISMISS_KEY: &str = "dismiss";
     const ACK_LABEL: &str = "Acquitter";
+    const CATEGORY: &str = "network.error";
+    /// Freedesktop sound theme name used when a severity forces a sound.
+    const SOUND_NAME: &str = "dialog-warning";
+    const PROMPT_TITLE: &str = "Acquitter l'evenement";
+    const PROMPT_TEXT: &str = "Message d'acquittement (laisser vide pour aucun)";
+    const COMMENT_TITLE: &str = "Commenter l'evenement";
+    const COMMENT_TEXT: &str = "Message ajoute sans acquitter";
+    const CONFIRM_TITLE: &str = "Confirmer l'acquittement";
+    const CONFIRM_KEY: &str = "confirm";
+    const CANCEL_KEY: &str = "cancel";
 
     pub fn send_toast(
         params: &ToastParams<'_>,
         ack_action: Option<&AckAction>,
     ) -> std::result::Result<(), NotifyError> {
+        let body = render_body(params, server_caps());
+        let summary = sanitize_text(params.summary);
+
         let mut builder = Notification::new();
         builder
-            .summary(params.summary)
-            .body(params.body)
+            .summary(&summary)
+            .body(&body)
             .appname(params.appname)
             .urgency(map_urgency(params.urgency))
-            .timeout(map_timeout(params.timeout));
+            .timeout(map_timeout(params.timeout))
+            .hint(Hint::Category(CATEGORY.to_string()));
 
-        if let Some(icon_path) = params.icon {
-            builder.icon(&icon_path.to_string_lossy());
+        if let Some(entry) = params.desktop_entry {
+            builder.hint(Hint::DesktopEntry(entry.to_string()));
         }
 
-        if ack_action.is_some() {
-            builder.action(ACK_KEY, ACK_LABEL);
+        if params.resident {
+            builder.hint(Hint::Resident(true));
+            builder.hint(Hint::Transient(false));
         }
 
-        if params.open_url.is_some() {
-            builder.action(OPEN_KEY, params.open_label);
+        match (params.sound, params.custom_sound()) {
+            (Some(false), _) => {
+                builder.hint(Hint::SuppressSound(true));
+            }
+            (_, Some(file)) => {
+                builder.hint(Hint::SoundFile(file.to_string_lossy().into_owned()));
+            }
+            (Some(true), None) => {
+                builder.hint(Hint::SoundName(SOUND_NAME.to_string()));
+            }
+            (None, None) => {}
         }
 
-        builder.action(DISMISS_KEY, "Ignorer");
+        if let Some(icon_path) = params.accent.or(params.icon) {
+            builder.icon(&icon_path.to_string_lossy());
+        }
 
-        let handle = builder.show().map_err(|_| NotifyError::Backend)?;
-        let open = params.open_url.map(str::to_string);
-        let mut ack = ack_action.cloned();
+        if let Some(image) = params.graph.or(params.hero_image) {
+            builder.image_path(&image.to_string_lossy());
+        }
 
-        handle.wait_for_action(move |action| match action {
-            OPEN_KEY => {
-                if let Some(url) = open.as_deref() {
-                    let _ = Command::new("xdg-open")
-                        .arg(url)
-                        .stdin(Stdio::null())
-                        .stdout(Stdio::null())
-                        .stderr(Stdio::null())
-                        .spawn();
-                }
+        let mute = params
+            .mute_action
+            .zip(params.ids.and_then(NotificationIds::path));
+        let actions = Actions {
+            open: params.open_url.map(str::to_string),
+            host: params.host_url.map(str::to_string),
+            ack: ack_action.cloned(),
+            mute: mute.map(|(mute_for, path)| (mute_for, path.to_path_buf())),
+            prompt_command: params.prompt_command.to_vec(),
+            ack_prompt: params.ack_prompt,
+            confirm: params.confirm_command.map(|command| Confirm {
+                command: command.to_vec(),
+                appname: params.appname.to_string(),
+                summary: summary.clone(),
+            }),
+        };
+        // Buttons are only offered while a thread is free to wait for them.
+        let waiting = if actions.any() {
+            let slot = Slot::reserve();
+            if slot.is_none() {
+                debug!("too many toasts waiting for an action; sending without buttons");
             }
-            ACK_KEY => {
-                if let Some(ack_action) = ack.take() {
-                    trace!("ack action triggered from toast");
-                    let message = prompt_ack_message();
-                    ack_action.spawn_with_message(message);
+            slot.map(|slot| (slot, actions))
+        } else {
+            None
+        };
+        if let Some((_, actions)) = &waiting {
+            actions.add_buttons(&mut builder, params);
+        }
+
+        let known = params.ids.zip(params.tag);
+        if let Some(id) = known
+            .and_then(|(ids, tag)| ids.get(tag))
+            .and_then(|id| id.parse::<u32>().ok())
+        {
+            builder.id(id);
+        }
+
+        let handle = match builder.show() {
+            Ok(handle) => handle,
+            Err(err) => {
+                forget_server_caps();
+                if !server_reachable() {
+                    warn!(error = %err, "notification server unreachable");
+                    return Err(NotifyError::Unreachable);
                 }
+                // Each show opens a new bus connection: a server that just
+                // restarted answers this one.
+                debug!(error = %err, "notification server reachable, showing the toast again");
+                builder.show().map_err(|_| NotifyError::Backend)?
             }
-            _ => {}
+        };
+        if let Some((ids, tag)) = known {
+            ids.record(tag, handle.id().to_string());
+        }
+        let Some((slot, actions)) = waiting else {
+            return Ok(());
+        };
+        // The wait ends on a click, an expiry or a close; resident and
+        // sticky toasts may stay for hours.
+        let spawned = slot.spawn("linux-toast", move || {
+            handle.wait_for_action(move |action| actions.run(action));
         });
+        if let Err(err) = spawned {
+            warn!(error = %err, "failed to start notification action thread");
+        }
         Ok(())
     }
 
-    const fn map_urgency(urgency: ToastUrgency) -> LibUrgency {
+    /// What the buttons of a shown toast act on.
+    struct Actions {
+        open: Option<String>,
+        host: Option<String>,
+        ack: Option<AckAction>,
+        /// How long to mute for, and the state file recording it.
+        mute: Option<(Duration, PathBuf)>,
+        prompt_command: Vec<String>,
+        ack_prompt: bool,
+        confirm: Option<Confirm>,
+    }
+
+    impl Actions {
+        /// Whether any button would act on something.
+        const fn any(&self) -> bool {
+            self.open.is_some() || self.host.is_some() || self.ack.is_some() || self.mute.is_some()
+        }
+
+        fn add_buttons(&self, builder: &mut Notification, params: &ToastParams<'_>) {
+            if self.ack.is_some() {
+                builder.action(ACK_KEY, ACK_LABEL);
+                if let Some(count) = params.host_ack_action {
+                    builder.action(HOST_ACK_KEY, &format!("Acquitter les {count} de l'hôte"));
+                }
+                if params.comment_action {
+                    builder.action(COMMENT_KEY, COMMENT_LABEL);
+                }
+                if params.cause_action {
+                    builder.action(CAUSE_KEY, CAUSE_LABEL);
+                }
+            }
+            if let Some((mute_for, _)) = self.mute {
+                let label = format!("Silence {}", humantime::format_duration(mute_for));
+                builder.action(MUTE_KEY, &label);
+            }
+            if self.open.is_some() {
+                builder.action(OPEN_KEY, params.open_label);
+            }
+            if self.host.is_some() {
+                builder.action(HOST_KEY, HOST_LABEL);
+            }
+            builder.action(DISMISS_KEY, "Ignorer");
+        }
+
+        fn run(self, action: &str) {
+            match action {
+                OPEN_KEY => {
+                    if let Some(url) = self.open.as_deref() {
+                        super::super::browser::open_url(url);
+                    }
+                }
+                HOST_KEY => {
+                    if let Some(url) = self.host.as_deref() {
+                        super::super::browser::open_url(url);
+                    }
+                }
+                COMMENT_KEY => {
+                    if let Some(ack_action) = self.ack {
+                        trace!("comment action triggered from toast");
+                        if let Some(message) =
+                            prompt_message(&self.prompt_command, COMMENT_TITLE, COMMENT_TEXT)
+                        {
+                            ack_action.spawn_comment(message);
+                        }
+                    }
+                }
+                MUTE_KEY => {
+                    if let Some((mute_for, path)) = self.mute {
+                        match super::super::super::mute::mute_for(&path, mute_for) {
+                            Ok(until) => info!(until, "notifications muted from toast"),
+                            Err(err) => {
+                                warn!(path = %path.display(), error = %err, "failed to record mute");
+                            }
+                        }
+                    }
+                }
+                CAUSE_KEY => {
+                    if let Some(ack_action) = self.ack {
+                        trace!("cause action triggered from toast");
+                        ack_action.spawn_make_cause();
+                    }
+                }
+                ACK_KEY | HOST_ACK_KEY => {
+                    if let Some(ack_action) = self.ack {
+                        trace!(action, "ack action triggered from toast");
+                        if self.confirm.as_ref().is_some_and(|confirm| !confirm.ask()) {
+                            debug!("acknowledgement cancelled at confirmation");
+                            return;
+                        }
+                        let message = if self.ack_prompt {
+                            prompt_message(&self.prompt_command, PROMPT_TITLE, PROMPT_TEXT)
+                        } else {
+                            None
+                        };
+                        if action == HOST_ACK_KEY {
+                            ack_action.spawn_ack_host(message);
+                        } else {
+                            ack_action.spawn_with_message(message);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn close_toasts(ids: &NotificationIds, event_ids: &[String]) {
+        let known: Vec<(&str, u32)> = event_ids
+            .iter()
+            .filter_map(|event_id| {
+                let id = ids.get(event_id)?.parse().ok()?;
+                Some((event_id.as_str(), id))
+            })
+            .collect();
+        if known.is_empty() {
+            return;
+        }
+        let connection = match zbus::blocking::Connection::session() {
+            Ok(connection) => connection,
+            Err(err) => {
+                warn!(error = %err, "cannot reach the session bus to close notifications");
+                return;
+            }
+        };
+        for (event_id, id) in known {
+            let closed = connection.call_method(
+                Some("org.freedesktop.Notifications"),
+                "/org/freedesktop/Notifications",
+                Some("org.freedesktop.Notifications"),
+                "CloseNotification",
+                &(id,),
+            );
+            match closed {
+                Ok(_) => debug!(%event_id, id, "notification closed"),
+                // Already dismissed by the user or expired.
+                Err(err) => trace!(%event_id, id, error = %err, "notification not closed"),
+            }
+        }
+    }
+
+    /// Body features advertised by the notification server.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub(super) struct BodyCaps {
+        pub markup: bool,
+        pub hyperlinks: bool,
+    }
+
+    /// Capabilities of the running notification server, queried again after
+    /// a failed toast since a restarted server may be another one.
+    static SERVER_CAPS: Mutex<Option<BodyCaps>> = Mutex::new(None);
+
+    fn server_caps() -> BodyCaps {
+        if let Some(caps) = SERVER_CAPS.lock().ok().and_then(|caps| *caps) {
+            return caps;
+        }
+        match notify_rust::get_capabilities() {
+            Ok(caps) => {
+                let caps = BodyCaps {
+                    markup: caps.iter().any(|cap| cap == "body-markup"),
+                    hyperlinks: caps.iter().any(|cap| cap == "body-hyperlinks"),
+                };
+                if let Ok(mut cached) = SERVER_CAPS.lock() {
+                    *cached = Some(caps);
+                }
+                caps
+            }
+            Err(err) => {
+                trace!(error = %err, "failed to query notification server capabilities");
+                BodyCaps::default()
+            }
+        }
+    }
+
+    fn forget_server_caps() {
+        if let Ok(mut cached) = SERVER_CAPS.lock() {
+            *cached = None;
+        }
+    }
+
+    /// Whether the notification server answers on a fresh session bus
+    /// connection, starting it through D-Bus activation if needed. Tells a
+    /// server gone away from one rejecting the toast.
+    fn server_reachable() -> bool {
+        zbus::blocking::Connection::session()
+            .and_then(|connection| {
+                connection.call_method(
+                    Some("org.freedesktop.Notifications"),
+                    "/org/freedesktop/Notifications",
+                    Some("org.freedesktop.Notifications"),
+                    "GetServerInformation",
+                    &(),
+                )
+            })
+            .inspect_err(|err| trace!(error = %err, "notification server probe failed"))
+            .is_ok()
+    }
+
+    /// How a line of the Linux body is rendered.
+    #[derive(Clone, Copy)]
+    enum Line {
+        Text,
+        /// The event name, bold with markup.
+        Detail,
+        /// The open URL, a hyperlink while it is not cut short.
+        Link,
+    }
+
+    /// Build the notification body, using markup only when the server
+    /// understands it (and escaping every dynamic part in that case). The
+    /// body limit applies to the assembled text, before any markup.
+    pub(super) fn render_body(params: &ToastParams<'_>, caps: BodyCaps) -> String {
+        let mut parts = vec![(sanitize_text(params.body), Line::Text)];
+        if let Some(detail) = params.detail {
+            parts.push((sanitize_text(detail), Line::Detail));
+        }
+        if let Some(url) = params.open_url.filter(|_| caps.markup && caps.hyperlinks) {
+            parts.push((url.to_string(), Line::Link));
+        }
+        if let Some(attribution) = params.attribution {
+            parts.push((sanitize_text(attribution), Line::Text));
+        }
+
+        let text = |line: &str| {
+            if caps.markup {
+                escape_xml(line)
+            } else {
+                line.to_string()
+            }
+        };
+        params
+            .body_limit
+            .fit(parts, Line::Text)
+            .into_iter()
+            .map(|(line, kind)| match kind {
+                Line::Detail if caps.markup => format!("<b>{}</b>", text(&line)),
+                Line::Link if params.open_url == Some(line.as_str()) => {
+                    let url = escape_xml(&line);
+                    format!(r#"<a href="{url}">{url}</a>"#)
+                }
+                _ => text(&line),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Drop control and bidi-override characters that trigger names could use
+    /// to garble or disguise the rendered text.
+    pub(super) fn sanitize_text(raw: &str) -> String {
+        raw.chars()
+            .filter(|ch| {
+                !(ch.is_control() && *ch != '\n' && *ch != '\t'
+                    || matches!(ch, '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'))
+            })
+            .collect()
+    }
+
+    const fn map_urgency(urgency: Urgency) -> LibUrgency {
         match urgency {
-            ToastUrgency::Low => LibUrgency::Low,
-            ToastUrgency::Normal => LibUrgency::Normal,
-            ToastUrgency::Critical => LibUrgency::Critical,
+            Urgency::Low => LibUrgency::Low,
+            Urgency::Normal => LibUrgency::Normal,
+            Urgency::Critical => LibUrgency::Critical,
         }
     }
 
@@ -124,19 +636,81 @@ mod linux {
         }
     }
 
-    fn prompt_ack_message() -> Option<String> {
-        let output = Command::new("zenity")
-            .arg("--entry")
-            .arg("--title")
-            .arg("Acquitter l'evenement")
-            .arg("--text")
-            .arg("Message d'acquittement (laisser vide pour aucun)")
+    /// Yes/no question asked before acknowledging (`notify.confirm_ack`).
+    struct Confirm {
+        command: Vec<String>,
+        appname: String,
+        /// Summary of the toast being acknowledged, shown in the question.
+        summary: String,
+    }
+
+    impl Confirm {
+        fn ask(&self) -> bool {
+            let question = format!("Acquitter ce probleme ?\n{}", self.summary);
+            if let Some((program, args)) = self.command.split_first() {
+                let status = Command::new(program)
+                    .args(args.iter().map(|arg| {
+                        arg.replace("{title}", CONFIRM_TITLE)
+                            .replace("{text}", &question)
+                    }))
+                    .status();
+                match status {
+                    Ok(status) => return status.success(),
+                    Err(err) => {
+                        trace!(error = %err, program = %program, "confirmation tool unavailable, asking with a toast");
+                    }
+                }
+            }
+            self.ask_with_toast(&question)
+        }
+
+        /// Second toast offering to confirm or cancel; closing it cancels.
+        fn ask_with_toast(&self, question: &str) -> bool {
+            let handle = Notification::new()
+                .summary(CONFIRM_TITLE)
+                .body(question)
+                .appname(&self.appname)
+                .urgency(LibUrgency::Critical)
+                .timeout(LibTimeout::Never)
+                .action(CONFIRM_KEY, ACK_LABEL)
+                .action(CANCEL_KEY, "Annuler")
+                .show();
+            let handle = match handle {
+                Ok(handle) => handle,
+                Err(err) => {
+                    warn!(error = %err, "failed to show ack confirmation");
+                    return false;
+                }
+            };
+            let mut confirmed = false;
+            handle.wait_for_action(|action| confirmed = action == CONFIRM_KEY);
+            confirmed
+        }
+    }
+
+    /// Ask for a message with `command`; `None` when cancelled or left empty.
+    fn prompt_message(command: &[String], heading: &str, label: &str) -> Option<String> {
+        let (program, args) = command.split_first()?;
+        let output = Command::new(program)
+            .args(
+                args.iter()
+                    .map(|arg| arg.replace("{title}", heading).replace("{text}", label)),
+            )
             .output();
 
+        #[cfg(feature = "native-prompt")]
+        let output = match output {
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                trace!(program = %program, "prompt tool not found, using built-in dialog");
+                builtin_prompt(heading, label)
+            }
+            other => other,
+        };
+
         let output = match output {
             Ok(out) => out,
             Err(err) => {
-                trace!(error = %err, "failed to launch zenity for ack message");
+                trace!(error = %err, program = %program, "failed to launch ack message prompt");
                 return None;
             }
         };
@@ -148,89 +722,712 @@ mod linux {
         let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
         if text.is_empty() { None } else { Some(text) }
     }
+
+    #[cfg(feature = "native-prompt")]
+    fn builtin_prompt(heading: &str, label: &str) -> std::io::Result<std::process::Output> {
+        Command::new(std::env::current_exe()?)
+            .args(["prompt", "--title", heading, "--text", label])
+            .stdin(std::process::Stdio::null())
+            .output()
+    }
 }
 
-#[cfg(target_os = "windows")]
+#[cfg(all(target_os = "windows", feature = "windows-toasts"))]
 mod windows {
+    use std::fmt::Write as _;
+    use std::path::Path;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
     use alerting::error::NotifyError;
-    use tauri_winrt_notification::{
-        Duration as WinDuration, LoopableSound, Scenario, Sound, Toast,
+    use alerting::types::Urgency;
+    use url::Url;
+    use windows::Data::Xml::Dom::XmlDocument;
+    use windows::Foundation::{DateTime, IReference, PropertyValue};
+    use windows::UI::Notifications::{
+        NotificationData, NotificationSetting, NotificationUpdateResult, ToastNotification,
+        ToastNotificationManager, ToastNotifier,
     };
-    use windows::UI::Notifications::{NotificationSetting, ToastNotificationManager};
-    use windows::core::HSTRING;
+    use windows::core::{HSTRING, Interface};
+
+    use super::super::super::state::NotificationIds;
+    use super::super::{AckAction, ToastTimeout};
+    use super::{HOST_LABEL, ToastParams, escape_xml};
+
+    /// Maximum length Windows accepts for a toast group or tag.
+    const MAX_TAG_LEN: usize = 64;
+
+    /// Status line under the progress bar of an aggregate toast.
+    const PROGRESS_STATUS: &str = "Resolved";
+
+    /// Summary of a toast with progress, bound to the `summary` value of its
+    /// data so updates can change it.
+    const SUMMARY_BINDING: &str = "{summary}";
 
-    use super::super::{AckAction, ToastTimeout, ToastUrgency};
-    use super::ToastParams;
+    /// Last sequence number given to toast data, so Windows drops updates
+    /// that arrive out of order.
+    static PROGRESS_SEQUENCE: AtomicU32 = AtomicU32::new(0);
+
+    /// Approximate display time of a `duration="short"` toast.
+    const SHORT_DURATION_MS: u32 = 7_000;
+
+    /// Offset between the Windows epoch (1601-01-01) and the Unix epoch, in
+    /// 100ns ticks.
+    const UNIX_EPOCH_TICKS: i64 = 116_444_736_000_000_000;
+
+    /// AUMID used when no application id is configured; toasts then appear
+    /// under PowerShell.
+    const POWERSHELL_APP_ID: &str =
+        "{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\\WindowsPowerShell\\v1.0\\powershell.exe";
 
     pub fn send_toast(
         params: &ToastParams<'_>,
         ack_action: Option<&AckAction>,
     ) -> std::result::Result<(), NotifyError> {
         let _ = ack_action;
-        let summary = params.summary;
-        let body = params.body;
-        let urgency = params.urgency;
-        let timeout = params.timeout;
-        let appname = params.appname;
-
-        let app_id = if appname.trim().is_empty() {
-            Toast::POWERSHELL_APP_ID
-        } else {
-            appname
-        };
-        let timeout_kind = match timeout {
+        let app_id = app_id(params.appname);
+        let timeout_kind = match params.timeout {
             ToastTimeout::Never => "never",
             ToastTimeout::Default => "default",
             ToastTimeout::Milliseconds(_) => "custom",
         };
         tracing::debug!(
-            summary,
+            summary = params.summary,
             app_id,
             timeout = timeout_kind,
-            urgency = ?urgency,
+            urgency = ?params.urgency,
             "sending windows toast"
         );
 
-        match ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(app_id)) {
-            Ok(notifier) => {
-                if let Ok(setting) = notifier.Setting() {
-                    tracing::debug!(
-                        setting = ?setting,
-                        "windows toast notification setting"
-                    );
-                    if setting != NotificationSetting::Enabled {
-                        tracing::warn!(?setting, "toast notifications are disabled for this app");
-                    }
+        let notifier = toast_notifier(app_id)?;
+        if update_progress(&notifier, params) {
+            tracing::debug!("windows toast progress updated");
+            return Ok(());
+        }
+        let toast = build_toast(params).map_err(|err| {
+            tracing::warn!(error = %err, "failed to build windows toast");
+            NotifyError::Backend
+        })?;
+
+        if let Err(err) = notifier.Show(&toast) {
+            tracing::warn!(error = %err, "windows toast failed");
+            return Err(NotifyError::Backend);
+        }
+        if let Some(file) = wav_sound(params) {
+            play_wav(file);
+        }
+        if let Some((ids, tag)) = params.ids.zip(params.tag) {
+            let group = params.group.map(truncate_tag).unwrap_or_default();
+            ids.record(tag, format!("{group}\n{}", truncate_tag(tag)));
+        }
+        tracing::debug!("windows toast displayed");
+        Ok(())
+    }
+
+    /// Remove the toasts of `event_ids` from Action Center, by the group and
+    /// tag recorded when they were shown.
+    pub fn close_toasts(appname: &str, ids: &NotificationIds, event_ids: &[String]) {
+        let known: Vec<(&str, (String, String))> = event_ids
+            .iter()
+            .filter_map(|event_id| Some((event_id.as_str(), stored_toast(ids, event_id)?)))
+            .collect();
+        if known.is_empty() {
+            return;
+        }
+        let history = match ToastNotificationManager::History() {
+            Ok(history) => history,
+            Err(err) => {
+                tracing::debug!(error = %err, "cannot reach the toast history to remove toasts");
+                return;
+            }
+        };
+        let app_id = HSTRING::from(app_id(appname));
+        for (event_id, (group, tag)) in known {
+            let removed =
+                history.RemoveGroupedTagWithId(&HSTRING::from(tag), &HSTRING::from(group), &app_id);
+            match removed {
+                Ok(()) => tracing::debug!(event_id, "windows toast removed"),
+                Err(err) => {
+                    tracing::debug!(event_id, error = %err, "failed to remove windows toast");
                 }
             }
-            Err(err) => {
+        }
+    }
+
+    /// AUMID the toasts are shown and removed under.
+    fn app_id(appname: &str) -> &str {
+        if appname.trim().is_empty() {
+            POWERSHELL_APP_ID
+        } else {
+            appname
+        }
+    }
+
+    /// Group and tag recorded for the toast of `event_id`. Entries from before
+    /// groups were recorded only hold the tag.
+    fn stored_toast(ids: &NotificationIds, event_id: &str) -> Option<(String, String)> {
+        let stored = ids.get(event_id)?;
+        let (group, tag) = stored.split_once('\n').unwrap_or(("", &stored));
+        Some((group.to_string(), tag.to_string()))
+    }
+
+    /// Toast audio of unpackaged apps only accepts system sounds, so a custom
+    /// `.wav` is played by `PowerShell`'s `SoundPlayer` while the toast stays
+    /// silent. Other formats keep the system sound.
+    fn wav_sound<'a>(params: &ToastParams<'a>) -> Option<&'a Path> {
+        params.custom_sound().filter(|file| {
+            file.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"))
+        })
+    }
+
+    fn play_wav(file: &Path) {
+        let quoted = file.to_string_lossy().replace('\'', "''");
+        let spawned = std::process::Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-NonInteractive",
+                "-Command",
+                &format!("(New-Object Media.SoundPlayer '{quoted}').PlaySync()"),
+            ])
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn();
+        if let Err(err) = spawned {
+            tracing::warn!(error = %err, file = %file.display(), "failed to play notification sound");
+        }
+    }
+
+    fn toast_notifier(app_id: &str) -> std::result::Result<ToastNotifier, NotifyError> {
+        let notifier = ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(app_id))
+            .map_err(|err| {
                 tracing::warn!(error = %err, "failed to query toast manager");
+                NotifyError::Backend
+            })?;
+        if let Ok(setting) = notifier.Setting() {
+            tracing::debug!(setting = ?setting, "windows toast notification setting");
+            if setting != NotificationSetting::Enabled {
+                tracing::warn!(?setting, "toast notifications are disabled for this app");
             }
         }
+        Ok(notifier)
+    }
 
-        let toast = Toast::new(app_id)
-            .title(summary)
-            .text1(body)
-            .duration(match timeout {
-                ToastTimeout::Never => WinDuration::Long,
-                _ => WinDuration::Short,
-            })
-            .scenario(match urgency {
-                ToastUrgency::Critical => Scenario::Alarm,
-                ToastUrgency::Normal => Scenario::Reminder,
-                ToastUrgency::Low => Scenario::IncomingCall,
-            })
-            .sound(match urgency {
-                ToastUrgency::Critical => Some(Sound::Loop(LoopableSound::Alarm)),
-                ToastUrgency::Normal => Some(Sound::Default),
-                ToastUrgency::Low => Some(Sound::Reminder),
+    /// Update the data of the toast already shown under the same tag, so a
+    /// toast with progress changes in place instead of popping up again.
+    /// `false` when there is no such toast and it has to be shown.
+    fn update_progress(notifier: &ToastNotifier, params: &ToastParams<'_>) -> bool {
+        let (Some(progress), Some(tag)) = (params.progress, toast_tag(params)) else {
+            return false;
+        };
+        let group = params.group.map(truncate_tag).unwrap_or_default();
+        let updated = progress_data(params.summary, progress).and_then(|data| {
+            notifier.UpdateWithTagAndGroup(
+                &data,
+                &HSTRING::from(tag.as_str()),
+                &HSTRING::from(group),
+            )
+        });
+        match updated {
+            Ok(result) => result == NotificationUpdateResult::Succeeded,
+            Err(err) => {
+                tracing::debug!(error = %err, "windows toast progress not updated");
+                false
+            }
+        }
+    }
+
+    /// Values bound into the summary and progress bar of the toast.
+    fn progress_data(
+        summary: &str,
+        (resolved, total): (usize, usize),
+    ) -> windows::core::Result<NotificationData> {
+        let ratio = |count: usize| f64::from(u32::try_from(count).unwrap_or(u32::MAX));
+        let value = if total == 0 {
+            0.0
+        } else {
+            ratio(resolved) / ratio(total)
+        };
+        let data = NotificationData::new()?;
+        let values = data.Values()?;
+        for (key, text) in [
+            ("summary", summary.to_string()),
+            ("progressValue", format!("{value:.3}")),
+            ("progressValueString", format!("{resolved}/{total}")),
+            ("progressStatus", PROGRESS_STATUS.to_string()),
+        ] {
+            values.Insert(&HSTRING::from(key), &HSTRING::from(text))?;
+        }
+        data.SetSequenceNumber(PROGRESS_SEQUENCE.fetch_add(1, Ordering::Relaxed) + 1)?;
+        Ok(data)
+    }
+
+    /// Tag of the toast, as stored for a previous one or as given.
+    fn toast_tag(params: &ToastParams<'_>) -> Option<String> {
+        let stored = params
+            .ids
+            .zip(params.tag)
+            .and_then(|(ids, tag)| stored_toast(ids, tag))
+            .map(|(_, tag)| tag);
+        stored
+            .as_deref()
+            .or(params.tag)
+            .map(truncate_tag)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+    }
+
+    fn build_toast(params: &ToastParams<'_>) -> windows::core::Result<ToastNotification> {
+        let document = XmlDocument::new()?;
+        document.LoadXml(&HSTRING::from(toast_xml(params)))?;
+        let toast = ToastNotification::CreateToastNotification(&document)?;
+        if let Some(group) = params.group.map(truncate_tag).filter(|g| !g.is_empty()) {
+            toast.SetGroup(&HSTRING::from(group))?;
+        }
+        if let Some(tag) = toast_tag(params) {
+            toast.SetTag(&HSTRING::from(tag.as_str()))?;
+        }
+        if let Some(progress) = params.progress {
+            toast.SetData(&progress_data(params.summary, progress)?)?;
+        }
+        if let ToastTimeout::Milliseconds(ms) = params.timeout {
+            toast.SetExpirationTime(&expiration_time(ms)?)?;
+        }
+        Ok(toast)
+    }
+
+    /// Absolute expiration `ms` milliseconds from now, after which Action
+    /// Center drops the toast.
+    fn expiration_time(ms: u32) -> windows::core::Result<IReference<DateTime>> {
+        let expires = SystemTime::now() + Duration::from_millis(u64::from(ms));
+        let since_unix = expires.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let ticks = i64::try_from(since_unix.as_nanos() / 100).unwrap_or(i64::MAX);
+        PropertyValue::CreateDateTime(DateTime {
+            UniversalTime: UNIX_EPOCH_TICKS.saturating_add(ticks),
+        })?
+        .cast()
+    }
+
+    fn truncate_tag(value: &str) -> &str {
+        value
+            .char_indices()
+            .nth(MAX_TAG_LEN)
+            .map_or(value, |(idx, _)| &value[..idx])
+    }
+
+    fn toast_xml(params: &ToastParams<'_>) -> String {
+        let duration = match params.timeout {
+            ToastTimeout::Never => "long",
+            ToastTimeout::Milliseconds(ms) if ms > SHORT_DURATION_MS => "long",
+            _ => "short",
+        };
+        let scenario = match params.urgency {
+            Urgency::Critical => "alarm",
+            Urgency::Normal => "reminder",
+            Urgency::Low => "incomingCall",
+        };
+
+        let mut xml = String::new();
+        let _ = write!(xml, r#"<toast duration="{duration}" scenario="{scenario}""#);
+        // Clicking the body opens the event, or the host when there is no
+        // event link, like the first button does.
+        if let Some(url) = params.open_url.or(params.host_url) {
+            let _ = write!(
+                xml,
+                r#" launch="{}" activationType="protocol""#,
+                escape_xml(url)
+            );
+        }
+        xml.push_str(r#"><visual><binding template="ToastGeneric">"#);
+        if let Some(hero) = params.hero_image.or(params.accent).and_then(file_uri) {
+            let _ = write!(
+                xml,
+                r#"<image placement="hero" src="{}"/>"#,
+                escape_xml(&hero)
+            );
+        }
+        if let Some(icon) = params.icon.and_then(file_uri) {
+            let _ = write!(
+                xml,
+                r#"<image placement="appLogoOverride" src="{}"/>"#,
+                escape_xml(&icon)
+            );
+        }
+        let summary = if params.progress.is_some() {
+            SUMMARY_BINDING.to_string()
+        } else {
+            escape_xml(params.summary)
+        };
+        let (body, detail) = params.limited_text();
+        let _ = write!(
+            xml,
+            "<text>{summary}</text><text>{}</text>",
+            escape_xml(&body)
+        );
+        if let Some(detail) = detail {
+            let _ = write!(xml, "<text>{}</text>", escape_xml(&detail));
+        }
+        if params.progress.is_some() {
+            xml.push_str(
+                r#"<progress value="{progressValue}" valueStringOverride="{progressValueString}" status="{progressStatus}"/>"#,
+            );
+        }
+        if let Some(graph) = params.graph.and_then(file_uri) {
+            let _ = write!(xml, r#"<image src="{}"/>"#, escape_xml(&graph));
+        }
+        if let Some(attribution) = params.attribution {
+            let _ = write!(
+                xml,
+                r#"<text placement="attribution">{}</text>"#,
+                escape_xml(attribution)
+            );
+        }
+        xml.push_str("</binding></visual>");
+
+        xml.push_str(match (params.sound, params.urgency) {
+            // The custom sound is played alongside by `play_wav`.
+            _ if wav_sound(params).is_some() => r#"<audio silent="true"/>"#,
+            (Some(false), _) => r#"<audio silent="true"/>"#,
+            (_, Urgency::Critical) => {
+                r#"<audio src="ms-winsoundevent:Notification.Looping.Alarm" loop="true"/>"#
+            }
+            (None, Urgency::Normal) => "",
+            (Some(true), Urgency::Normal) => {
+                r#"<audio src="ms-winsoundevent:Notification.Default"/>"#
+            }
+            (_, Urgency::Low) => r#"<audio src="ms-winsoundevent:Notification.Reminder"/>"#,
+        });
+
+        let links = [
+            params.open_url.map(|url| (params.open_label, url)),
+            params.host_url.map(|url| (HOST_LABEL, url)),
+        ];
+        if links.iter().any(Option::is_some) {
+            xml.push_str("<actions>");
+            for (label, url) in links.into_iter().flatten() {
+                let _ = write!(
+                    xml,
+                    r#"<action content="{}" activationType="protocol" arguments="{}"/>"#,
+                    escape_xml(label),
+                    escape_xml(url)
+                );
+            }
+            xml.push_str("</actions>");
+        }
+        xml.push_str("</toast>");
+        xml
+    }
+
+    fn file_uri(path: &Path) -> Option<String> {
+        Url::from_file_path(path).ok().map(String::from)
+    }
+}
+
+#[cfg(all(target_os = "macos", feature = "macos-toasts"))]
+mod macos {
+    use std::path::Path;
+    use std::process::{Command, Stdio};
+    use std::sync::Once;
+
+    use alerting::error::NotifyError;
+    use alerting::types::Urgency;
+    use mac_notification_sys::{
+        MainButton, Notification, NotificationResponse, Sound, get_bundle_identifier,
+        set_application,
+    };
+    use tracing::{debug, warn};
+
+    use super::super::AckAction;
+    use super::super::waiters::Slot;
+    use super::{HOST_LABEL, ToastParams};
+
+    /// System sound of critical toasts, from `/System/Library/Sounds`.
+    const ALARM_SOUND: &str = "Sosumi";
+
+    static APPLICATION: Once = Once::new();
+
+    /// Owned copy of what the toast shows, so it can wait for a click on a
+    /// thread of its own.
+    struct Toast {
+        title: String,
+        subtitle: Option<String>,
+        message: String,
+        icon: Option<String>,
+        image: Option<String>,
+        sound: Option<Sound>,
+        /// Button label and URL, the first one also opened by a click on
+        /// the toast itself.
+        links: Vec<(String, String)>,
+    }
+
+    pub fn send_toast(
+        params: &ToastParams<'_>,
+        ack_action: Option<&AckAction>,
+    ) -> std::result::Result<(), NotifyError> {
+        let _ = ack_action;
+        APPLICATION.call_once(|| use_application(params.appname));
+        debug!(
+            summary = params.summary,
+            timeout = ?params.timeout,
+            urgency = ?params.urgency,
+            "sending macos notification"
+        );
+
+        let (message, subtitle) = params.limited_text();
+        let mut toast = Toast {
+            title: params.summary.to_string(),
+            subtitle,
+            message,
+            icon: params.icon.and_then(path_str),
+            image: params.graph.or(params.hero_image).and_then(path_str),
+            sound: sound(params),
+            links: [
+                params.open_url.map(|url| (params.open_label, url)),
+                params.host_url.map(|url| (HOST_LABEL, url)),
+            ]
+            .into_iter()
+            .flatten()
+            .map(|(label, url)| (label.to_string(), url.to_string()))
+            .collect(),
+        };
+
+        let slot = if toast.links.is_empty() {
+            None
+        } else {
+            Slot::reserve()
+        };
+        let Some(slot) = slot else {
+            if !toast.links.is_empty() {
+                debug!("too many toasts waiting for a click; sending without links");
+                toast.links.clear();
+            }
+            return toast.send().map(drop).map_err(|err| {
+                warn!(error = %err, "macos notification failed");
+                NotifyError::Backend
             });
+        };
+        // A toast with links blocks until it is clicked or dismissed.
+        slot.spawn("macos-toast", move || match toast.send() {
+            Ok(response) => toast.follow(&response),
+            Err(err) => warn!(error = %err, "macos notification failed"),
+        })
+        .map_err(|err| {
+            warn!(error = %err, "failed to start macos notification thread");
+            NotifyError::Backend
+        })
+    }
 
-        if let Err(err) = toast.show() {
-            tracing::warn!(error = %err, "windows toast failed");
-            return Err(NotifyError::Backend);
+    /// Post as the application `appname` names: a bundle identifier such
+    /// as `com.example.Alerting`, or the name of an installed application.
+    /// Otherwise the library default is kept.
+    fn use_application(appname: &str) {
+        let bundle = if appname.contains('.') {
+            Some(appname.to_string())
+        } else {
+            get_bundle_identifier(appname)
+        };
+        let Some(bundle) = bundle else {
+            debug!(appname, "no application bundle found; using the default");
+            return;
+        };
+        if let Err(err) = set_application(&bundle) {
+            warn!(error = %err, bundle = %bundle, "failed to set notification application");
         }
-        tracing::debug!("windows toast displayed");
-        Ok(())
+    }
+
+    /// Notification Center has no urgency: critical toasts get an alarm
+    /// sound, normal ones the default sound, low ones none, unless the
+    /// severity forces it. A custom sound is looked up by name in the
+    /// system and user `Library/Sounds` folders.
+    fn sound(params: &ToastParams<'_>) -> Option<Sound> {
+        if let Some(file) = params.custom_sound() {
+            let name = file.file_stem()?.to_string_lossy().into_owned();
+            return Some(Sound::Custom(name));
+        }
+        match (params.sound, params.urgency) {
+            (Some(false), _) | (None, Urgency::Low) => None,
+            (_, Urgency::Critical) => Some(Sound::Custom(ALARM_SOUND.to_string())),
+            (Some(true), _) | (None, Urgency::Normal) => Some(Sound::Default),
+        }
+    }
+
+    fn path_str(path: &Path) -> Option<String> {
+        path.to_str().map(str::to_string)
+    }
+
+    impl Toast {
+        fn send(&self) -> mac_notification_sys::error::NotificationResult<NotificationResponse> {
+            let labels: Vec<&str> = self.links.iter().map(|(label, _)| label.as_str()).collect();
+            let mut notification = Notification::new();
+            notification
+                .title(&self.title)
+                .maybe_subtitle(self.subtitle.as_deref())
+                .message(&self.message)
+                .maybe_sound(self.sound.clone());
+            if let Some(icon) = &self.icon {
+                notification.app_icon(icon);
+            }
+            if let Some(image) = &self.image {
+                notification.content_image(image);
+            }
+            match labels.as_slice() {
+                [] => {
+                    notification.asynchronous(true);
+                }
+                [label] => {
+                    notification
+                        .main_button(MainButton::SingleAction(label))
+                        .wait_for_click(true);
+                }
+                [first, ..] => {
+                    notification
+                        .main_button(MainButton::DropdownActions(first, &labels))
+                        .wait_for_click(true);
+                }
+            }
+            let sent = notification.send();
+            debug!(ok = sent.is_ok(), "macos notification done");
+            sent
+        }
+
+        /// Open the link the user picked, or the first one on a click on
+        /// the toast, like the body of a Windows toast.
+        fn follow(&self, response: &NotificationResponse) {
+            let url = match response {
+                NotificationResponse::ActionButton(label) => self
+                    .links
+                    .iter()
+                    .find(|(link, _)| link == label)
+                    .map(|(_, url)| url),
+                NotificationResponse::Click => self.links.first().map(|(_, url)| url),
+                _ => None,
+            };
+            let Some(url) = url else {
+                return;
+            };
+            let spawned = Command::new("open")
+                .arg(url)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn();
+            if let Err(err) = spawned {
+                warn!(error = %err, "failed to open url");
+            }
+        }
+    }
+}
+
+#[cfg(all(test, target_os = "linux", feature = "linux-toasts"))]
+mod tests {
+    use super::linux::{BodyCaps, render_body, sanitize_text};
+    use super::{BodyLimit, ToastParams, ToastTimeout};
+    use alerting::types::Urgency;
+
+    const fn params<'a>(detail: &'a str, open_url: Option<&'a str>) -> ToastParams<'a> {
+        ToastParams {
+            summary: "High – srv",
+            body: "Event #7 [UNACK]",
+            detail: Some(detail),
+            body_limit: BodyLimit {
+                max_lines: None,
+                max_chars: None,
+            },
+            urgency: Urgency::Critical,
+            timeout: ToastTimeout::Default,
+            appname: "test",
+            icon: None,
+            hero_image: None,
+            accent: None,
+            graph: None,
+            attribution: None,
+            open_url,
+            open_label: "Open",
+            host_url: None,
+            group: None,
+            tag: None,
+            ids: None,
+            desktop_entry: None,
+            prompt_command: &[],
+            ack_prompt: false,
+            confirm_command: None,
+            cause_action: false,
+            comment_action: false,
+            host_ack_action: None,
+            mute_action: None,
+            resident: false,
+            progress: None,
+            sound: None,
+            sound_file: None,
+        }
+    }
+
+    #[test]
+    fn markup_body_escapes_and_links() {
+        let caps = BodyCaps {
+            markup: true,
+            hyperlinks: true,
+        };
+        let body = render_body(
+            &params("Disk <90%> & rising", Some("https://z/?a=1&b=2")),
+            caps,
+        );
+        assert_eq!(
+            body,
+            "Event #7 [UNACK]\n<b>Disk &lt;90%&gt; &amp; rising</b>\n\
+             <a href=\"https://z/?a=1&amp;b=2\">https://z/?a=1&amp;b=2</a>"
+        );
+    }
+
+    #[test]
+    fn plain_body_is_left_untouched() {
+        let body = render_body(
+            &params("Disk <90%>", Some("https://z")),
+            BodyCaps::default(),
+        );
+        assert_eq!(body, "Event #7 [UNACK]\nDisk <90%>");
+    }
+
+    #[test]
+    fn body_limit_applies_to_the_assembled_body() {
+        let mut params = params("Disk <90%>", Some("https://z/?a=1"));
+        params.body = "Event #7\nhost db01";
+        params.body_limit = BodyLimit {
+            max_lines: Some(2),
+            max_chars: None,
+        };
+        let caps = BodyCaps {
+            markup: true,
+            hyperlinks: true,
+        };
+        assert_eq!(
+            render_body(&params, caps),
+            "Event #7\nhost db01\n…and 2 more"
+        );
+
+        params.body_limit = BodyLimit {
+            max_lines: None,
+            max_chars: Some(26),
+        };
+        assert_eq!(
+            render_body(&params, caps),
+            "Event #7\nhost db01\n<b>Disk &lt;…</b>"
+        );
+        assert_eq!(
+            params.limited_text(),
+            (
+                "Event #7\nhost db01".to_string(),
+                Some("Disk <…".to_string())
+            )
+        );
+    }
+
+    #[test]
+    fn sanitize_strips_control_and_bidi_characters() {
+        assert_eq!(
+            sanitize_text("disk\u{1b}[31m full\u{202E}gnp.exe\nnext"),
+            "disk[31m fullgnp.exe\nnext"
+        );
     }
 }
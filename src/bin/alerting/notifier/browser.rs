@@ -0,0 +1,140 @@
+//! Open event URLs in the user's browser.
+//!
+//! Flatpak and Snap browsers tend to crash when they inherit the environment
+//! of a hardened service unit, so they are started through `gtk-launch`
+//! (falling back to `gio open`) with only the session variables they need.
+
+use std::env;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use tracing::{debug, warn};
+
+/// Variables passed through to a sandboxed browser.
+const SESSION_VARS: &[&str] = &[
+    "HOME",
+    "USER",
+    "LANG",
+    "PATH",
+    "DISPLAY",
+    "WAYLAND_DISPLAY",
+    "XDG_RUNTIME_DIR",
+    "XDG_DATA_DIRS",
+    "XDG_CURRENT_DESKTOP",
+    "XDG_SESSION_TYPE",
+    "DBUS_SESSION_BUS_ADDRESS",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Sandbox {
+    Flatpak,
+    Snap,
+}
+
+pub(super) fn open_url(url: &str) {
+    let browser = default_browser();
+    let sandbox = browser
+        .as_deref()
+        .and_then(|id| sandbox_of(id, &export_dirs()));
+
+    let result = match (browser, sandbox) {
+        (Some(id), Some(kind)) => {
+            debug!(browser = %id, ?kind, "opening url with sandboxed browser");
+            launch_sandboxed(&id, url)
+        }
+        _ => spawn(Command::new("xdg-open").arg(url)),
+    };
+    if let Err(err) = result {
+        warn!(error = %err, "failed to open url");
+    }
+}
+
+fn default_browser() -> Option<String> {
+    let output = Command::new("xdg-settings")
+        .args(["get", "default-web-browser"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !id.is_empty()).then_some(id)
+}
+
+/// Directories where Flatpak and Snap export the desktop files of installed apps.
+fn export_dirs() -> Vec<(PathBuf, Sandbox)> {
+    let mut dirs = Vec::with_capacity(3);
+    if let Some(home) = env::var_os("HOME") {
+        dirs.push((
+            Path::new(&home).join(".local/share/flatpak/exports/share/applications"),
+            Sandbox::Flatpak,
+        ));
+    }
+    dirs.push((
+        PathBuf::from("/var/lib/flatpak/exports/share/applications"),
+        Sandbox::Flatpak,
+    ));
+    dirs.push((
+        PathBuf::from("/var/lib/snapd/desktop/applications"),
+        Sandbox::Snap,
+    ));
+    dirs
+}
+
+fn sandbox_of(desktop_id: &str, dirs: &[(PathBuf, Sandbox)]) -> Option<Sandbox> {
+    dirs.iter()
+        .find(|(dir, _)| dir.join(desktop_id).is_file())
+        .map(|(_, kind)| *kind)
+}
+
+fn launch_sandboxed(desktop_id: &str, url: &str) -> io::Result<()> {
+    let app = desktop_id.strip_suffix(".desktop").unwrap_or(desktop_id);
+    spawn(clean_env(Command::new("gtk-launch").arg(app).arg(url))).or_else(|err| {
+        debug!(error = %err, "gtk-launch unavailable, falling back to gio open");
+        spawn(clean_env(Command::new("gio").args(["open", url])))
+    })
+}
+
+fn clean_env(command: &mut Command) -> &mut Command {
+    command.env_clear();
+    for var in SESSION_VARS {
+        if let Some(value) = env::var_os(var) {
+            command.env(var, value);
+        }
+    }
+    command
+}
+
+fn spawn(command: &mut Command) -> io::Result<()> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(drop)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Sandbox, sandbox_of};
+
+    #[test]
+    fn detects_browser_by_exported_desktop_file() -> Result<(), Box<dyn std::error::Error>> {
+        let root = std::env::temp_dir().join(format!("alerting-browser-{}", uuid::Uuid::new_v4()));
+        let flatpak = root.join("flatpak");
+        let snap = root.join("snap");
+        std::fs::create_dir_all(&flatpak)?;
+        std::fs::create_dir_all(&snap)?;
+        std::fs::write(snap.join("firefox_firefox.desktop"), "")?;
+
+        let dirs = [(flatpak, Sandbox::Flatpak), (snap, Sandbox::Snap)];
+        assert_eq!(
+            sandbox_of("firefox_firefox.desktop", &dirs),
+            Some(Sandbox::Snap)
+        );
+        assert_eq!(sandbox_of("firefox.desktop", &dirs), None);
+
+        std::fs::remove_dir_all(root)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,82 @@
+//! Forward problem notifications to a phone paired with KDE Connect, so
+//! alerts still reach the user away from the desk.
+
+use std::process::{Command, Stdio};
+
+use alerting::config::NotifySettings;
+use tracing::{debug, warn};
+
+use super::NotificationItem;
+
+/// Ping the phone with `summary`, the problem name and its event URL, unless
+/// the problem is acknowledged or below `notify.kdeconnect.min_severity`.
+///
+/// `kdeconnect-cli` runs on the blocking pool: it waits for the daemon to
+/// reach the phone, which must not hold up the toasts.
+pub(super) fn forward(notify: &NotifySettings, summary: &str, item: &NotificationItem) {
+    let Some(kdeconnect) = &notify.kdeconnect else {
+        return;
+    };
+    if item.problem.acknowledged || item.problem.severity < kdeconnect.min_severity {
+        return;
+    }
+    let summary = format!(
+        "{}{summary}{}",
+        notify.summary_prefix, notify.summary_suffix
+    );
+    let message = ping_message(&summary, &item.problem.name, item.open_url.as_deref());
+    let device = kdeconnect.device.clone();
+    let span = tracing::Span::current();
+    tokio::task::spawn_blocking(move || {
+        let _entered = span.enter();
+        let output = Command::new("kdeconnect-cli")
+            .args(["--device", &device, "--ping-msg", &message])
+            .stdin(Stdio::null())
+            .output();
+        match output {
+            Ok(output) if output.status.success() => {
+                debug!(%device, "notification forwarded to KDE Connect");
+            }
+            Ok(output) => {
+                warn!(
+                    %device,
+                    status = %output.status,
+                    stderr = %String::from_utf8_lossy(&output.stderr).trim(),
+                    "kdeconnect-cli failed to forward notification"
+                );
+            }
+            Err(err) => warn!(error = %err, "failed to run kdeconnect-cli"),
+        }
+    });
+}
+
+/// One line each for the summary, the problem and the link, if any.
+fn ping_message(summary: &str, problem: &str, open_url: Option<&str>) -> String {
+    let mut message = format!("{summary}\n{problem}");
+    if let Some(url) = open_url {
+        message.push('\n');
+        message.push_str(url);
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ping_message;
+
+    #[test]
+    fn message_carries_summary_problem_and_link() {
+        assert_eq!(
+            ping_message(
+                "High – web01",
+                "Disk full",
+                Some("https://zbx.example/tr_events.php?eventid=42")
+            ),
+            "High – web01\nDisk full\nhttps://zbx.example/tr_events.php?eventid=42"
+        );
+        assert_eq!(
+            ping_message("High – web01", "Disk full", None),
+            "High – web01\nDisk full"
+        );
+    }
+}
@@ -1,67 +1,484 @@
+mod accent;
 mod backends;
+#[cfg(all(target_os = "linux", feature = "linux-toasts"))]
+mod browser;
+#[cfg(target_os = "linux")]
+mod kdeconnect;
+#[cfg(any(
+    all(target_os = "linux", feature = "linux-toasts"),
+    all(target_os = "macos", feature = "macos-toasts")
+))]
+mod waiters;
 
 use alerting::Result;
-use alerting::config::NotifySettings;
-use alerting::error::Error as AlertError;
-use alerting::types::Severity;
-use alerting::zbx_client::{HostMeta, Problem, ZbxClient};
+use alerting::config::{DisplaySettings, NotifySettings, ToastGrouping};
+use alerting::error::{Error as AlertError, NotifyError};
+use alerting::types::{AckFilter, DetailLevel, Severity, Urgency};
+use alerting::zbx_client::{Availability, EventRank, HostMeta, Problem, ProblemQuery, ZbxClient};
 use async_channel::Receiver;
+use chrono::{Local, NaiveDateTime};
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
 use std::convert::TryFrom;
-#[cfg(target_os = "windows")]
+use std::fmt::Write as _;
+use std::io::Write as _;
+#[cfg(all(target_os = "windows", feature = "windows-toasts"))]
 use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
 use tokio::task::JoinHandle;
-use tracing::{error, info};
+use tracing::{Instrument, Span, error, info, info_span, warn};
 
+use super::heartbeat::heartbeat_text;
+use super::history::{Entry, History, Outcome, unix_now};
+use super::poll_diff::ChangeCounts;
+use super::state::NotificationIds;
+use super::updates::EventNews;
 use backends::ToastParams;
 
+/// Notices kept while the notification server is unreachable; the oldest
+/// are dropped beyond this.
+const MAX_PENDING: usize = 64;
+/// How often kept notices are tried again when nothing else comes in.
+const PENDING_RETRY: Duration = Duration::from_secs(5);
+
+/// Show the notices of `rx` until it closes. Without `client`, for
+/// simulated problems, toast buttons only log what they would have sent.
 pub async fn run_notifier(
-    rx: Receiver<NotificationItem>,
+    rx: Receiver<Notice>,
     notify: NotifySettings,
-    client: ZbxClient,
+    display: DisplaySettings,
+    client: Option<ZbxClient>,
+    ids: NotificationIds,
+    history: History,
     dry_run: bool,
 ) {
-    while let Ok(item) = rx.recv().await {
+    let mut pending = VecDeque::new();
+    loop {
+        let received = if pending.is_empty() {
+            rx.recv().await.map(Some)
+        } else {
+            tokio::time::timeout(PENDING_RETRY, rx.recv())
+                .await
+                .map_or(Ok(None), |received| received.map(Some))
+        };
+        let Ok(notice) = received else {
+            break;
+        };
         if dry_run {
+            if let Some(notice) = &notice {
+                log_dry_run(notice);
+                print_dry_run(notice, &notify, &display);
+            }
+            continue;
+        }
+        if let Some(Notice::Close(event_ids)) = &notice {
+            forget_pending(&mut pending, event_ids);
+        }
+        let deliver = |notice| deliver(notice, &notify, &display, client.as_ref(), &ids, &history);
+        while let Some(held) = pending.pop_front() {
+            if let Some(held) = deliver(held) {
+                pending.push_front(held);
+                break;
+            }
+        }
+        if let Some(unsent) = notice.and_then(deliver) {
+            hold(&mut pending, unsent);
+        }
+    }
+}
+
+/// Keep a notice the notification server could not take for a later try.
+fn hold(pending: &mut VecDeque<Notice>, notice: Notice) {
+    if pending.len() >= MAX_PENDING {
+        pending.pop_front();
+        warn!(
+            max = MAX_PENDING,
+            "too many notifications waiting for the notification server; dropping the oldest"
+        );
+    }
+    pending.push_back(notice);
+    warn!(
+        pending = pending.len(),
+        "notification server unreachable; notification kept for a retry"
+    );
+}
+
+/// Drop kept problems that were resolved or acknowledged meanwhile.
+fn forget_pending(pending: &mut VecDeque<Notice>, event_ids: &[String]) {
+    let gone = |item: &NotificationItem| event_ids.contains(&item.problem.event_id);
+    pending.retain_mut(|notice| match notice {
+        Notice::Problem(item) => !gone(item),
+        Notice::Digest(items) | Notice::HostBatch(items) | Notice::StormOver(items, _) => {
+            items.retain(|item| !gone(item));
+            !items.is_empty()
+        }
+        _ => true,
+    });
+}
+
+const fn unreachable(err: &AlertError) -> bool {
+    matches!(err, AlertError::Notify(NotifyError::Unreachable))
+}
+
+fn log_dry_run(notice: &Notice) {
+    match notice {
+        Notice::Problem(item) => {
             info!(
                 event_id = %item.problem.event_id,
+                correlation_id = %item.correlation_id,
                 host = item.host.as_ref().map_or("<unknown>", |h| h.display_name.as_str()),
                 severity = ?item.problem.severity,
                 "dry-run: would emit notification"
             );
-            continue;
         }
+        Notice::Digest(items) => {
+            info!(count = items.len(), "dry-run: would emit catch-up digest");
+        }
+        Notice::Storm {
+            problems,
+            hosts,
+            resolved,
+        } => {
+            info!(
+                problems,
+                hosts, resolved, "dry-run: would emit alert storm notification"
+            );
+        }
+        Notice::StormOver(items, unlisted) => {
+            info!(
+                count = items.len() + unlisted,
+                "dry-run: would emit alert storm digest"
+            );
+        }
+        Notice::Heartbeat { .. } => info!("dry-run: would emit heartbeat"),
+        Notice::Watchdog { stalled } => {
+            info!(
+                stalled_secs = stalled.map(|silent| silent.as_secs()),
+                "dry-run: would emit watchdog notification"
+            );
+        }
+        Notice::HostBatch(items) => {
+            info!(count = items.len(), "dry-run: would emit host batch");
+        }
+        Notice::Close(event_ids) => {
+            info!(
+                count = event_ids.len(),
+                "dry-run: would close notifications"
+            );
+        }
+        Notice::PollSummary { counts, active } => {
+            info!(%counts, active, "dry-run: would emit poll summary");
+        }
+        Notice::EventUpdate(news) => {
+            info!(
+                event_id = %news.event_id,
+                author = %news.author,
+                "dry-run: would emit problem update"
+            );
+        }
+    }
+}
+
+/// One line of the `--dry-run` NDJSON output, per problem a toast would
+/// have shown.
+#[derive(Serialize)]
+struct DryRunLine<'a> {
+    kind: &'static str,
+    event_id: &'a str,
+    correlation_id: &'a str,
+    host: Option<&'a str>,
+    severity: Severity,
+    acknowledged: bool,
+    summary: String,
+    body: String,
+    open_url: Option<&'a str>,
+    host_url: Option<&'a str>,
+}
+
+/// Write the problems of `notice` to stdout as NDJSON, for `jq` and test
+/// harnesses. Notices without problems (heartbeat, storm...) are only logged.
+fn print_dry_run(notice: &Notice, notify: &NotifySettings, display: &DisplaySettings) {
+    let (kind, items): (&str, Vec<&NotificationItem>) = match notice {
+        Notice::Problem(item) => ("problem", vec![item]),
+        Notice::Digest(items) => ("digest", items.iter().collect()),
+        Notice::HostBatch(items) => ("host_batch", items.iter().collect()),
+        Notice::StormOver(items, _) => ("storm_over", items.iter().collect()),
+        _ => return,
+    };
+    let mut out = std::io::stdout().lock();
+    for item in items {
+        let host = item.host.as_ref().map(|h| h.display_name.as_str());
+        let line = DryRunLine {
+            kind,
+            event_id: &item.problem.event_id,
+            correlation_id: &item.correlation_id,
+            host,
+            severity: item.problem.severity,
+            acknowledged: item.problem.acknowledged,
+            summary: problem_summary(notify, item, host.unwrap_or("<unknown>")),
+            body: BodyLimit::of(notify).apply(&toast_body(item, notify.detail, display)),
+            open_url: item.open_url.as_deref(),
+            host_url: item.host_url.as_deref(),
+        };
+        let written = serde_json::to_writer(&mut out, &line)
+            .map_err(std::io::Error::from)
+            .and_then(|()| out.write_all(b"\n"));
+        if let Err(err) = written {
+            warn!(error = %err, "failed to write dry-run line");
+        }
+    }
+}
 
-        if let Err(err) = send_notification(&notify, &client, &item) {
-            error!(error = %err, event_id = %item.problem.event_id, "failed to send notification");
+/// Show `notice`; it is handed back when the notification server was
+/// unreachable, to be tried again.
+fn deliver(
+    notice: Notice,
+    notify: &NotifySettings,
+    display: &DisplaySettings,
+    client: Option<&ZbxClient>,
+    ids: &NotificationIds,
+    history: &History,
+) -> Option<Notice> {
+    match notice {
+        Notice::Problem(item) => {
+            let sent = item
+                .span
+                .in_scope(|| send_notification(notify, display, client, ids, history, &item));
+            match sent {
+                Ok(()) => {
+                    history.record(Outcome::Notified, &item.problem, item.host.as_ref());
+                }
+                Err(err) if unreachable(&err) => return Some(Notice::Problem(item)),
+                Err(err) => item.span.in_scope(|| {
+                    error!(error_code = err.code(), error = %err, "failed to send notification");
+                }),
+            }
+        }
+        Notice::Digest(items) => {
+            let sent = send_digest(notify, &items);
+            return settle(sent, history, items, Notice::Digest, "catch-up digest");
+        }
+        Notice::Storm {
+            problems,
+            hosts,
+            resolved,
+        } => {
+            if let Err(err) = send_storm(notify, ids, problems, hosts, resolved) {
+                error!(
+                    error_code = err.code(),
+                    error = %err,
+                    "failed to send alert storm notification"
+                );
+            }
+        }
+        Notice::StormOver(items, unlisted) => {
+            let sent = send_storm_over(notify, ids, &items, unlisted);
+            return settle(
+                sent,
+                history,
+                items,
+                |items| Notice::StormOver(items, unlisted),
+                "alert storm digest",
+            );
+        }
+        Notice::Heartbeat { last_poll, active } => {
+            if let Err(err) = send_heartbeat(notify, ids, last_poll, active) {
+                error!(
+                    error_code = err.code(),
+                    error = %err,
+                    "failed to send heartbeat"
+                );
+            }
+        }
+        Notice::Watchdog { stalled } => {
+            if let Err(err) = send_watchdog(notify, ids, stalled) {
+                error!(
+                    error_code = err.code(),
+                    error = %err,
+                    "failed to send watchdog notification"
+                );
+            }
+        }
+        Notice::Close(event_ids) => backends::close_toasts(&notify.appname, ids, &event_ids),
+        Notice::PollSummary { counts, active } => {
+            if let Err(err) = send_poll_summary(notify, ids, counts, active) {
+                error!(
+                    error_code = err.code(),
+                    error = %err,
+                    "failed to send poll summary"
+                );
+            }
+        }
+        Notice::HostBatch(items) => {
+            let sent = send_host_batch(notify, &items);
+            return settle(sent, history, items, Notice::HostBatch, "host batch");
+        }
+        Notice::EventUpdate(news) => {
+            if let Err(err) = send_event_update(notify, ids, &news) {
+                error!(
+                    error_code = err.code(),
+                    error = %err,
+                    event_id = %news.event_id,
+                    "failed to send problem update"
+                );
+            }
         }
     }
+    None
+}
+
+/// Record the problems of a toast covering several as notified, or hand
+/// them back in `notice` when the notification server was unreachable.
+fn settle(
+    sent: Result<()>,
+    history: &History,
+    items: Vec<NotificationItem>,
+    notice: impl FnOnce(Vec<NotificationItem>) -> Notice,
+    what: &str,
+) -> Option<Notice> {
+    match sent {
+        Ok(()) => record_notified(history, &items),
+        Err(err) if unreachable(&err) => return Some(notice(items)),
+        Err(err) => {
+            error!(
+                error_code = err.code(),
+                error = %err,
+                count = items.len(),
+                "failed to send {what}"
+            );
+        }
+    }
+    None
+}
+
+fn record_notified(history: &History, items: &[NotificationItem]) {
+    for item in items {
+        history.record(Outcome::Notified, &item.problem, item.host.as_ref());
+    }
+}
+
+/// What the poll loop hands over to the notifier task.
+pub enum Notice {
+    /// A single problem, shown as its own toast.
+    Problem(Box<NotificationItem>),
+    /// Problems that appeared while the machine was suspended, summarised in
+    /// one toast instead of a burst.
+    Digest(Vec<NotificationItem>),
+    /// New problems of one host found by the same poll, shown as one toast
+    /// when `notify.batch_by_host` is set.
+    HostBatch(Vec<NotificationItem>),
+    /// An alert storm started, grew or some of its problems were resolved:
+    /// individual toasts are held back.
+    Storm {
+        problems: usize,
+        hosts: usize,
+        resolved: usize,
+    },
+    /// The storm subsided; reconcile with everything that was held back,
+    /// and the number of problems past the cap that were only counted.
+    StormOver(Vec<NotificationItem>, usize),
+    /// Scheduled proof of life (`notify.heartbeat`).
+    Heartbeat {
+        last_poll: Option<Duration>,
+        active: usize,
+    },
+    /// No poll succeeded for this long (`app.watchdog_deadline`); `None`
+    /// once polling recovered.
+    Watchdog { stalled: Option<Duration> },
+    /// Problems acknowledged (`notify.close_on_ack`), or resolved too with
+    /// `notify.resident`: withdraw their toasts.
+    Close(Vec<String>),
+    /// What the last poll changed (`notify.poll_summary`).
+    PollSummary { counts: ChangeCounts, active: usize },
+    /// Someone else commented on or acknowledged a problem already shown
+    /// (`notify.comment_updates`).
+    EventUpdate(Box<EventNews>),
 }
 
 pub struct NotificationItem {
     pub(crate) problem: Problem,
     pub(crate) host: Option<HostMeta>,
     pub(crate) open_url: Option<String>,
+    /// Host dashboard link rendered from `app.host_url_fmt`.
+    pub(crate) host_url: Option<String>,
+    /// Other active problems on the same host in the poll that found this one.
+    pub(crate) others_on_host: usize,
+    /// Unacknowledged problems on the same host in that poll, this one
+    /// included.
+    pub(crate) unacked_on_host: usize,
+    /// Business services impacted by the problem.
+    pub(crate) services: Vec<String>,
+    /// Trigger graph downloaded for the toast.
+    pub(crate) graph: Option<PathBuf>,
+    /// Expanded trigger expression, looked up at the `debug` detail level.
+    pub(crate) trigger_expression: Option<String>,
+    /// Shared by the queueing log line, the toast and any ack it triggers.
+    pub(crate) correlation_id: String,
+    /// `notification` span, opened under the poll that queued the item.
+    pub(crate) span: Span,
 }
 
+/// Acknowledgement run from a toast button; only the Linux backend has any.
 #[derive(Clone)]
+#[cfg_attr(
+    not(all(target_os = "linux", feature = "linux-toasts")),
+    allow(dead_code)
+)]
 struct AckAction {
-    client: ZbxClient,
+    /// `None` for a simulated problem: nothing is sent.
+    client: Option<ZbxClient>,
     event_id: String,
+    /// Host of the event, for acknowledging all of its problems at once.
+    host: Option<HostMeta>,
+    history: History,
+    /// History entry written once the ack went through.
+    entry: Entry,
+    span: Span,
 }
 
+#[cfg_attr(
+    not(all(target_os = "linux", feature = "linux-toasts")),
+    allow(dead_code)
+)]
 impl AckAction {
-    pub(crate) fn new(client: &ZbxClient, event_id: &str) -> Self {
+    /// Must be created inside the notification's span so the ack nests under it.
+    pub(crate) fn new(
+        client: Option<&ZbxClient>,
+        history: &History,
+        item: &NotificationItem,
+    ) -> Self {
+        let event_id = item.problem.event_id.as_str();
+        let correlation_id = item.correlation_id.as_str();
         Self {
-            client: client.clone(),
+            client: client.map(|client| client.clone().with_correlation_id(correlation_id)),
             event_id: event_id.to_string(),
+            host: item.host.clone(),
+            history: history.clone(),
+            entry: Entry::new(Outcome::Acked, &item.problem, item.host.as_ref()),
+            span: info_span!("ack", %correlation_id, %event_id),
         }
     }
 
     pub(crate) fn spawn_with_message(self, message: Option<String>) -> JoinHandle<()> {
-        let Self { client, event_id } = self;
-        tokio::spawn(async move {
+        let Self {
+            client,
+            event_id,
+            history,
+            entry,
+            span,
+            ..
+        } = self;
+        let Some(client) = client else {
+            return simulated(span, event_id, "acknowledge");
+        };
+        let task = async move {
             match client.ack_event(&event_id, message.clone()).await {
                 Ok(()) => {
+                    history.append(&Entry {
+                        at: unix_now(),
+                        ..entry
+                    });
                     if let Some(msg) = message {
                         tracing::info!(%event_id, message = %msg, "event acknowledged from toast");
                     } else {
@@ -69,21 +486,166 @@ impl AckAction {
                     }
                 }
                 Err(err) => {
-                    tracing::warn!(%event_id, error = %err, "failed to acknowledge event from toast");
+                    tracing::warn!(
+                        %event_id,
+                        error_code = err.code(),
+                        error = %err,
+                        "failed to acknowledge event from toast"
+                    );
+                }
+            }
+        };
+        tokio::spawn(task.instrument(span))
+    }
+
+    /// Acknowledge every problem of the event's host that is still
+    /// unacknowledged, in one `event.acknowledge` call.
+    pub(crate) fn spawn_ack_host(self, message: Option<String>) -> JoinHandle<()> {
+        let Self {
+            client,
+            event_id,
+            host,
+            history,
+            span,
+            ..
+        } = self;
+        let Some(client) = client else {
+            return simulated(span, event_id, "acknowledge the host");
+        };
+        let task = async move {
+            let Some(host_id) = host.as_ref().and_then(|host| host.host_id.clone()) else {
+                return;
+            };
+            let query = ProblemQuery {
+                ack: AckFilter::Unacked,
+                event_ids: Vec::new(),
+                host_ids: vec![host_id.clone()],
+                severities: Vec::new(),
+                started_before: None,
+            };
+            let result = match client.find_problems(&query).await {
+                Ok(problems) => {
+                    let event_ids: Vec<&str> = problems
+                        .iter()
+                        .map(|problem| problem.event_id.as_str())
+                        .collect();
+                    client
+                        .ack_events(&event_ids, message)
+                        .await
+                        .map(|()| problems)
+                }
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(problems) => {
+                    for problem in &problems {
+                        history.append(&Entry::new(Outcome::Acked, problem, host.as_ref()));
+                    }
+                    tracing::info!(
+                        %host_id,
+                        count = problems.len(),
+                        "host problems acknowledged from toast"
+                    );
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        %host_id,
+                        error_code = err.code(),
+                        error = %err,
+                        "failed to acknowledge host problems from toast"
+                    );
                 }
             }
-        })
+        };
+        tokio::spawn(task.instrument(span))
+    }
+
+    /// Add `message` to the event, leaving it unacknowledged.
+    pub(crate) fn spawn_comment(self, message: String) -> JoinHandle<()> {
+        let Self {
+            client,
+            event_id,
+            span,
+            ..
+        } = self;
+        let Some(client) = client else {
+            return simulated(span, event_id, "comment");
+        };
+        let task = async move {
+            match client.comment_event(&event_id, &message).await {
+                Ok(()) => tracing::info!(%event_id, %message, "comment added from toast"),
+                Err(err) => {
+                    tracing::warn!(
+                        %event_id,
+                        error_code = err.code(),
+                        error = %err,
+                        "failed to add comment from toast"
+                    );
+                }
+            }
+        };
+        tokio::spawn(task.instrument(span))
+    }
+
+    /// Turn the event into a cause, for a symptom the user finds misfiled.
+    pub(crate) fn spawn_make_cause(self) -> JoinHandle<()> {
+        let Self {
+            client,
+            event_id,
+            span,
+            ..
+        } = self;
+        let Some(client) = client else {
+            return simulated(span, event_id, "make a cause");
+        };
+        let task = async move {
+            match client.rank_event(&event_id, &EventRank::Cause).await {
+                Ok(()) => tracing::info!(%event_id, "event made a cause from toast"),
+                Err(err) => {
+                    tracing::warn!(
+                        %event_id,
+                        error_code = err.code(),
+                        error = %err,
+                        "failed to make event a cause from toast"
+                    );
+                }
+            }
+        };
+        tokio::spawn(task.instrument(span))
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-enum ToastUrgency {
-    Low,
-    Normal,
-    Critical,
+/// Log the button a simulated problem's toast got instead of calling Zabbix.
+#[cfg_attr(
+    not(all(target_os = "linux", feature = "linux-toasts")),
+    allow(dead_code)
+)]
+fn simulated(span: Span, event_id: String, action: &'static str) -> JoinHandle<()> {
+    let task = async move {
+        info!(%event_id, action, "simulated problem: nothing sent to zabbix");
+    };
+    tokio::spawn(task.instrument(span))
 }
 
+/// Group and tag of the catch-up digest, so a newer digest replaces the last.
+const DIGEST_TAG: &str = "digest";
+/// Group and tag shared by the storm toast and its closing digest.
+const STORM_TAG: &str = "storm";
+/// Group and tag of the heartbeat, so each one replaces the last.
+const HEARTBEAT_TAG: &str = "heartbeat";
+/// Group and tag of the watchdog warning, replaced when polling recovers.
+const WATCHDOG_TAG: &str = "watchdog";
+/// Group and tag of the poll summary, so each one replaces the last.
+const SUMMARY_TAG: &str = "summary";
+
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(
+    not(any(
+        all(target_os = "linux", feature = "linux-toasts"),
+        all(target_os = "windows", feature = "windows-toasts")
+    )),
+    allow(dead_code)
+)]
 enum ToastTimeout {
     Default,
     Never,
@@ -92,62 +654,646 @@ enum ToastTimeout {
 
 fn send_notification(
     notify: &NotifySettings,
-    client: &ZbxClient,
+    display: &DisplaySettings,
+    client: Option<&ZbxClient>,
+    ids: &NotificationIds,
+    history: &History,
     item: &NotificationItem,
 ) -> Result<()> {
     let severity = item.problem.severity;
-    let urgency = match severity {
-        Severity::Disaster | Severity::High => ToastUrgency::Critical,
-        Severity::Average | Severity::Warning => ToastUrgency::Normal,
-        Severity::Info => ToastUrgency::Low,
+    let acked = item.problem.acknowledged;
+    // Acknowledged problems are someone else's now: keep them quiet and low.
+    let (urgency, escalated) = if acked {
+        (Urgency::Low, false)
+    } else {
+        urgency_for_severity(notify, severity, Local::now().naive_local())
     };
 
     let timeout_ms = notify.timeout.and_then(|dur| u128_to_u32(dur.as_millis()));
-    let timeout = compute_timeout(notify.sticky, timeout_ms, notify.default_timeout);
+    let timeout = compute_timeout(
+        notify.sticky || escalated,
+        timeout_ms,
+        notify.default_timeout,
+    );
 
     let host_label = item
         .host
         .as_ref()
         .map_or("<unknown>", |h| h.display_name.as_str());
-
-    let summary = format!("{severity:?} – {host_label}");
-    let body = format!(
-        "Event #{} {}\n{}",
-        item.problem.event_id,
-        if item.problem.acknowledged {
-            "[ACK]"
-        } else {
-            "[UNACK]"
-        },
-        item.problem.name
-    );
+    let summary = problem_summary(notify, item, host_label);
+    let body = toast_body(item, notify.detail, display);
 
     let open_url = item.open_url.clone();
+    let group = match notify.group_by {
+        ToastGrouping::Host => host_label.to_string(),
+        ToastGrouping::Severity => severity.to_string(),
+    };
 
-    #[cfg(not(target_os = "linux"))]
-    let _ = client;
+    #[cfg(not(all(target_os = "linux", feature = "linux-toasts")))]
+    let _ = (client, history);
 
-    #[cfg(target_os = "linux")]
+    #[cfg(all(target_os = "linux", feature = "linux-toasts"))]
     let ack_action =
-        (!item.problem.acknowledged).then(|| AckAction::new(client, &item.problem.event_id));
-    #[cfg(not(target_os = "linux"))]
+        offers_ack(notify, &item.problem).then(|| AckAction::new(client, history, item));
+    #[cfg(not(all(target_os = "linux", feature = "linux-toasts")))]
     let ack_action = None;
 
+    let accent = if acked {
+        None
+    } else {
+        notify.style_for(severity).color.and_then(accent::file)
+    };
+
     let params = ToastParams {
         summary: &summary,
         body: &body,
+        detail: Some(&item.problem.name),
+        body_limit: BodyLimit::default(),
         urgency,
         timeout,
         appname: &notify.appname,
-        icon: notify.icon.as_deref(),
+        icon: if acked {
+            notify.acked_icon.as_deref().or(notify.icon.as_deref())
+        } else {
+            notify.icon.as_deref()
+        },
+        hero_image: notify.hero_image.as_deref(),
+        accent: accent.as_deref(),
+        graph: item.graph.as_deref(),
+        attribution: notify.attribution.as_deref(),
         open_url: open_url.as_deref(),
+        host_url: item.host_url.as_deref(),
         open_label: &notify.open_label,
+        group: Some(&group),
+        tag: Some(&item.problem.event_id),
+        ids: Some(ids),
+        desktop_entry: notify.desktop_entry.as_deref(),
+        prompt_command: &notify.prompt_command,
+        ack_prompt: notify.ack_prompt.asks(urgency),
+        confirm_command: notify
+            .confirm_ack
+            .then_some(notify.confirm_command.as_slice()),
+        cause_action: notify.rank_action && item.problem.cause_event_id.is_some(),
+        comment_action: notify.comment_action,
+        host_ack_action: host_ack_count(notify, item),
+        mute_action: notify
+            .mute_action
+            .filter(|_| !acked && severity < notify.mute_floor),
+        resident: notify.resident && urgency == Urgency::Critical,
+        progress: None,
+        sound_file: notify.sound_file.as_deref(),
+        sound: if acked {
+            Some(false)
+        } else {
+            notify.style_for(severity).sound
+        },
     };
 
-    backends::send_toast(&params, ack_action.as_ref()).map_err(AlertError::from)?;
+    #[cfg(target_os = "linux")]
+    kdeconnect::forward(notify, &summary, item);
+
+    show(notify, &params, ack_action.as_ref())?;
     Ok(())
 }
 
+/// Whether the toast of `problem` carries the ack buttons: not for problems
+/// already acknowledged nor below `notify.ack_min_severity`.
+#[cfg_attr(
+    not(all(target_os = "linux", feature = "linux-toasts")),
+    allow(dead_code)
+)]
+fn offers_ack(notify: &NotifySettings, problem: &Problem) -> bool {
+    !problem.acknowledged && problem.severity >= notify.ack_min_severity
+}
+
+/// Summary of a problem toast, marking hosts Zabbix cannot reach.
+fn problem_summary(notify: &NotifySettings, item: &NotificationItem, host_label: &str) -> String {
+    let severity = item.problem.severity;
+    let acked = item.problem.acknowledged;
+    let unreachable = item
+        .host
+        .as_ref()
+        .is_some_and(|h| h.availability == Some(Availability::Unavailable));
+    let summary = if unreachable {
+        toast_summary(severity, &format!("{host_label} (unreachable)"), acked)
+    } else {
+        toast_summary(severity, host_label, acked)
+    };
+    severity_marked(notify, severity, summary)
+}
+
+/// Problems the "ack the host" button would cover, when it is offered: the
+/// host must be known by id and have other unacknowledged problems.
+fn host_ack_count(notify: &NotifySettings, item: &NotificationItem) -> Option<usize> {
+    let known = item
+        .host
+        .as_ref()
+        .is_some_and(|host| host.host_id.is_some());
+    (notify.host_ack_action && known && item.unacked_on_host > 1).then_some(item.unacked_on_host)
+}
+
+/// Marker put in front of the summary of problems that were already
+/// acknowledged, so they can be told apart from new alerts at a glance.
+const ACKED_PREFIX: &str = "[ACK by user]";
+
+fn toast_summary(severity: Severity, host_label: &str, acked: bool) -> String {
+    if acked {
+        format!("{ACKED_PREFIX} {severity:?} – {host_label}")
+    } else {
+        format!("{severity:?} – {host_label}")
+    }
+}
+
+/// `summary` behind the severity's coloured dot when `notify.severity_emoji`
+/// is set, for desktops that ignore the urgency styling.
+fn severity_marked(notify: &NotifySettings, severity: Severity, summary: String) -> String {
+    if notify.severity_emoji {
+        format!("{} {summary}", severity.emoji())
+    } else {
+        summary
+    }
+}
+
+/// Summarise several problems in a single toast after resuming from suspend.
+fn send_digest(notify: &NotifySettings, items: &[NotificationItem]) -> Result<()> {
+    let summary = format!("{} problems while suspended", items.len());
+    let lines = digest_lines(items);
+    send_summary(
+        notify,
+        &summary,
+        &lines,
+        items,
+        DIGEST_TAG,
+        Some(DIGEST_TAG),
+        None,
+    )
+}
+
+/// Sticky toast standing in for every notification held back by a storm.
+/// Without buttons nothing waits on it: refreshes and the closing digest
+/// replace it as they come.
+fn send_storm(
+    notify: &NotifySettings,
+    ids: &NotificationIds,
+    problems: usize,
+    hosts: usize,
+    resolved: usize,
+) -> Result<()> {
+    let summary = format!("Alert storm: {problems} problems across {hosts} hosts");
+    let mut body = "Individual notifications are paused until the flood subsides.".to_string();
+    if resolved > 0 {
+        let _ = write!(body, "\nResolved {resolved}/{problems}");
+    }
+    let params = ToastParams {
+        summary: &summary,
+        body: &body,
+        detail: None,
+        body_limit: BodyLimit::default(),
+        urgency: Urgency::Critical,
+        timeout: ToastTimeout::Never,
+        appname: &notify.appname,
+        icon: notify.icon.as_deref(),
+        hero_image: notify.hero_image.as_deref(),
+        accent: None,
+        graph: None,
+        attribution: notify.attribution.as_deref(),
+        open_url: None,
+        host_url: None,
+        open_label: &notify.open_label,
+        group: Some(STORM_TAG),
+        tag: Some(STORM_TAG),
+        ids: Some(ids),
+        desktop_entry: notify.desktop_entry.as_deref(),
+        prompt_command: &notify.prompt_command,
+        ack_prompt: false,
+        confirm_command: None,
+        cause_action: false,
+        comment_action: false,
+        host_ack_action: None,
+        mute_action: None,
+        resident: false,
+        progress: Some((resolved, problems)),
+        sound_file: notify.sound_file.as_deref(),
+        sound: None,
+    };
+
+    show(notify, &params, None)
+}
+
+/// Quiet toast replacing the previous heartbeat.
+fn send_heartbeat(
+    notify: &NotifySettings,
+    ids: &NotificationIds,
+    last_poll: Option<Duration>,
+    active: usize,
+) -> Result<()> {
+    let body = heartbeat_text(last_poll, active);
+    let params = ToastParams {
+        summary: "Alerting is running",
+        body: &body,
+        detail: None,
+        body_limit: BodyLimit::default(),
+        urgency: Urgency::Low,
+        timeout: ToastTimeout::Default,
+        appname: &notify.appname,
+        icon: notify.icon.as_deref(),
+        hero_image: None,
+        accent: None,
+        graph: None,
+        attribution: notify.attribution.as_deref(),
+        open_url: None,
+        host_url: None,
+        open_label: &notify.open_label,
+        group: Some(HEARTBEAT_TAG),
+        tag: Some(HEARTBEAT_TAG),
+        ids: Some(ids),
+        desktop_entry: notify.desktop_entry.as_deref(),
+        prompt_command: &notify.prompt_command,
+        ack_prompt: false,
+        confirm_command: None,
+        cause_action: false,
+        comment_action: false,
+        host_ack_action: None,
+        mute_action: None,
+        resident: false,
+        progress: None,
+        sound_file: notify.sound_file.as_deref(),
+        sound: Some(false),
+    };
+
+    show(notify, &params, None)
+}
+
+/// Quiet toast for a comment or acknowledgement someone else made; a newer
+/// one on the same problem replaces it.
+fn send_event_update(
+    notify: &NotifySettings,
+    ids: &NotificationIds,
+    news: &EventNews,
+) -> Result<()> {
+    let verb = if news.acknowledged {
+        "acknowledged"
+    } else {
+        "commented"
+    };
+    let summary = format!("{} {verb}: {}", news.author, news.problem);
+    let body = if news.message.is_empty() {
+        news.host.clone().unwrap_or_default()
+    } else {
+        news.message.clone()
+    };
+    let tag = format!("update-{}", news.event_id);
+    let params = ToastParams {
+        summary: &summary,
+        body: &body,
+        detail: None,
+        body_limit: BodyLimit::default(),
+        urgency: Urgency::Low,
+        timeout: ToastTimeout::Default,
+        appname: &notify.appname,
+        icon: notify.icon.as_deref(),
+        hero_image: None,
+        accent: None,
+        graph: None,
+        attribution: notify.attribution.as_deref(),
+        open_url: news.open_url.as_deref(),
+        host_url: None,
+        open_label: &notify.open_label,
+        group: Some(&tag),
+        tag: Some(&tag),
+        ids: Some(ids),
+        desktop_entry: notify.desktop_entry.as_deref(),
+        prompt_command: &notify.prompt_command,
+        ack_prompt: false,
+        confirm_command: None,
+        cause_action: false,
+        comment_action: false,
+        host_ack_action: None,
+        mute_action: None,
+        resident: false,
+        progress: None,
+        sound_file: notify.sound_file.as_deref(),
+        sound: Some(false),
+    };
+
+    show(notify, &params, None)
+}
+
+/// "2 new, 1 escalated, 3 resolved": quiet unless something got worse.
+fn send_poll_summary(
+    notify: &NotifySettings,
+    ids: &NotificationIds,
+    counts: ChangeCounts,
+    active: usize,
+) -> Result<()> {
+    let summary = format!("Zabbix: {counts}");
+    let body = format!(
+        "{active} active problem{}",
+        if active == 1 { "" } else { "s" }
+    );
+    let worse = counts.new > 0 || counts.escalated > 0;
+    let params = ToastParams {
+        summary: &summary,
+        body: &body,
+        detail: None,
+        body_limit: BodyLimit::default(),
+        urgency: if worse { Urgency::Normal } else { Urgency::Low },
+        timeout: ToastTimeout::Default,
+        appname: &notify.appname,
+        icon: notify.icon.as_deref(),
+        hero_image: None,
+        accent: None,
+        graph: None,
+        attribution: notify.attribution.as_deref(),
+        open_url: None,
+        host_url: None,
+        open_label: &notify.open_label,
+        group: Some(SUMMARY_TAG),
+        tag: Some(SUMMARY_TAG),
+        ids: Some(ids),
+        desktop_entry: notify.desktop_entry.as_deref(),
+        prompt_command: &notify.prompt_command,
+        ack_prompt: false,
+        confirm_command: None,
+        cause_action: false,
+        comment_action: false,
+        host_ack_action: None,
+        mute_action: None,
+        resident: false,
+        progress: None,
+        sound_file: notify.sound_file.as_deref(),
+        sound: (!worse).then_some(false),
+    };
+
+    show(notify, &params, None)
+}
+
+/// Sticky warning while polls fail, replaced by a short-lived notice once
+/// one succeeds again. It has no buttons, so nothing waits on it and the
+/// replacement goes out as soon as it is queued.
+fn send_watchdog(
+    notify: &NotifySettings,
+    ids: &NotificationIds,
+    stalled: Option<Duration>,
+) -> Result<()> {
+    let (summary, body, urgency, timeout) = stalled.map_or_else(
+        || {
+            (
+                "Alerting is polling again",
+                "Zabbix answered again; notifications resume.".to_string(),
+                Urgency::Normal,
+                ToastTimeout::Default,
+            )
+        },
+        |silent| {
+            let silent = Duration::from_secs(silent.as_secs());
+            (
+                "Alerting cannot reach Zabbix",
+                format!(
+                    "No successful poll for {}; new problems are not being notified.",
+                    humantime::format_duration(silent)
+                ),
+                Urgency::Critical,
+                ToastTimeout::Never,
+            )
+        },
+    );
+    let params = ToastParams {
+        summary,
+        body: &body,
+        detail: None,
+        body_limit: BodyLimit::default(),
+        urgency,
+        timeout,
+        appname: &notify.appname,
+        icon: notify.icon.as_deref(),
+        hero_image: None,
+        accent: None,
+        graph: None,
+        attribution: notify.attribution.as_deref(),
+        open_url: None,
+        host_url: None,
+        open_label: &notify.open_label,
+        group: Some(WATCHDOG_TAG),
+        tag: Some(WATCHDOG_TAG),
+        ids: Some(ids),
+        desktop_entry: notify.desktop_entry.as_deref(),
+        prompt_command: &notify.prompt_command,
+        ack_prompt: false,
+        confirm_command: None,
+        cause_action: false,
+        comment_action: false,
+        host_ack_action: None,
+        mute_action: None,
+        resident: false,
+        progress: None,
+        sound_file: notify.sound_file.as_deref(),
+        sound: None,
+    };
+
+    show(notify, &params, None)
+}
+
+/// Replace the storm toast with a digest of the problems it held back.
+fn send_storm_over(
+    notify: &NotifySettings,
+    ids: &NotificationIds,
+    items: &[NotificationItem],
+    unlisted: usize,
+) -> Result<()> {
+    let summary = format!(
+        "Alert storm over: {} problems across {} hosts",
+        items.len() + unlisted,
+        distinct_hosts(items)
+    );
+    let mut lines = digest_lines(items);
+    if unlisted > 0 {
+        lines.push(format!("…and {unlisted} more not kept"));
+    }
+    send_summary(
+        notify,
+        &summary,
+        &lines,
+        items,
+        STORM_TAG,
+        Some(STORM_TAG),
+        Some(ids),
+    )
+}
+
+/// Number of different hosts among `items`.
+pub fn distinct_hosts(items: &[NotificationItem]) -> usize {
+    items
+        .iter()
+        .map(|item| item.host.as_ref().map(|h| h.display_name.as_str()))
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+/// One line per problem. Symptoms whose cause is in the same digest are
+/// listed right under it, indented.
+fn digest_lines(items: &[NotificationItem]) -> Vec<String> {
+    let present: HashSet<&str> = items
+        .iter()
+        .map(|item| item.problem.event_id.as_str())
+        .collect();
+    let nested = |item: &&NotificationItem| {
+        item.problem
+            .cause_event_id
+            .as_deref()
+            .is_some_and(|cause| present.contains(cause))
+    };
+
+    let mut lines = Vec::with_capacity(items.len());
+    for item in items.iter().filter(|item| !nested(item)) {
+        lines.push(digest_line(item));
+        let cause = Some(item.problem.event_id.as_str());
+        for symptom in items
+            .iter()
+            .filter(|other| other.problem.cause_event_id.as_deref() == cause)
+        {
+            lines.push(format!("  ↳ {}", digest_line(symptom)));
+        }
+    }
+    lines
+}
+
+fn digest_line(item: &NotificationItem) -> String {
+    let host_label = item
+        .host
+        .as_ref()
+        .map_or("<unknown>", |h| h.display_name.as_str());
+    format!(
+        "{:?} – {host_label}: {}",
+        item.problem.severity, item.problem.name
+    )
+}
+
+/// List the new problems of one host in a single toast.
+fn send_host_batch(notify: &NotifySettings, items: &[NotificationItem]) -> Result<()> {
+    let host_label = items
+        .first()
+        .and_then(|item| item.host.as_ref())
+        .map_or("<unknown>", |h| h.display_name.as_str());
+    let summary = format!("{} problems – {host_label}", items.len());
+    let lines: Vec<String> = items
+        .iter()
+        .map(|item| format!("{:?}: {}", item.problem.severity, item.problem.name))
+        .collect();
+    let group = match notify.group_by {
+        ToastGrouping::Host => host_label.to_string(),
+        ToastGrouping::Severity => items
+            .iter()
+            .map(|item| item.problem.severity)
+            .max()
+            .map_or_else(String::new, |severity| severity.to_string()),
+    };
+    send_summary(notify, &summary, &lines, items, &group, None, None)
+}
+
+/// Toast summing up several problems, styled after the most severe one.
+fn send_summary(
+    notify: &NotifySettings,
+    summary: &str,
+    lines: &[String],
+    items: &[NotificationItem],
+    group: &str,
+    tag: Option<&str>,
+    ids: Option<&NotificationIds>,
+) -> Result<()> {
+    let Some(severity) = items.iter().map(|item| item.problem.severity).max() else {
+        return Ok(());
+    };
+    let (urgency, escalated) = urgency_for_severity(notify, severity, Local::now().naive_local());
+    let timeout_ms = notify.timeout.and_then(|dur| u128_to_u32(dur.as_millis()));
+    let timeout = compute_timeout(
+        notify.sticky || escalated,
+        timeout_ms,
+        notify.default_timeout,
+    );
+
+    let body = lines.join("\n");
+
+    let summary = severity_marked(notify, severity, summary.to_string());
+    let params = ToastParams {
+        summary: &summary,
+        body: &body,
+        detail: None,
+        body_limit: BodyLimit::default(),
+        urgency,
+        timeout,
+        appname: &notify.appname,
+        icon: notify.icon.as_deref(),
+        hero_image: notify.hero_image.as_deref(),
+        accent: None,
+        graph: None,
+        attribution: notify.attribution.as_deref(),
+        open_url: None,
+        host_url: None,
+        open_label: &notify.open_label,
+        group: Some(group),
+        tag,
+        ids,
+        desktop_entry: notify.desktop_entry.as_deref(),
+        prompt_command: &notify.prompt_command,
+        ack_prompt: false,
+        confirm_command: None,
+        cause_action: false,
+        comment_action: false,
+        host_ack_action: None,
+        mute_action: None,
+        resident: false,
+        progress: None,
+        sound_file: notify.sound_file.as_deref(),
+        sound: notify.style_for(severity).sound,
+    };
+
+    show(notify, &params, None)
+}
+
+/// Hand a toast to the platform backend, framing its summary with
+/// `notify.summary_prefix` and `notify.summary_suffix`.
+fn show(
+    notify: &NotifySettings,
+    params: &ToastParams<'_>,
+    ack_action: Option<&AckAction>,
+) -> Result<()> {
+    let summary = format!(
+        "{}{}{}",
+        notify.summary_prefix, params.summary, notify.summary_suffix
+    );
+    let params = ToastParams {
+        summary: &summary,
+        body_limit: BodyLimit::of(notify),
+        ..*params
+    };
+    backends::send_toast(&params, ack_action).map_err(AlertError::from)
+}
+
+/// Map a severity to a toast urgency, honouring the configured severity
+/// style and then the time-of-day schedule.
+///
+/// The boolean is `true` when the style or the schedule asks for the toast to
+/// stay on screen until dismissed.
+fn urgency_for_severity(
+    notify: &NotifySettings,
+    severity: Severity,
+    now: NaiveDateTime,
+) -> (Urgency, bool) {
+    let style = notify.style_for(severity);
+    let (urgency, escalated) = notify
+        .schedule
+        .as_ref()
+        .and_then(|schedule| schedule.urgency_at(severity, now))
+        .unwrap_or_else(|| {
+            let urgency = style
+                .urgency
+                .unwrap_or_else(|| Urgency::for_severity(severity));
+            (urgency, false)
+        });
+    (urgency, escalated || style.sticky)
+}
+
 const fn compute_timeout(
     sticky: bool,
     timeout_ms: Option<u32>,
@@ -164,7 +1310,7 @@ const fn compute_timeout(
     }
 }
 
-#[cfg(target_os = "windows")]
+#[cfg(all(target_os = "windows", feature = "windows-toasts"))]
 pub fn send_test_toast(
     summary: &str,
     body: &str,
@@ -175,28 +1321,335 @@ pub fn send_test_toast(
     let params = ToastParams {
         summary,
         body,
-        urgency: ToastUrgency::Normal,
+        detail: None,
+        body_limit: BodyLimit::default(),
+        urgency: Urgency::Normal,
         timeout: ToastTimeout::Milliseconds(5_000),
         appname,
         icon,
+        hero_image: None,
+        accent: None,
+        graph: None,
+        attribution: None,
         open_url: None,
+        host_url: None,
         open_label,
+        group: None,
+        tag: None,
+        ids: None,
+        desktop_entry: None,
+        prompt_command: &[],
+        ack_prompt: false,
+        confirm_command: None,
+        cause_action: false,
+        comment_action: false,
+        host_ack_action: None,
+        mute_action: None,
+        resident: false,
+        progress: None,
+        sound_file: None,
+        sound: None,
     };
 
     backends::send_toast(&params, None).map_err(AlertError::from)
 }
 
+/// Body of a single-problem toast, as detailed as `detail` asks.
+fn toast_body(item: &NotificationItem, detail: DetailLevel, display: &DisplaySettings) -> String {
+    let mut body = format!(
+        "Event #{} {}",
+        item.problem.event_id,
+        if item.problem.acknowledged {
+            "[ACK]"
+        } else {
+            "[UNACK]"
+        }
+    );
+    if detail == DetailLevel::Minimal {
+        return body;
+    }
+    if display.relative_time {
+        let _ = write!(
+            body,
+            "\nstarted {}",
+            display.when(item.problem.clock, unix_now(), "%Y-%m-%d %H:%M")
+        );
+    }
+    match item.others_on_host {
+        0 => {}
+        1 => body.push_str("\n(1 other active problem on this host)"),
+        count => {
+            let _ = write!(body, "\n({count} other active problems on this host)");
+        }
+    }
+    if !item.services.is_empty() {
+        let _ = write!(body, "\naffects: {}", item.services.join(", "));
+    }
+    if detail == DetailLevel::Debug {
+        if let Some(trigger_id) = &item.problem.trigger_id {
+            let _ = write!(body, "\ntrigger {trigger_id}");
+            if let Some(expression) = &item.trigger_expression {
+                let _ = write!(body, ": {expression}");
+            }
+        }
+        if !item.problem.tags.is_empty() {
+            let tags: Vec<String> = item
+                .problem
+                .tags
+                .iter()
+                .map(|tag| {
+                    if tag.value.is_empty() {
+                        tag.tag.clone()
+                    } else {
+                        format!("{}: {}", tag.tag, tag.value)
+                    }
+                })
+                .collect();
+            let _ = write!(body, "\ntags: {}", tags.join(", "));
+        }
+    }
+    body
+}
+
+/// `notify.max_body_lines` and `notify.max_body_chars`, applied by the
+/// backend once it has assembled everything the toast body shows.
+#[derive(Clone, Copy, Debug, Default)]
+struct BodyLimit {
+    max_lines: Option<usize>,
+    max_chars: Option<usize>,
+}
+
+impl BodyLimit {
+    const fn of(notify: &NotifySettings) -> Self {
+        Self {
+            max_lines: notify.max_body_lines,
+            max_chars: notify.max_body_chars,
+        }
+    }
+
+    /// Cap plain `text`.
+    fn apply(self, text: &str) -> String {
+        self.fit(vec![(text.to_string(), ())], ())
+            .into_iter()
+            .map(|(line, ())| line)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Cap the body made of `parts` joined by newlines, each tagged with
+    /// how the backend renders it. Extra lines become one "…and N more"
+    /// line tagged `more`, and the text past `max_chars` an ellipsis.
+    /// Returns one entry per line.
+    fn fit<T: Copy>(self, parts: Vec<(String, T)>, more: T) -> Vec<(String, T)> {
+        let mut lines: Vec<(String, T)> = parts
+            .into_iter()
+            .flat_map(|(text, tag)| {
+                text.split('\n')
+                    .map(|line| (line.to_string(), tag))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        if let Some(max) = self.max_lines
+            && lines.len() > max
+        {
+            let hidden = lines.len() - max;
+            lines.truncate(max);
+            lines.push((format!("…and {hidden} more"), more));
+        }
+
+        let Some(max) = self.max_chars else {
+            return lines;
+        };
+        let total = lines
+            .iter()
+            .map(|(line, _)| line.chars().count())
+            .sum::<usize>()
+            + lines.len().saturating_sub(1);
+        if total <= max {
+            return lines;
+        }
+        // Keep `max - 1` characters, newlines included, then the ellipsis.
+        let mut budget = max.saturating_sub(1);
+        for idx in 0..lines.len() {
+            if idx > 0 {
+                if budget == 0 {
+                    lines.truncate(idx);
+                    if let Some((line, _)) = lines.last_mut() {
+                        line.push('…');
+                    }
+                    return lines;
+                }
+                budget -= 1;
+            }
+            let line = &mut lines[idx].0;
+            let len = line.chars().count();
+            if len <= budget {
+                budget -= len;
+                continue;
+            }
+            if let Some((cut, _)) = line.char_indices().nth(budget) {
+                line.truncate(cut);
+            }
+            line.push('…');
+            lines.truncate(idx + 1);
+            break;
+        }
+        lines
+    }
+}
+
 fn u128_to_u32(value: u128) -> Option<u32> {
     u32::try_from(value).ok()
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ToastTimeout, compute_timeout};
+    use super::{
+        BodyLimit, Notice, NotificationItem, ToastTimeout, compute_timeout, digest_lines,
+        forget_pending, toast_body, toast_summary,
+    };
+    use alerting::config::DisplaySettings;
+    use alerting::types::{DetailLevel, Severity};
+    use alerting::zbx_client::{EventTag, Problem};
+    use std::collections::VecDeque;
+    use tracing::Span;
+
+    fn item(event_id: &str, cause: Option<&str>) -> NotificationItem {
+        NotificationItem {
+            problem: Problem {
+                event_id: event_id.to_string(),
+                clock: 0,
+                last_change: 0,
+                name: format!("problem {event_id}"),
+                severity: Severity::High,
+                acknowledged: false,
+                cause_event_id: cause.map(str::to_string),
+                trigger_id: None,
+                tags: Vec::new(),
+            },
+            host: None,
+            open_url: None,
+            host_url: None,
+            others_on_host: 0,
+            unacked_on_host: 1,
+            services: Vec::new(),
+            graph: None,
+            trigger_expression: None,
+            correlation_id: String::new(),
+            span: Span::none(),
+        }
+    }
+
+    #[test]
+    fn resolved_problems_leave_the_pending_queue() {
+        let mut pending = VecDeque::from([
+            Notice::Problem(Box::new(item("1", None))),
+            Notice::Digest(vec![item("2", None), item("3", None)]),
+            Notice::HostBatch(vec![item("4", None)]),
+        ]);
+        forget_pending(
+            &mut pending,
+            &["1".to_string(), "2".to_string(), "4".to_string()],
+        );
+        assert_eq!(pending.len(), 1);
+        match &pending[0] {
+            Notice::Digest(items) => assert_eq!(items[0].problem.event_id, "3"),
+            _ => panic!("digest expected"),
+        }
+    }
 
     #[test]
     fn timeout_prefers_sticky() {
         let timeout = compute_timeout(true, Some(1000), true);
         assert!(matches!(timeout, ToastTimeout::Never));
     }
+
+    #[test]
+    fn body_follows_detail_level() {
+        let mut item = item("42", None);
+        item.others_on_host = 1;
+        item.problem.trigger_id = Some("13491".to_string());
+        item.problem.tags = vec![EventTag {
+            tag: "scope".to_string(),
+            value: "capacity".to_string(),
+        }];
+        item.trigger_expression = Some("last(/db01/vfs.fs.pused)>90".to_string());
+
+        assert_eq!(
+            toast_body(&item, DetailLevel::Minimal, &DisplaySettings::default()),
+            "Event #42 [UNACK]"
+        );
+        assert_eq!(
+            toast_body(&item, DetailLevel::Normal, &DisplaySettings::default()),
+            "Event #42 [UNACK]\n(1 other active problem on this host)"
+        );
+        assert_eq!(
+            toast_body(&item, DetailLevel::Debug, &DisplaySettings::default()),
+            "Event #42 [UNACK]\n(1 other active problem on this host)\n\
+             trigger 13491: last(/db01/vfs.fs.pused)>90\ntags: scope: capacity"
+        );
+
+        item.problem.clock = super::unix_now() - 300;
+        let relative = DisplaySettings {
+            relative_time: true,
+            ..DisplaySettings::default()
+        };
+        assert!(
+            toast_body(&item, DetailLevel::Normal, &relative)
+                .starts_with("Event #42 [UNACK]\nstarted 5 min ago (")
+        );
+    }
+
+    #[test]
+    fn acked_summary_is_prefixed() {
+        assert_eq!(toast_summary(Severity::High, "db1", false), "High – db1");
+        assert_eq!(
+            toast_summary(Severity::High, "db1", true),
+            "[ACK by user] High – db1"
+        );
+    }
+
+    #[test]
+    fn digest_nests_symptoms_under_their_cause() {
+        let items = [item("2", Some("1")), item("1", None), item("3", Some("9"))];
+        assert_eq!(
+            digest_lines(&items),
+            [
+                "High – <unknown>: problem 1",
+                "  ↳ High – <unknown>: problem 2",
+                "High – <unknown>: problem 3",
+            ]
+        );
+    }
+
+    #[test]
+    fn body_limit_caps_lines_and_chars() {
+        let limit = |max_lines, max_chars| BodyLimit {
+            max_lines,
+            max_chars,
+        };
+        let digest = "a\nb\nc\nd";
+        assert_eq!(limit(Some(2), None).apply(digest), "a\nb\n…and 2 more");
+        assert_eq!(limit(None, Some(4)).apply("abcdef"), "abc…");
+        assert_eq!(limit(Some(1), Some(4)).apply("abcd"), "abcd");
+        assert_eq!(limit(None, Some(3)).apply("ab\ncd"), "ab…");
+        assert_eq!(limit(None, Some(4)).apply("ab\ncd"), "ab\n…");
+    }
+
+    #[test]
+    fn body_limit_spans_every_part() {
+        let limit = BodyLimit {
+            max_lines: Some(2),
+            max_chars: None,
+        };
+        let parts = vec![("body\nmore".to_string(), 0), ("detail".to_string(), 1)];
+        assert_eq!(
+            limit.fit(parts, 2),
+            [
+                ("body".to_string(), 0),
+                ("more".to_string(), 0),
+                ("…and 1 more".to_string(), 2)
+            ]
+        );
+    }
 }
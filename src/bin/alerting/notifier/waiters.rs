@@ -0,0 +1,114 @@
+//! Threads waiting for a click on a shown toast. Backends whose toasts
+//! block until an action or a close hand the wait to one of these, so the
+//! notifier keeps delivering (storm refreshes, closes, replacements) while a
+//! sticky or resident toast stays on screen.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Toasts waiting at once; past this many, backends show further toasts
+/// without actions.
+const MAX_WAITING: usize = 8;
+
+static WAITERS: Waiters = Waiters::new(MAX_WAITING);
+
+/// Bounded count of toasts waiting for an action.
+pub(super) struct Waiters {
+    waiting: AtomicUsize,
+    max: usize,
+}
+
+impl Waiters {
+    const fn new(max: usize) -> Self {
+        Self {
+            waiting: AtomicUsize::new(0),
+            max,
+        }
+    }
+
+    fn reserve(&'static self) -> Option<Slot> {
+        self.waiting
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |waiting| {
+                (waiting < self.max).then_some(waiting + 1)
+            })
+            .ok()
+            .map(|_| Slot(self))
+    }
+}
+
+/// One of the `MAX_WAITING` waiting threads, given back on drop.
+pub(super) struct Slot(&'static Waiters);
+
+impl Slot {
+    /// A free slot, or `None` when `MAX_WAITING` toasts already wait.
+    pub(super) fn reserve() -> Option<Self> {
+        WAITERS.reserve()
+    }
+
+    /// Run `wait` on a thread of its own, holding the slot until it
+    /// returns. Tokio tasks it spawns (acks from a button) run on the
+    /// runtime of the caller, when there is one.
+    pub(super) fn spawn(
+        self,
+        name: &str,
+        wait: impl FnOnce() + Send + 'static,
+    ) -> std::io::Result<()> {
+        let runtime = tokio::runtime::Handle::try_current().ok();
+        std::thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || {
+                let _slot = self;
+                let _runtime = runtime.as_ref().map(tokio::runtime::Handle::enter);
+                wait();
+            })
+            .map(drop)
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.waiting.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Waiters;
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn slots_are_bounded_and_given_back() {
+        static POOL: Waiters = Waiters::new(2);
+        let first = POOL.reserve();
+        let second = POOL.reserve();
+        assert!(first.is_some() && second.is_some());
+        assert!(POOL.reserve().is_none());
+        drop(first);
+        assert!(POOL.reserve().is_some());
+    }
+
+    #[test]
+    fn close_reaches_a_resident_toast_still_waiting() -> Result<(), Box<dyn std::error::Error>> {
+        static POOL: Waiters = Waiters::new(1);
+        // A resident toast waits until it is closed.
+        let (close, closed) = mpsc::channel::<()>();
+        let (done_tx, done) = mpsc::channel();
+        let slot = POOL.reserve().ok_or("no free slot")?;
+        slot.spawn("test-toast", move || {
+            let _ = closed.recv();
+            let _ = done_tx.send(());
+        })?;
+
+        // Back on the notifier while the toast is open: the close goes out.
+        assert!(POOL.reserve().is_none());
+        assert!(done.try_recv().is_err());
+        close.send(())?;
+        done.recv_timeout(Duration::from_secs(5))?;
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while POOL.reserve().is_none() {
+            assert!(Instant::now() < deadline, "slot not given back");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+//! PID file written for the lifetime of the daemon.
+
+use std::fs::{File, TryLockError};
+use std::io::{Read as _, Seek as _, Write as _};
+use std::path::{Path, PathBuf};
+
+use alerting::Result;
+use alerting::error::Error as AlertError;
+use tracing::{debug, warn};
+
+/// Holds the PID file locked and removes it when dropped.
+pub struct PidFile {
+    path: PathBuf,
+    /// Kept open for the lock, released when the process exits however it
+    /// exits.
+    _file: File,
+}
+
+impl PidFile {
+    /// Lock `path` and write the current process id to it. A file left
+    /// behind by a daemon that died is reused; one still locked by a
+    /// running daemon is an error.
+    pub fn create(path: &Path) -> Result<Self> {
+        let io_error = |source| AlertError::PidFile {
+            path: path.to_path_buf(),
+            source,
+        };
+        let mut file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(io_error)?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let mut pid = String::new();
+                let _ = file.read_to_string(&mut pid);
+                return Err(AlertError::AlreadyRunning {
+                    path: path.to_path_buf(),
+                    pid: pid.trim().to_string(),
+                });
+            }
+            Err(TryLockError::Error(source)) => return Err(io_error(source)),
+        }
+        file.set_len(0)
+            .and_then(|()| file.rewind())
+            .and_then(|()| writeln!(file, "{}", std::process::id()))
+            .map_err(io_error)?;
+        debug!(path = %path.display(), "wrote PID file");
+        Ok(Self {
+            path: path.to_path_buf(),
+            _file: file,
+        })
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        if let Err(err) = std::fs::remove_file(&self.path) {
+            warn!(path = %self.path.display(), error = %err, "failed to remove PID file");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PidFile;
+
+    #[test]
+    fn pid_file_is_removed_on_drop() -> Result<(), Box<dyn std::error::Error>> {
+        let path = std::env::temp_dir().join(format!("alerting-{}.pid", uuid::Uuid::new_v4()));
+
+        let pid_file = PidFile::create(&path)?;
+        let contents = std::fs::read_to_string(&path)?;
+        assert_eq!(contents.trim(), std::process::id().to_string());
+
+        drop(pid_file);
+        assert!(!path.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn locked_pid_file_is_refused() -> Result<(), Box<dyn std::error::Error>> {
+        let path = std::env::temp_dir().join(format!("alerting-{}.pid", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "1\n")?;
+
+        let pid_file = PidFile::create(&path)?;
+        let Err(err) = PidFile::create(&path) else {
+            panic!("second PID file lock succeeded");
+        };
+        assert_eq!(err.code(), "ALREADY_RUNNING");
+        assert!(err.to_string().contains(&std::process::id().to_string()));
+
+        drop(pid_file);
+        let pid_file = PidFile::create(&path)?;
+        drop(pid_file);
+        Ok(())
+    }
+}
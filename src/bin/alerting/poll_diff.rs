@@ -0,0 +1,215 @@
+//! What changed between two consecutive polls.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+
+use alerting::zbx_client::{HostMeta, Problem};
+use tracing::{debug, info};
+
+pub type Row = (Problem, Option<HostMeta>);
+
+/// Severity or acknowledgement change of a problem still active.
+#[derive(Debug)]
+pub struct Change {
+    pub before: Problem,
+    pub after: Row,
+}
+
+/// Delta between the previous poll and this one.
+#[derive(Debug, Default)]
+pub struct PollDiff {
+    pub appeared: Vec<Row>,
+    pub resolved: Vec<Row>,
+    pub changed: Vec<Change>,
+}
+
+impl PollDiff {
+    pub const fn is_empty(&self) -> bool {
+        self.appeared.is_empty() && self.resolved.is_empty() && self.changed.is_empty()
+    }
+
+    /// What `notify.poll_summary` reports about this poll.
+    pub fn counts(&self) -> ChangeCounts {
+        ChangeCounts {
+            new: self.appeared.len(),
+            escalated: self
+                .changed
+                .iter()
+                .filter(|change| change.after.0.severity > change.before.severity)
+                .count(),
+            acknowledged: self
+                .changed
+                .iter()
+                .filter(|change| !change.before.acknowledged && change.after.0.acknowledged)
+                .count(),
+            resolved: self.resolved.len(),
+        }
+    }
+
+    fn note_change(&mut self, before: Problem, after: &Row) {
+        if before.severity != after.0.severity || before.acknowledged != after.0.acknowledged {
+            self.changed.push(Change {
+                before,
+                after: after.clone(),
+            });
+        }
+    }
+
+    pub fn log(&self, active: usize) {
+        for (problem, _) in &self.appeared {
+            debug!(event_id = %problem.event_id, severity = ?problem.severity, "problem appeared");
+        }
+        for (problem, _) in &self.resolved {
+            debug!(event_id = %problem.event_id, "problem resolved");
+        }
+        for change in &self.changed {
+            let after = &change.after.0;
+            debug!(
+                event_id = %after.event_id,
+                severity_before = ?change.before.severity,
+                severity = ?after.severity,
+                acked_before = change.before.acknowledged,
+                acked = after.acknowledged,
+                "problem changed"
+            );
+        }
+        if !self.is_empty() {
+            info!(
+                appeared = self.appeared.len(),
+                resolved = self.resolved.len(),
+                changed = self.changed.len(),
+                active,
+                "{} appeared, {} resolved, {} changed severity/ack",
+                self.appeared.len(),
+                self.resolved.len(),
+                self.changed.len()
+            );
+        }
+    }
+}
+
+/// Changes of one poll, e.g. "2 new, 1 escalated, 3 resolved"; counts of
+/// zero are left out.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ChangeCounts {
+    pub new: usize,
+    /// Still active with a higher severity.
+    pub escalated: usize,
+    pub acknowledged: usize,
+    pub resolved: usize,
+}
+
+impl ChangeCounts {
+    pub const fn is_empty(self) -> bool {
+        self.new == 0 && self.escalated == 0 && self.acknowledged == 0 && self.resolved == 0
+    }
+}
+
+impl Display for ChangeCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = [
+            (self.new, "new"),
+            (self.escalated, "escalated"),
+            (self.acknowledged, "acknowledged"),
+            (self.resolved, "resolved"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, label)| format!("{count} {label}"))
+        .collect();
+        f.write_str(&parts.join(", "))
+    }
+}
+
+/// Problems active at the last poll.
+#[derive(Default)]
+pub struct ActiveProblems {
+    rows: HashMap<String, Row>,
+    /// The first poll only sets the baseline: everything would "appear".
+    primed: bool,
+}
+
+impl ActiveProblems {
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn problems(&self) -> impl Iterator<Item = &Problem> {
+        self.rows.values().map(|(problem, _)| problem)
+    }
+
+    /// Compare `rows` with the last poll. Problems missing from `rows` are
+    /// returned apart rather than counted as resolved: the list may be
+    /// filtered or truncated, so the caller checks them with Zabbix and
+    /// passes them to [`Self::still_active`] or [`PollDiff::resolved`].
+    pub fn update(&mut self, rows: &[Row]) -> (PollDiff, Vec<Row>) {
+        let mut previous = std::mem::take(&mut self.rows);
+        let mut diff = PollDiff::default();
+        for row in rows {
+            let event_id = &row.0.event_id;
+            match previous.remove(event_id) {
+                Some((before, _)) => diff.note_change(before, row),
+                None if self.primed => diff.appeared.push(row.clone()),
+                None => {}
+            }
+            self.rows.insert(event_id.clone(), row.clone());
+        }
+        self.primed = true;
+        (diff, previous.into_values().collect())
+    }
+
+    /// Keep tracking a problem missing from the poll that Zabbix still
+    /// reports as `current`, and note its change if any.
+    pub fn still_active(&mut self, (before, host): Row, current: Problem, diff: &mut PollDiff) {
+        let row = (current, host);
+        diff.note_change(before, &row);
+        self.rows.insert(row.0.event_id.clone(), row);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ActiveProblems, Row};
+    use alerting::types::Severity;
+    use alerting::zbx_client::Problem;
+
+    fn row(event_id: &str, severity: Severity, acknowledged: bool) -> Row {
+        let problem = Problem {
+            event_id: event_id.to_string(),
+            clock: 1_700_000_000,
+            last_change: 1_700_000_000,
+            name: format!("problem {event_id}"),
+            severity,
+            acknowledged,
+            cause_event_id: None,
+            trigger_id: None,
+            tags: Vec::new(),
+        };
+        (problem, None)
+    }
+
+    #[test]
+    fn diff_sorts_appeared_vanished_and_changed() {
+        let mut active = ActiveProblems::default();
+        let (baseline, vanished) = active.update(&[
+            row("1", Severity::High, false),
+            row("2", Severity::Warning, false),
+        ]);
+        assert!(baseline.is_empty() && vanished.is_empty());
+
+        let (diff, vanished) = active.update(&[
+            row("2", Severity::Average, false),
+            row("3", Severity::Info, false),
+        ]);
+        let ids = |rows: &[Row]| -> Vec<String> {
+            rows.iter().map(|(p, _)| p.event_id.clone()).collect()
+        };
+        assert_eq!(ids(&diff.appeared), ["3"]);
+        assert_eq!(ids(&vanished), ["1"]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].before.severity, Severity::Warning);
+        assert_eq!(diff.changed[0].after.0.severity, Severity::Average);
+        assert_eq!(active.len(), 2);
+        assert_eq!(diff.counts().to_string(), "1 new, 1 escalated");
+    }
+}
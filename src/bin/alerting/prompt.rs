@@ -0,0 +1,54 @@
+//! Built-in ack-message dialog, used when no external prompt tool is
+//! installed. Runs in its own process (`alerting prompt`) because the
+//! windowing event loop cannot be recreated inside the long-running daemon.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use alerting::Result;
+use alerting::error::{Error as AlertError, NotifyError};
+use eframe::egui;
+
+/// Show the dialog and print the entered message on stdout, like `zenity --entry`.
+pub fn run(title: &str, text: &str) -> Result<()> {
+    let entered = Rc::new(RefCell::new(None::<String>));
+    let result = Rc::clone(&entered);
+    let label = text.to_string();
+    let mut input = String::new();
+
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([420.0, 120.0])
+            .with_resizable(false)
+            .with_always_on_top(),
+        ..Default::default()
+    };
+
+    eframe::run_simple_native(title, options, move |ctx, _frame| {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.label(&label);
+            let field = ui.text_edit_singleline(&mut input);
+            let submitted = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            field.request_focus();
+            ui.horizontal(|ui| {
+                if ui.button("OK").clicked() || submitted {
+                    *result.borrow_mut() = Some(input.clone());
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+                if ui.button("Annuler").clicked() || ui.input(|i| i.key_pressed(egui::Key::Escape))
+                {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+            });
+        });
+    })
+    .map_err(|err| {
+        tracing::warn!(error = %err, "built-in prompt failed");
+        AlertError::from(NotifyError::Backend)
+    })?;
+
+    if let Some(message) = entered.take() {
+        println!("{message}");
+    }
+    Ok(())
+}
@@ -0,0 +1,99 @@
+//! Hosts monitored by the proxies of `zabbix.proxy_ids`, from `host.get`,
+//! so `problem.get` only returns their problems.
+
+use std::time::{Duration, Instant};
+
+use alerting::Result;
+use alerting::zbx_client::ZbxClient;
+use tracing::{debug, warn};
+
+/// Hosts moved between proxies show up after at most this long.
+const PROXY_HOSTS_TTL: Duration = Duration::from_mins(5);
+
+/// Host ids of the configured proxies, refetched once per TTL.
+pub struct ProxyHosts {
+    proxy_ids: Vec<String>,
+    ttl: Duration,
+    fetched_at: Option<Instant>,
+    host_ids: Vec<String>,
+}
+
+impl ProxyHosts {
+    pub const fn new(proxy_ids: Vec<String>) -> Self {
+        Self {
+            proxy_ids,
+            ttl: PROXY_HOSTS_TTL,
+            fetched_at: None,
+            host_ids: Vec::new(),
+        }
+    }
+
+    /// The host ids, refetched once they are older than the TTL. A failed
+    /// refetch keeps the previous list until the next poll retries it; with
+    /// no list yet, the error fails the poll.
+    pub async fn host_ids(&mut self, client: &ZbxClient) -> Result<&[String]> {
+        let now = Instant::now();
+        if self.is_stale(now) {
+            match client.proxy_host_ids(&self.proxy_ids).await {
+                Ok(host_ids) => {
+                    debug!(hosts = host_ids.len(), "refreshed hosts of the proxies");
+                    self.host_ids = host_ids;
+                    self.fetched_at = Some(now);
+                }
+                Err(err) if self.fetched_at.is_some() => warn!(
+                    error_code = err.code(),
+                    error = %err,
+                    "failed to refresh hosts of the proxies; keeping the previous list"
+                ),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(&self.host_ids)
+    }
+
+    fn is_stale(&self, now: Instant) -> bool {
+        self.fetched_at
+            .is_none_or(|at| now.duration_since(at) >= self.ttl)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ProxyHosts;
+    use alerting::zbx_client::ZbxClient;
+    use secrecy::SecretString;
+    use serde_json::json;
+    use std::time::{Duration, Instant};
+    use url::Url;
+    use wiremock::matchers::{body_string_contains, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn hosts_are_fetched_once_per_ttl() -> Result<(), Box<dyn std::error::Error>> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("\"proxyids\":[\"10500\"]"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "result": [{ "hostid": "10084" }],
+                "id": 1
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+        let client = ZbxClient::new(
+            Url::parse(&server.uri())?,
+            SecretString::from("token"),
+            Duration::from_secs(2),
+            Duration::from_secs(1),
+            true,
+        )?;
+
+        let mut hosts = ProxyHosts::new(vec!["10500".to_string()]);
+        assert_eq!(hosts.host_ids(&client).await?, ["10084"]);
+        assert_eq!(hosts.host_ids(&client).await?, ["10084"]);
+        hosts.fetched_at = Instant::now().checked_sub(hosts.ttl);
+        assert_eq!(hosts.host_ids(&client).await?, ["10084"]);
+        Ok(())
+    }
+}
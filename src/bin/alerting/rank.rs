@@ -0,0 +1,64 @@
+//! `alerting rank`: curate cause/symptom correlation (Zabbix 6.4+).
+
+use std::str::FromStr;
+
+use alerting::Result;
+use alerting::error::{ConfigError, Error as AlertError};
+use alerting::zbx_client::{EventRank, ZbxClient};
+
+/// Rank asked on the command line.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RankKind {
+    Cause,
+    Symptom,
+}
+
+impl FromStr for RankKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "cause" => Ok(Self::Cause),
+            "symptom" => Ok(Self::Symptom),
+            other => Err(format!("unknown rank {other:?} (cause or symptom)")),
+        }
+    }
+}
+
+pub async fn run(
+    client: &ZbxClient,
+    event_id: &str,
+    kind: RankKind,
+    cause_event_id: Option<&str>,
+) -> Result<()> {
+    let rank = match (kind, cause_event_id) {
+        (RankKind::Cause, None) => EventRank::Cause,
+        (RankKind::Symptom, Some(cause)) if cause != event_id => EventRank::Symptom {
+            cause_event_id: cause.to_string(),
+        },
+        (RankKind::Cause, Some(_)) => {
+            return Err(usage_error("a cause does not take a cause event id"));
+        }
+        (RankKind::Symptom, Some(_)) => {
+            return Err(usage_error("an event cannot be its own cause"));
+        }
+        (RankKind::Symptom, None) => {
+            return Err(usage_error("a symptom needs the event id of its cause"));
+        }
+    };
+    client.rank_event(event_id, &rank).await?;
+    match rank {
+        EventRank::Cause => println!("event {event_id} is now a cause"),
+        EventRank::Symptom { cause_event_id } => {
+            println!("event {event_id} is now a symptom of event {cause_event_id}");
+        }
+    }
+    Ok(())
+}
+
+fn usage_error(message: &str) -> AlertError {
+    AlertError::from(ConfigError::InvalidField {
+        field: "CAUSE_EVENTID",
+        message: message.to_string(),
+    })
+}
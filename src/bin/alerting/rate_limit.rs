@@ -1,5 +1,6 @@
 use std::collections::VecDeque;
-use std::time::Instant;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
 
 pub struct LeakyBucket {
     window: std::time::Duration,
@@ -32,9 +33,109 @@ impl LeakyBucket {
     }
 }
 
+/// Lets through the first occurrence of a repetitive log line per window,
+/// so floods stay visible without drowning the rest of the log.
+pub struct LogSampler {
+    window: Duration,
+    /// Start of the current window and occurrences left out since.
+    state: Mutex<Option<(Instant, u64)>>,
+}
+
+impl LogSampler {
+    pub const fn new(window: Duration) -> Self {
+        Self {
+            window,
+            state: Mutex::new(None),
+        }
+    }
+
+    /// Count one occurrence; returns how many were left out since the last
+    /// logged one when this one should be logged.
+    pub fn sample(&self) -> Option<u64> {
+        self.sample_at(Instant::now())
+    }
+
+    fn sample_at(&self, now: Instant) -> Option<u64> {
+        let window = self.window;
+        advance(
+            &mut self.state.lock().unwrap_or_else(PoisonError::into_inner),
+            now,
+            window,
+        )
+    }
+}
+
+/// Count an occurrence in the window of `state`, opening a new one at
+/// `now` once it is over.
+fn advance(state: &mut Option<(Instant, u64)>, now: Instant, window: Duration) -> Option<u64> {
+    match state {
+        Some((start, suppressed)) if now.duration_since(*start) < window => {
+            *suppressed += 1;
+            None
+        }
+        _ => {
+            let suppressed = state.map_or(0, |(_, suppressed)| suppressed);
+            *state = Some((now, 0));
+            Some(suppressed)
+        }
+    }
+}
+
+/// Where an alert flood stands after the latest poll.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StormPhase {
+    Calm,
+    Started,
+    Raging,
+    Subsided,
+}
+
+/// Counts the notifications each poll would emit over a sliding window and
+/// flags a storm while they exceed the threshold.
+pub struct StormDetector {
+    threshold: usize,
+    window: std::time::Duration,
+    recent: VecDeque<Instant>,
+    raging: bool,
+}
+
+impl StormDetector {
+    pub const fn new(threshold: usize, window: std::time::Duration) -> Self {
+        Self {
+            threshold,
+            window,
+            recent: VecDeque::new(),
+            raging: false,
+        }
+    }
+
+    /// Record `count` notifications at `now`. Call it on every poll, even
+    /// with zero, so a storm can end once the window drains.
+    pub fn observe(&mut self, now: Instant, count: usize) -> StormPhase {
+        while let Some(front) = self.recent.front() {
+            if now.duration_since(*front) > self.window {
+                self.recent.pop_front();
+            } else {
+                break;
+            }
+        }
+        self.recent.extend(std::iter::repeat_n(now, count));
+
+        let flooded = self.recent.len() > self.threshold;
+        let phase = match (self.raging, flooded) {
+            (false, false) => StormPhase::Calm,
+            (false, true) => StormPhase::Started,
+            (true, true) => StormPhase::Raging,
+            (true, false) => StormPhase::Subsided,
+        };
+        self.raging = flooded;
+        phase
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::LeakyBucket;
+    use super::{LeakyBucket, LogSampler, StormDetector, StormPhase};
     use std::time::{Duration, Instant};
 
     #[test]
@@ -55,4 +156,41 @@ mod tests {
         let later = now + Duration::from_secs(2);
         assert!(bucket.try_acquire(later));
     }
+
+    #[test]
+    fn log_sampler_logs_first_per_window_with_suppressed_count() {
+        let sampler = LogSampler::new(Duration::from_mins(1));
+        let now = Instant::now();
+        let at = |secs| now + Duration::from_secs(secs);
+        assert_eq!(sampler.sample_at(now), Some(0));
+        assert_eq!(sampler.sample_at(at(1)), None);
+        assert_eq!(sampler.sample_at(at(59)), None);
+        assert_eq!(sampler.sample_at(at(60)), Some(2));
+        // Hours after a flood the next line is logged again.
+        assert_eq!(sampler.sample_at(at(4 * 3600)), Some(0));
+    }
+
+    #[test]
+    fn storm_starts_above_threshold_and_subsides_when_window_drains() {
+        let mut storm = StormDetector::new(3, Duration::from_mins(1));
+        let now = Instant::now();
+        assert_eq!(storm.observe(now, 3), StormPhase::Calm);
+        assert_eq!(storm.observe(now, 1), StormPhase::Started);
+        assert_eq!(
+            storm.observe(now + Duration::from_secs(30), 2),
+            StormPhase::Raging
+        );
+        assert_eq!(
+            storm.observe(now + Duration::from_secs(45), 0),
+            StormPhase::Raging
+        );
+        assert_eq!(
+            storm.observe(now + Duration::from_secs(61), 0),
+            StormPhase::Subsided
+        );
+        assert_eq!(
+            storm.observe(now + Duration::from_secs(62), 0),
+            StormPhase::Calm
+        );
+    }
 }
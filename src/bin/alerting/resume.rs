@@ -0,0 +1,87 @@
+//! Suspend/resume detection.
+//!
+//! After the machine wakes up the poll loop may still be sleeping for most of
+//! a poll interval. The watcher returned by [`watch`] is notified on resume so
+//! the loop can poll immediately. On Linux it listens to logind's
+//! `PrepareForSleep` signal; elsewhere (or when the system bus is unavailable)
+//! it falls back to detecting wall-clock jumps, since the monotonic clock used
+//! by timers stops while suspended.
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use tokio::sync::Notify;
+use tokio::time::sleep;
+use tracing::info;
+
+/// How often the fallback detector checks the wall clock.
+const CLOCK_TICK: Duration = Duration::from_secs(10);
+/// Extra wall-clock time over a tick that is treated as a suspend.
+const CLOCK_SLACK: Duration = Duration::from_secs(30);
+
+/// Spawn the resume watcher and return the handle notified on each wake-up.
+pub fn watch() -> Arc<Notify> {
+    let resumed = Arc::new(Notify::new());
+    #[cfg(target_os = "linux")]
+    tokio::spawn(logind::watch(Arc::clone(&resumed)));
+    #[cfg(not(target_os = "linux"))]
+    tokio::spawn(watch_clock(Arc::clone(&resumed)));
+    resumed
+}
+
+async fn watch_clock(resumed: Arc<Notify>) {
+    loop {
+        let before = SystemTime::now();
+        sleep(CLOCK_TICK).await;
+        let elapsed = SystemTime::now().duration_since(before).unwrap_or_default();
+        if elapsed > CLOCK_TICK + CLOCK_SLACK {
+            info!(
+                gap_secs = elapsed.as_secs(),
+                "wall clock jumped, assuming resume from suspend"
+            );
+            resumed.notify_one();
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod logind {
+    use std::sync::Arc;
+
+    use futures_util::StreamExt;
+    use tokio::sync::Notify;
+    use tracing::{debug, info};
+
+    #[zbus::proxy(
+        interface = "org.freedesktop.login1.Manager",
+        default_service = "org.freedesktop.login1",
+        default_path = "/org/freedesktop/login1"
+    )]
+    trait Manager {
+        #[zbus(signal)]
+        fn prepare_for_sleep(&self, start: bool) -> zbus::Result<()>;
+    }
+
+    pub(super) async fn watch(resumed: Arc<Notify>) {
+        match listen(&resumed).await {
+            Ok(()) => debug!("logind signal stream ended"),
+            Err(err) => debug!(error = %err, "logind unavailable"),
+        }
+        debug!("falling back to wall-clock resume detection");
+        super::watch_clock(resumed).await;
+    }
+
+    async fn listen(resumed: &Notify) -> zbus::Result<()> {
+        let connection = zbus::Connection::system().await?;
+        let manager = ManagerProxy::new(&connection).await?;
+        let mut signals = manager.receive_prepare_for_sleep().await?;
+        debug!("listening for logind PrepareForSleep");
+        while let Some(signal) = signals.next().await {
+            if !signal.args()?.start {
+                info!("resumed from suspend");
+                resumed.notify_one();
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+//! Business services impacted by each problem, from `service.get`.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use alerting::zbx_client::ZbxClient;
+use tracing::{debug, warn};
+
+/// Event → service names map, refetched once per `ttl` and when a problem
+/// newer than the last fetch shows up.
+pub struct ServiceCache {
+    ttl: Duration,
+    fetched_at: Option<Instant>,
+    by_event: HashMap<String, Vec<String>>,
+    /// Events that existed at the last fetch: missing from `by_event`, they
+    /// impact no service.
+    checked: HashSet<String>,
+    /// The last fetch failed; only the TTL brings the next one.
+    failed: bool,
+}
+
+impl ServiceCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            fetched_at: None,
+            by_event: HashMap::new(),
+            checked: HashSet::new(),
+            failed: false,
+        }
+    }
+
+    /// Refetch the map once it is older than the TTL, or when one of
+    /// `event_ids` appeared after the last fetch. A failed lookup keeps the
+    /// previous map and is not retried before the TTL expires again, so a
+    /// token without access to services only costs one warning per TTL.
+    pub async fn refresh(&mut self, client: &ZbxClient, event_ids: &[&str]) {
+        let now = Instant::now();
+        if !self.is_stale(now) && (self.failed || !self.is_missing(event_ids)) {
+            return;
+        }
+        self.fetched_at = Some(now);
+        self.checked = event_ids.iter().map(|id| (*id).to_string()).collect();
+        match client.services_by_event().await {
+            Ok(by_event) => {
+                debug!(events = by_event.len(), "refreshed business service impact");
+                self.by_event = by_event;
+                self.failed = false;
+            }
+            Err(err) => {
+                self.failed = true;
+                warn!(
+                    error_code = err.code(),
+                    error = %err,
+                    "failed to look up impacted business services"
+                );
+            }
+        }
+    }
+
+    /// Names of the services `event_id` impacts, empty when none.
+    pub fn for_event(&self, event_id: &str) -> Vec<String> {
+        self.by_event.get(event_id).cloned().unwrap_or_default()
+    }
+
+    /// Whether one of `event_ids` is newer than the last fetch.
+    fn is_missing(&self, event_ids: &[&str]) -> bool {
+        event_ids
+            .iter()
+            .any(|id| !self.by_event.contains_key(*id) && !self.checked.contains(*id))
+    }
+
+    fn is_stale(&self, now: Instant) -> bool {
+        self.fetched_at
+            .is_none_or(|at| now.duration_since(at) >= self.ttl)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ServiceCache;
+    use alerting::zbx_client::ZbxClient;
+    use secrecy::SecretString;
+    use serde_json::json;
+    use std::time::{Duration, Instant};
+    use url::Url;
+    use wiremock::matchers::{body_string_contains, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn services(result: &serde_json::Value) -> ResponseTemplate {
+        ResponseTemplate::new(200)
+            .set_body_json(json!({ "jsonrpc": "2.0", "result": result, "id": 1 }))
+    }
+
+    #[tokio::test]
+    async fn new_events_refetch_before_the_ttl() -> Result<(), Box<dyn std::error::Error>> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("service.get"))
+            .respond_with(services(&json!([
+                { "serviceid": "1", "name": "Payments", "problem_events": [{ "eventid": "42" }] }
+            ])))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("service.get"))
+            .respond_with(services(&json!([
+                {
+                    "serviceid": "1",
+                    "name": "Payments",
+                    "problem_events": [{ "eventid": "42" }, { "eventid": "43" }]
+                }
+            ])))
+            .mount(&server)
+            .await;
+        let client = ZbxClient::new(
+            Url::parse(&server.uri())?,
+            SecretString::from("token"),
+            Duration::from_secs(2),
+            Duration::from_secs(1),
+            true,
+        )?;
+
+        let mut cache = ServiceCache::new(Duration::from_hours(1));
+        cache.refresh(&client, &["42", "7"]).await;
+        assert_eq!(cache.for_event("42"), ["Payments"]);
+        // Known events, with or without services, cost no call.
+        cache.refresh(&client, &["42", "7"]).await;
+        cache.refresh(&client, &["42", "43"]).await;
+        assert_eq!(cache.for_event("43"), ["Payments"]);
+        let calls = server.received_requests().await.unwrap_or_default();
+        assert_eq!(calls.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn cache_goes_stale_after_ttl() {
+        let mut cache = ServiceCache::new(Duration::from_mins(1));
+        let now = Instant::now();
+        assert!(cache.is_stale(now));
+        cache.fetched_at = Some(now);
+        assert!(!cache.is_stale(now + Duration::from_secs(59)));
+        assert!(cache.is_stale(now + Duration::from_mins(1)));
+        assert!(cache.for_event("42").is_empty());
+    }
+}
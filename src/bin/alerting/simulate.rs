@@ -0,0 +1,105 @@
+//! Synthetic problems for `--simulate`, fed through the normal pipeline.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use alerting::types::Severity;
+use alerting::zbx_client::{HostMeta, Problem};
+
+const DEFAULT_HOSTS: &[&str] = &["sim-web-01", "sim-db-01", "sim-cache-01"];
+const DEFAULT_SEVERITIES: &[Severity] = &[
+    Severity::Info,
+    Severity::Warning,
+    Severity::Average,
+    Severity::High,
+    Severity::Disaster,
+];
+const PROBLEM_NAMES: &[&str] = &[
+    "High CPU utilization (over 90% for 5m)",
+    "Free disk space is less than 10% on volume /",
+    "Zabbix agent is not available (for 3m)",
+    "Interface eth0: Link down",
+    "MySQL: Service is down",
+    "Certificate expires in less than 7 days",
+];
+
+pub struct Simulator {
+    per_poll: u32,
+    severities: Vec<Severity>,
+    hosts: Vec<String>,
+    next_event: u64,
+    rng: fastrand::Rng,
+}
+
+impl Simulator {
+    /// Empty `severities`/`hosts` fall back to every severity and a few sample hosts.
+    pub fn new(per_poll: u32, severities: Vec<Severity>, hosts: Vec<String>) -> Self {
+        Self {
+            per_poll,
+            severities: if severities.is_empty() {
+                DEFAULT_SEVERITIES.to_vec()
+            } else {
+                severities
+            },
+            hosts: if hosts.is_empty() {
+                DEFAULT_HOSTS.iter().map(ToString::to_string).collect()
+            } else {
+                hosts
+            },
+            next_event: 1,
+            rng: fastrand::Rng::new(),
+        }
+    }
+
+    /// Problems "returned" by one poll, with their host.
+    pub fn next_batch(&mut self) -> Vec<(Problem, Option<HostMeta>)> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .and_then(|d| i64::try_from(d.as_secs()).ok())
+            .unwrap_or_default();
+
+        (0..self.per_poll)
+            .map(|_| {
+                let event_id = format!("sim-{}", self.next_event);
+                self.next_event += 1;
+                let host = &self.hosts[self.rng.usize(..self.hosts.len())];
+                let problem = Problem {
+                    event_id,
+                    clock: now,
+                    last_change: now,
+                    name: PROBLEM_NAMES[self.rng.usize(..PROBLEM_NAMES.len())].to_string(),
+                    severity: self.severities[self.rng.usize(..self.severities.len())],
+                    acknowledged: false,
+                    cause_event_id: None,
+                    trigger_id: None,
+                    tags: Vec::new(),
+                };
+                let host = HostMeta {
+                    host_id: None,
+                    host: Some(host.clone()),
+                    display_name: host.clone(),
+                    status: Some(0),
+                    availability: None,
+                };
+                (problem, Some(host))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Simulator;
+    use alerting::types::Severity;
+
+    #[test]
+    fn batches_use_configured_severities_and_hosts() {
+        let mut sim = Simulator::new(3, vec![Severity::High], vec!["lab".to_string()]);
+        let batch = sim.next_batch();
+        assert_eq!(batch.len(), 3);
+        assert!(batch.iter().all(|(p, h)| {
+            p.severity == Severity::High && h.as_ref().is_some_and(|h| h.display_name == "lab")
+        }));
+        assert_ne!(batch[0].0.event_id, batch[1].0.event_id);
+    }
+}
@@ -0,0 +1,184 @@
+//! State kept across daemon restarts in the optional `app.state_file`.
+
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Number of event → notification mappings remembered.
+const MAX_NOTIFICATION_IDS: NonZeroUsize = match NonZeroUsize::new(512) {
+    Some(n) => n,
+    None => NonZeroUsize::MIN,
+};
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct StateData {
+    /// Oldest first.
+    #[serde(default)]
+    notifications: Vec<(String, String)>,
+    /// Unix time `alerting mute` silences low severities until.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    muted_until: Option<i64>,
+}
+
+/// Maps event ids to the backend id of the toast showing them: the
+/// notification id on Linux, the toast group and tag on Windows. Lets a
+/// restarted daemon replace toasts it showed before instead of stacking
+/// duplicates, and withdraw them.
+#[cfg_attr(
+    not(any(
+        all(target_os = "linux", feature = "linux-toasts"),
+        all(target_os = "windows", feature = "windows-toasts")
+    )),
+    allow(dead_code)
+)]
+pub struct NotificationIds {
+    path: Option<PathBuf>,
+    ids: Mutex<LruCache<String, String>>,
+}
+
+impl NotificationIds {
+    /// Load the mappings from `path`; a missing or unreadable file starts empty.
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let mut ids = LruCache::new(MAX_NOTIFICATION_IDS);
+        if let Some(data) = path.as_deref().and_then(read_state) {
+            for (event_id, id) in data.notifications {
+                ids.put(event_id, id);
+            }
+        }
+        Self {
+            path,
+            ids: Mutex::new(ids),
+        }
+    }
+
+    /// The state file, also holding the mute the toast action sets.
+    #[cfg_attr(
+        not(all(target_os = "linux", feature = "linux-toasts")),
+        allow(dead_code)
+    )]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    #[cfg_attr(
+        not(any(
+            all(target_os = "linux", feature = "linux-toasts"),
+            all(target_os = "windows", feature = "windows-toasts")
+        )),
+        allow(dead_code)
+    )]
+    pub fn get(&self, event_id: &str) -> Option<String> {
+        self.ids.lock().ok()?.get(event_id).cloned()
+    }
+
+    #[cfg_attr(
+        not(any(
+            all(target_os = "linux", feature = "linux-toasts"),
+            all(target_os = "windows", feature = "windows-toasts")
+        )),
+        allow(dead_code)
+    )]
+    pub fn record(&self, event_id: &str, id: String) {
+        let Ok(mut ids) = self.ids.lock() else {
+            return;
+        };
+        ids.put(event_id.to_string(), id);
+        if let Some(path) = self.path.as_deref() {
+            let data = StateData {
+                notifications: ids
+                    .iter()
+                    .rev()
+                    .map(|(event, id)| (event.clone(), id.clone()))
+                    .collect(),
+                // Written by `alerting mute` from another process.
+                muted_until: read_state(path).and_then(|data| data.muted_until),
+            };
+            if let Err(err) = write_state(path, &data) {
+                warn!(path = %path.display(), error = %err, "failed to write state file");
+            }
+        }
+    }
+}
+
+/// Unix time the mute recorded in `path` ends, if it is still ahead of `now`.
+pub fn muted_until(path: &Path, now: i64) -> Option<i64> {
+    read_state(path)?.muted_until.filter(|until| *until > now)
+}
+
+/// Record a mute ending at `until`, or lift it with `None`. The toast ids
+/// the daemon keeps in the same file are left alone.
+pub fn set_muted_until(path: &Path, until: Option<i64>) -> std::io::Result<()> {
+    let mut data = read_state(path).unwrap_or_default();
+    data.muted_until = until;
+    write_state(path, &data)
+}
+
+fn read_state(path: &Path) -> Option<StateData> {
+    let raw = match std::fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(err) => {
+            warn!(path = %path.display(), error = %err, "failed to read state file");
+            return None;
+        }
+    };
+    serde_json::from_str(&raw)
+        .inspect_err(
+            |err| warn!(path = %path.display(), error = %err, "ignoring invalid state file"),
+        )
+        .ok()
+}
+
+fn write_state(path: &Path, data: &StateData) -> std::io::Result<()> {
+    alerting::paths::create_parent(path)?;
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, serde_json::to_vec(data)?)?;
+    std::fs::rename(tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NotificationIds, muted_until, set_muted_until};
+
+    #[test]
+    fn ids_survive_reload() {
+        let path =
+            std::env::temp_dir().join(format!("alerting-state-{}.json", uuid::Uuid::new_v4()));
+
+        let ids = NotificationIds::load(Some(path.clone()));
+        ids.record("101", "7".to_string());
+        ids.record("102", "8".to_string());
+
+        let reloaded = NotificationIds::load(Some(path.clone()));
+        assert_eq!(reloaded.get("101").as_deref(), Some("7"));
+        assert_eq!(reloaded.get("102").as_deref(), Some("8"));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn mute_and_ids_share_the_file() -> std::io::Result<()> {
+        let path =
+            std::env::temp_dir().join(format!("alerting-state-{}.json", uuid::Uuid::new_v4()));
+
+        let ids = NotificationIds::load(Some(path.clone()));
+        ids.record("101", "7".to_string());
+        set_muted_until(&path, Some(2_000))?;
+        ids.record("102", "8".to_string());
+        assert_eq!(muted_until(&path, 1_000), Some(2_000));
+        assert_eq!(muted_until(&path, 2_000), None);
+        assert_eq!(
+            NotificationIds::load(Some(path.clone()))
+                .get("101")
+                .as_deref(),
+            Some("7")
+        );
+
+        set_muted_until(&path, None)?;
+        assert_eq!(muted_until(&path, 1_000), None);
+        std::fs::remove_file(path)
+    }
+}
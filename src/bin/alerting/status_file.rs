@@ -0,0 +1,212 @@
+//! `app.status_file`: a JSON snapshot of the daemon's health, rewritten
+//! after every poll so status bars (waybar, polybar, Rainmeter...) can show
+//! it without talking to Zabbix.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use alerting::error::Error as AlertError;
+use alerting::types::Severity;
+use alerting::zbx_client::CallStats;
+use serde::Serialize;
+use tracing::{debug, warn};
+
+use super::history::unix_now;
+use super::poll_diff::ActiveProblems;
+
+#[derive(Serialize)]
+struct Snapshot<'a> {
+    /// Unix time the file was written.
+    updated_at: i64,
+    /// Unix time of the last successful poll.
+    last_poll: Option<i64>,
+    /// Problems active at the last successful poll.
+    active: usize,
+    unacknowledged: usize,
+    /// Every severity, zero included, so bars need no default.
+    by_severity: BTreeMap<Severity, usize>,
+    /// Notifications waiting for the notifier.
+    queue_depth: usize,
+    /// Set while polls fail.
+    error: Option<&'a PollError>,
+    /// Latency and errors of each JSON-RPC method since the start.
+    zabbix_calls: BTreeMap<&'a str, CallSummary>,
+}
+
+#[derive(Serialize)]
+struct CallSummary {
+    calls: u64,
+    errors: u64,
+    error_rate: f64,
+    p50_ms: u64,
+    p95_ms: u64,
+    max_ms: u64,
+}
+
+impl From<&CallStats> for CallSummary {
+    fn from(stats: &CallStats) -> Self {
+        Self {
+            calls: stats.calls,
+            errors: stats.errors,
+            error_rate: stats.error_rate(),
+            p50_ms: stats.quantile_ms(0.5),
+            p95_ms: stats.quantile_ms(0.95),
+            max_ms: stats.max_ms,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PollError {
+    code: &'static str,
+    message: String,
+    /// Unix time of the first failure in a row.
+    since: i64,
+}
+
+pub struct StatusFile {
+    path: PathBuf,
+    last_poll: Option<i64>,
+    error: Option<PollError>,
+    /// Whether the last write failed, so that failure is logged once.
+    failing: bool,
+}
+
+impl StatusFile {
+    pub const fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            last_poll: None,
+            error: None,
+            failing: false,
+        }
+    }
+
+    /// Record the outcome of a poll (`failure` when it failed) and rewrite
+    /// the file, with the client's `calls` so far.
+    pub fn update(
+        &mut self,
+        failure: Option<&AlertError>,
+        active: &ActiveProblems,
+        queue_depth: usize,
+        calls: &[CallStats],
+    ) {
+        let now = unix_now();
+        match failure {
+            None => {
+                self.last_poll = Some(now);
+                self.error = None;
+            }
+            Some(err) => {
+                let since = self.error.as_ref().map_or(now, |error| error.since);
+                self.error = Some(PollError {
+                    code: err.code(),
+                    message: err.to_string(),
+                    since,
+                });
+            }
+        }
+        let snapshot = self.snapshot(now, active, queue_depth, calls);
+        match self.write(&snapshot) {
+            Ok(()) => {
+                self.failing = false;
+                debug!(path = %self.path.display(), "status file written");
+            }
+            Err(err) if !self.failing => {
+                self.failing = true;
+                warn!(path = %self.path.display(), error = %err, "failed to write status file");
+            }
+            Err(_) => {}
+        }
+    }
+
+    fn snapshot<'a>(
+        &'a self,
+        now: i64,
+        active: &ActiveProblems,
+        queue_depth: usize,
+        calls: &'a [CallStats],
+    ) -> Snapshot<'a> {
+        let mut by_severity: BTreeMap<Severity, usize> = Severity::ALL
+            .into_iter()
+            .map(|severity| (severity, 0))
+            .collect();
+        let mut unacknowledged = 0;
+        for problem in active.problems() {
+            *by_severity.entry(problem.severity).or_default() += 1;
+            unacknowledged += usize::from(!problem.acknowledged);
+        }
+        Snapshot {
+            updated_at: now,
+            last_poll: self.last_poll,
+            active: active.len(),
+            unacknowledged,
+            by_severity,
+            queue_depth,
+            error: self.error.as_ref(),
+            zabbix_calls: calls
+                .iter()
+                .map(|stats| (stats.method.as_str(), CallSummary::from(stats)))
+                .collect(),
+        }
+    }
+
+    /// Replace the file at once, so readers never see half of it.
+    fn write(&self, snapshot: &Snapshot<'_>) -> std::io::Result<()> {
+        alerting::paths::create_parent(&self.path)?;
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(snapshot)?)?;
+        std::fs::rename(tmp, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alerting::error::{Error as AlertError, ZbxError};
+    use alerting::zbx_client::CallStats;
+    use serde_json::Value;
+
+    use super::StatusFile;
+    use crate::poll_diff::ActiveProblems;
+
+    #[test]
+    fn errors_are_kept_until_a_poll_succeeds() -> Result<(), Box<dyn std::error::Error>> {
+        let path =
+            std::env::temp_dir().join(format!("alerting-status-{}.json", uuid::Uuid::new_v4()));
+        let read = || -> Result<Value, Box<dyn std::error::Error>> {
+            Ok(serde_json::from_slice(&std::fs::read(&path)?)?)
+        };
+        let mut status = StatusFile::new(path.clone());
+        let active = ActiveProblems::default();
+
+        let calls = [CallStats {
+            method: "problem.get".to_string(),
+            calls: 4,
+            errors: 1,
+            max_ms: 80,
+            ..CallStats::default()
+        }];
+        status.update(None, &active, 2, &calls);
+        let written = read()?;
+        assert!(written["last_poll"].is_i64());
+        assert_eq!(written["queue_depth"], 2);
+        assert_eq!(written["zabbix_calls"]["problem.get"]["errors"], 1);
+        assert_eq!(written["zabbix_calls"]["problem.get"]["error_rate"], 0.25);
+        assert_eq!(written["by_severity"]["disaster"], 0);
+        assert!(written["error"].is_null());
+
+        let err = AlertError::Zabbix(ZbxError::Api {
+            code: -32_500,
+            message: "Application error.".to_string(),
+        });
+        status.update(Some(&err), &active, 0, &[]);
+        let failed = read()?;
+        assert_eq!(failed["error"]["code"], err.code());
+        assert_eq!(failed["last_poll"], written["last_poll"]);
+
+        status.update(None, &active, 0, &[]);
+        assert!(read()?["error"].is_null());
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,52 @@
+//! `alerting suppress`: silence a problem in Zabbix for a while.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alerting::Result;
+use alerting::config::DisplaySettings;
+use alerting::zbx_client::ZbxClient;
+
+use super::history::unix_now;
+
+pub async fn run(
+    client: &ZbxClient,
+    event_id: &str,
+    until: Option<Duration>,
+    message: Option<&str>,
+    display: &DisplaySettings,
+) -> Result<()> {
+    let until = until.map(|wait| unix_after(SystemTime::now(), wait));
+    client
+        .suppress_event(event_id, until, message.map(str::to_string))
+        .await?;
+    match until {
+        Some(end) => println!(
+            "event {event_id} suppressed until {}",
+            display.when(end, unix_now(), "%Y-%m-%d %H:%M")
+        ),
+        None => println!("event {event_id} suppressed until unsuppressed"),
+    }
+    Ok(())
+}
+
+/// Unix time `wait` after `now`, rounded up to the second so a short
+/// suppression is not cut.
+pub fn unix_after(now: SystemTime, wait: Duration) -> i64 {
+    let at = now.duration_since(UNIX_EPOCH).unwrap_or_default() + wait;
+    let secs = at.as_secs() + u64::from(at.subsec_nanos() > 0);
+    i64::try_from(secs).unwrap_or(i64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unix_after;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn duration_becomes_a_unix_timestamp() {
+        let now = UNIX_EPOCH + Duration::from_millis(1_700_000_000_500);
+        assert_eq!(unix_after(now, Duration::from_hours(2)), 1_700_007_201);
+        let whole = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(unix_after(whole, Duration::from_hours(2)), 1_700_007_200);
+    }
+}
@@ -0,0 +1,96 @@
+//! Zabbix API token asked for on the terminal when the configuration has
+//! none, and optionally kept in the system keyring (Secret Service on Linux,
+//! Credential Manager on Windows) for the next start.
+
+use std::io::IsTerminal;
+
+use secrecy::SecretString;
+use tracing::warn;
+
+/// Keyring entry holding the token.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+const SERVICE: &str = "alerting";
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+const USER: &str = "zabbix.token";
+
+/// Token saved in the keyring by an earlier prompt, if any.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+pub fn stored() -> Option<SecretString> {
+    let password = keyring_call(keyring::Entry::get_password);
+    match password {
+        Ok(token) => {
+            tracing::info!("using the Zabbix token stored in the keyring");
+            Some(token.into())
+        }
+        Err(keyring::Error::NoEntry) => None,
+        Err(err) => {
+            warn!(error = %err, "failed to read the Zabbix token from the keyring");
+            None
+        }
+    }
+}
+
+/// No keyring on this platform.
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub const fn stored() -> Option<SecretString> {
+    None
+}
+
+/// Ask for the token when stdin is a terminal, then offer to store it in
+/// the keyring. `None` without a terminal or when nothing was typed.
+pub fn prompt() -> Option<SecretString> {
+    if !std::io::stdin().is_terminal() {
+        return None;
+    }
+    let token = match rpassword::prompt_password("Zabbix API token: ") {
+        Ok(token) if !token.trim().is_empty() => token.trim().to_string(),
+        Ok(_) => return None,
+        Err(err) => {
+            warn!(error = %err, "failed to read the Zabbix token");
+            return None;
+        }
+    };
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    if confirm("Store it in the system keyring? [y/N] ") {
+        match keyring_call(|entry| entry.set_password(&token)) {
+            Ok(()) => println!("token stored; it will be used while zabbix.token is unset"),
+            Err(err) => warn!(error = %err, "failed to store the Zabbix token in the keyring"),
+        }
+    }
+    Some(token.into())
+}
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn confirm(question: &str) -> bool {
+    use std::io::{BufRead, Write};
+
+    print!("{question}");
+    let _ = std::io::stdout().flush();
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer).is_ok()
+        && matches!(
+            answer.trim().to_ascii_lowercase().as_str(),
+            "y" | "yes" | "o" | "oui"
+        )
+}
+
+/// Run `call` on the token entry. The Secret Service client blocks on its
+/// own runtime, which cannot start on a worker thread of ours.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn keyring_call<T>(
+    call: impl FnOnce(&keyring::Entry) -> keyring::Result<T> + Send,
+) -> keyring::Result<T>
+where
+    T: Send,
+{
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| keyring::Entry::new(SERVICE, USER).and_then(|entry| call(&entry)))
+            .join()
+            .unwrap_or_else(|_| {
+                Err(keyring::Error::PlatformFailure(
+                    "keyring thread panicked".into(),
+                ))
+            })
+    })
+}
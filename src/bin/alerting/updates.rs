@@ -0,0 +1,274 @@
+//! Comments and acknowledgements others add to problems already shown, from
+//! `event.get` with `select_acknowledges` (`notify.comment_updates`).
+
+use std::num::NonZeroUsize;
+use std::time::Duration;
+
+use alerting::zbx_client::{EventUpdate, ZbxClient};
+use lru::LruCache;
+use tracing::{debug, warn};
+
+use super::rate_limit::LogSampler;
+
+/// Problems followed at once; the least recently shown are dropped.
+const MAX_WATCHED: NonZeroUsize = match NonZeroUsize::new(256) {
+    Some(n) => n,
+    None => NonZeroUsize::MIN,
+};
+
+/// Servers before Zabbix 6.4 never tell the token's user, so the failed
+/// lookup is retried every poll but logged at most once an hour.
+static OWN_USER_LOG: LogSampler = LogSampler::new(Duration::from_hours(1));
+
+/// A problem shown as a toast, followed until it is resolved.
+struct Watched {
+    problem: String,
+    host: Option<String>,
+    open_url: Option<String>,
+    /// Server time of the problem's last change: older updates were
+    /// already on the toast.
+    since: i64,
+    /// Last update reported.
+    last_update: i64,
+}
+
+/// A comment or acknowledgement someone else made on a followed problem.
+pub struct EventNews {
+    pub event_id: String,
+    pub problem: String,
+    pub host: Option<String>,
+    pub open_url: Option<String>,
+    pub author: String,
+    pub message: String,
+    pub acknowledged: bool,
+}
+
+pub struct UpdateWatch {
+    watched: LruCache<String, Watched>,
+    /// The token's user, looked up until a check succeeds; while `None`,
+    /// every update counts as someone else's.
+    own_user: Option<String>,
+}
+
+impl UpdateWatch {
+    pub fn new() -> Self {
+        Self {
+            watched: LruCache::new(MAX_WATCHED),
+            own_user: None,
+        }
+    }
+
+    /// Follow `event_id`, reporting updates made from `since` on. It is a
+    /// Zabbix timestamp (the problem's `clock` or `lastchange`), so it
+    /// compares with update clocks whatever the local clock says.
+    pub fn watch(
+        &mut self,
+        event_id: &str,
+        problem: &str,
+        host: Option<&str>,
+        open_url: Option<&str>,
+        since: i64,
+    ) {
+        if self.watched.contains(event_id) {
+            self.watched.promote(event_id);
+            return;
+        }
+        self.watched.put(
+            event_id.to_string(),
+            Watched {
+                problem: problem.to_string(),
+                host: host.map(str::to_string),
+                open_url: open_url.map(str::to_string),
+                since,
+                last_update: 0,
+            },
+        );
+    }
+
+    /// Stop following resolved problems.
+    pub fn forget<'a>(&mut self, event_ids: impl IntoIterator<Item = &'a str>) {
+        for event_id in event_ids {
+            self.watched.pop(event_id);
+        }
+    }
+
+    /// Updates made by others since the last check. A failed lookup is
+    /// logged and retried at the next poll.
+    pub async fn check(&mut self, client: &ZbxClient) -> Vec<EventNews> {
+        if self.watched.is_empty() {
+            return Vec::new();
+        }
+        if self.own_user.is_none() {
+            self.own_user = client
+                .token_user_id()
+                .await
+                .inspect_err(|err| {
+                    if let Some(suppressed) = OWN_USER_LOG.sample() {
+                        warn!(
+                            error_code = err.code(),
+                            error = %err,
+                            suppressed,
+                            "cannot tell the token's user; reporting its own comments too"
+                        );
+                    }
+                })
+                .ok();
+        }
+        let event_ids: Vec<String> = self.watched.iter().map(|(id, _)| id.clone()).collect();
+        match client.event_updates(&event_ids).await {
+            Ok(by_event) => by_event
+                .into_iter()
+                .flat_map(|(event_id, updates)| self.news(&event_id, updates))
+                .collect(),
+            Err(err) => {
+                warn!(
+                    error_code = err.code(),
+                    error = %err,
+                    "failed to look up problem comments"
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    fn news(&mut self, event_id: &str, updates: Vec<EventUpdate>) -> Vec<EventNews> {
+        let own_user = self.own_user.clone();
+        let Some(watched) = self.watched.peek_mut(event_id) else {
+            return Vec::new();
+        };
+        let mut news = Vec::new();
+        for update in updates {
+            if update.update_id <= watched.last_update {
+                continue;
+            }
+            watched.last_update = update.update_id;
+            let message = update.message.trim();
+            let relevant = update.acknowledges() || !message.is_empty();
+            if update.clock < watched.since
+                || !relevant
+                || own_user.as_deref() == Some(update.user_id.as_str())
+            {
+                continue;
+            }
+            debug!(
+                event_id,
+                update_id = update.update_id,
+                "new update on a shown problem"
+            );
+            news.push(EventNews {
+                event_id: event_id.to_string(),
+                problem: watched.problem.clone(),
+                host: watched.host.clone(),
+                open_url: watched.open_url.clone(),
+                author: update.author(),
+                message: message.to_string(),
+                acknowledged: update.acknowledges(),
+            });
+        }
+        news
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UpdateWatch;
+    use alerting::zbx_client::{EventUpdate, ZbxClient};
+    use secrecy::SecretString;
+    use serde_json::json;
+    use std::time::Duration;
+    use url::Url;
+    use wiremock::matchers::{body_string_contains, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn update(
+        update_id: i64,
+        user_id: &str,
+        clock: i64,
+        action: i64,
+        message: &str,
+    ) -> EventUpdate {
+        EventUpdate {
+            update_id,
+            user_id: user_id.to_string(),
+            clock,
+            action,
+            message: message.to_string(),
+            username: Some("jdoe".to_string()),
+            name: Some("Jane".to_string()),
+            surname: Some("Doe".to_string()),
+        }
+    }
+
+    #[test]
+    fn only_new_updates_of_others_are_news() {
+        let mut watch = UpdateWatch::new();
+        watch.own_user = Some("1".to_string());
+        watch.watch("42", "Disk full", Some("db01"), None, 100);
+
+        let updates = vec![
+            update(1, "2", 90, 4, "seen before the toast"),
+            update(2, "1", 110, 2, "our own ack"),
+            update(3, "2", 120, 4, "looking into it"),
+            update(4, "2", 130, 4, "   "),
+        ];
+        let news = watch.news("42", updates.clone());
+        assert_eq!(news.len(), 1);
+        assert_eq!(news[0].author, "Jane Doe");
+        assert_eq!(news[0].message, "looking into it");
+        assert!(!news[0].acknowledged);
+
+        assert!(watch.news("42", updates).is_empty());
+        watch.forget(["42"]);
+        assert!(
+            watch
+                .news("42", vec![update(5, "2", 140, 2, "")])
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn own_user_lookup_is_retried_after_a_failure() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let server = MockServer::start().await;
+        let rpc = |result: serde_json::Value| {
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "jsonrpc": "2.0", "result": result, "id": 1 }))
+        };
+        Mock::given(method("POST"))
+            .and(body_string_contains("user.checkAuthentication"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "error": { "code": -32500, "message": "Application error." },
+                "id": 1
+            })))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("user.checkAuthentication"))
+            .respond_with(rpc(json!({ "userid": "1", "type": "3", "roleid": "3" })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("select_acknowledges"))
+            .respond_with(rpc(json!([])))
+            .mount(&server)
+            .await;
+        let client = ZbxClient::new(
+            Url::parse(&server.uri())?,
+            SecretString::from("token"),
+            Duration::from_secs(2),
+            Duration::from_secs(1),
+            true,
+        )?;
+
+        let mut watch = UpdateWatch::new();
+        watch.watch("42", "Disk full", None, None, 1_700_000_000);
+        watch.check(&client).await;
+        assert_eq!(watch.own_user, None);
+        watch.check(&client).await;
+        assert_eq!(watch.own_user.as_deref(), Some("1"));
+        Ok(())
+    }
+}
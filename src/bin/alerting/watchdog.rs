@@ -0,0 +1,63 @@
+//! Sticky warning when no poll has succeeded for `app.watchdog_deadline`, so
+//! an outage of Zabbix or of the network is not mistaken for a quiet day.
+
+use std::time::{Duration, Instant};
+
+use async_channel::{Sender, TrySendError};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+use tracing::{info, warn};
+
+use super::heartbeat::PollStatus;
+use super::notifier::Notice;
+
+/// Warn once per outage and once more when polling recovers, until the poll
+/// loop drops its status sender.
+pub fn spawn(
+    deadline: Duration,
+    mut status: watch::Receiver<Option<PollStatus>>,
+    tx: Sender<Notice>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let started = Instant::now();
+        let mut tripped = false;
+        loop {
+            let last_success = status.borrow().map_or(started, |status| status.at);
+            let silent = last_success.elapsed();
+            if let Some(left) = deadline.checked_sub(silent).filter(|left| !left.is_zero()) {
+                if tripped {
+                    tripped = false;
+                    info!("poll succeeded again, watchdog cleared");
+                    send(&tx, None);
+                }
+                tokio::select! {
+                    changed = status.changed() => {
+                        if changed.is_err() {
+                            break;
+                        }
+                    }
+                    () = sleep(left) => {}
+                }
+                continue;
+            }
+            if !tripped {
+                tripped = true;
+                warn!(
+                    silent_secs = silent.as_secs(),
+                    "no successful poll within the watchdog deadline"
+                );
+                send(&tx, Some(silent));
+            }
+            if status.changed().await.is_err() {
+                break;
+            }
+        }
+    })
+}
+
+fn send(tx: &Sender<Notice>, stalled: Option<Duration>) {
+    if let Err(TrySendError::Full(_)) = tx.try_send(Notice::Watchdog { stalled }) {
+        warn!("notification queue full; dropping watchdog notification");
+    }
+}
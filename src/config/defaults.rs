@@ -4,6 +4,10 @@ pub(super) const fn default_limit() -> u32 {
     20
 }
 
+pub(super) const fn default_max_pages() -> u32 {
+    5
+}
+
 pub(super) const fn default_concurrency() -> usize {
     4
 }
@@ -16,6 +20,49 @@ pub(super) fn default_notify_appname() -> String {
     "Alerting".to_string()
 }
 
+pub(super) fn default_desktop_entry() -> String {
+    "alerting".to_string()
+}
+
+pub(super) fn default_ack_prompt() -> String {
+    "always".to_string()
+}
+
+pub(super) fn default_prompt_command() -> Vec<String> {
+    [
+        "zenity", "--entry", "--title", "{title}", "--text", "{text}",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
+pub(super) fn default_confirm_command() -> Vec<String> {
+    [
+        "zenity",
+        "--question",
+        "--title",
+        "{title}",
+        "--text",
+        "{text}",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
+pub(super) fn default_group_by() -> String {
+    "host".to_string()
+}
+
+pub(super) fn default_event_sources() -> Vec<String> {
+    vec!["trigger".to_string()]
+}
+
+pub(super) fn default_poll_summary() -> String {
+    "off".to_string()
+}
+
 pub(super) fn default_open_label() -> String {
     "Open".to_string()
 }
@@ -40,6 +87,78 @@ pub(super) const fn default_rate_limit_window() -> Duration {
     Duration::from_secs(5)
 }
 
+pub(super) const fn default_service_cache_ttl() -> Duration {
+    Duration::from_mins(1)
+}
+
+pub(super) const fn default_graph_period() -> Duration {
+    Duration::from_hours(1)
+}
+
+pub(super) const fn default_http_timeout() -> Duration {
+    super::DEFAULT_HTTP_TIMEOUT
+}
+
+pub(super) const fn default_connect_timeout() -> Duration {
+    super::DEFAULT_CONNECT_TIMEOUT
+}
+
+pub(super) const fn default_pool_idle_timeout() -> Duration {
+    Duration::from_secs(30)
+}
+
+pub(super) const fn default_host_miss_ttl() -> Duration {
+    Duration::from_mins(5)
+}
+
+pub(super) const fn default_history_max_age() -> Duration {
+    Duration::from_hours(30 * 24)
+}
+
+pub(super) const fn default_storm_window() -> Duration {
+    Duration::from_mins(1)
+}
+
 pub(super) const fn default_poll_interval() -> Duration {
     Duration::from_secs(30)
 }
+
+pub(super) const fn default_true() -> bool {
+    true
+}
+
+pub(super) fn default_work_start() -> String {
+    "08:00".to_string()
+}
+
+pub(super) fn default_work_end() -> String {
+    "19:00".to_string()
+}
+
+pub(super) fn default_heartbeat_every() -> String {
+    "daily".to_string()
+}
+
+pub(super) fn default_heartbeat_weekday() -> String {
+    "mon".to_string()
+}
+
+pub(super) fn default_heartbeat_at() -> String {
+    "09:00".to_string()
+}
+
+pub(super) fn default_heartbeat_backend() -> String {
+    "toast".to_string()
+}
+
+pub(super) fn default_kdeconnect_min_severity() -> String {
+    "average".to_string()
+}
+
+pub(super) fn default_ack_min_severity() -> String {
+    "info".to_string()
+}
+
+pub(super) fn default_mute_floor() -> String {
+    "disaster".to_string()
+}
@@ -1,3 +1,4 @@
+use std::path::Path;
 use std::time::Duration;
 
 use humantime::parse_duration;
@@ -53,3 +54,72 @@ pub(super) fn env_duration(
     }
     Ok(None)
 }
+
+/// A command line: a JSON array of arguments when the value starts with `[`,
+/// otherwise split with shell quoting rules, so arguments may hold spaces.
+/// An empty value is an empty command.
+pub(super) fn env_command(
+    key: &'static str,
+) -> std::result::Result<Option<Vec<String>>, ConfigError> {
+    env_string(key)?
+        .map(|value| {
+            parse_command(&value).map_err(|message| ConfigError::InvalidField {
+                field: key,
+                message,
+            })
+        })
+        .transpose()
+}
+
+/// Arguments of a command-line value; the error says what is malformed.
+pub(super) fn parse_command(value: &str) -> std::result::Result<Vec<String>, String> {
+    let value = value.trim();
+    if value.starts_with('[') {
+        return serde_json::from_str(value)
+            .map_err(|err| format!("expected a JSON array of strings: {err}"));
+    }
+    shlex::split(value).ok_or_else(|| "unbalanced quotes or trailing backslash".to_string())
+}
+
+/// Load `.env` from the directory holding `config_path`, without overriding
+/// variables already present in the environment.
+pub(super) fn load_dotenv(config_path: &Path) -> std::result::Result<(), ConfigError> {
+    let dir = config_path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let path = dir.join(".env");
+    match dotenvy::from_path(&path) {
+        Ok(()) => Ok(()),
+        Err(dotenvy::Error::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(dotenvy::Error::Io(source)) => Err(ConfigError::FileRead { path, source }),
+        Err(err) => Err(ConfigError::Parse(format!("{}: {err}", path.display()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_command;
+
+    #[test]
+    fn commands_keep_quoted_arguments() -> Result<(), Box<dyn std::error::Error>> {
+        assert_eq!(
+            parse_command(r#"kdialog --title "{title}" --inputbox 'Votre message'"#)?,
+            [
+                "kdialog",
+                "--title",
+                "{title}",
+                "--inputbox",
+                "Votre message"
+            ]
+        );
+        assert_eq!(
+            parse_command(r#"["zenity", "--text", "Acquitter ce probleme ?"]"#)?,
+            ["zenity", "--text", "Acquitter ce probleme ?"]
+        );
+        assert!(parse_command("")?.is_empty());
+        assert!(parse_command("zenity --text 'open").is_err());
+        assert!(parse_command(r#"["zenity", 1]"#).is_err());
+        Ok(())
+    }
+}
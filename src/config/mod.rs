@@ -1,60 +1,443 @@
+use std::collections::BTreeMap;
 use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::time::Duration;
 
-use secrecy::SecretString;
+use ::serde::Serialize;
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveDateTime, NaiveTime, TimeZone, Weekday};
+use chrono_tz::Tz;
+use secrecy::{ExposeSecret, SecretString};
+use serde_with::{DisplayFromStr, serde_as};
 use url::Url;
 
 use crate::Result;
 use crate::error::Error as AlertError;
-use crate::types::AckFilter;
+use crate::types::{
+    AckFilter, Color, DetailLevel, EventObject, EventSource, Severity, SymptomMode, Urgency,
+};
 
 mod defaults;
 mod env;
 mod raw;
+mod schema;
 pub(crate) mod serde;
 
+pub use schema::{EnvKind, EnvVariable, env_variables};
 pub use serde::HumantimeDuration;
 
 const MAX_NOTIF_BOUNDS: RangeInclusive<usize> = 1..=100;
 const DEFAULT_HTTP_TIMEOUT: Duration = Duration::from_secs(10);
 const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
 
-#[derive(Debug, Clone)]
+/// Syntax of the configuration file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+/// Options controlling how [`Config::load`] reads the configuration.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LoadOptions<'a> {
+    /// Named profile merged over the top-level settings.
+    pub profile: Option<&'a str>,
+    /// File syntax; guessed from the extension when `None`.
+    pub format: Option<ConfigFormat>,
+    /// Load a `.env` file located next to the configuration file first.
+    pub dotenv: bool,
+    /// API token used when neither the file nor the environment sets
+    /// `zabbix.token`, e.g. one read from the keyring.
+    pub token: Option<&'a SecretString>,
+    /// Fill a missing `zabbix.url` or `zabbix.token` with a placeholder,
+    /// for runs that never contact Zabbix such as `--simulate`.
+    pub offline: bool,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
 pub struct Config {
     pub base_url: Url,
+    /// Other URLs of the same server, tried in order when `base_url` cannot
+    /// be connected to.
+    pub fallback_urls: Vec<Url>,
+    #[serde(serialize_with = "self::serde::redact_secret")]
     pub token: SecretString,
+    /// Problems fetched per `problem.get` call.
     pub limit: u32,
+    /// Calls made per poll while pages come back full.
+    pub max_pages: u32,
     pub concurrency: usize,
     pub ack_filter: AckFilter,
+    /// Only notify problems on hosts monitored by these proxy ids; `"0"`
+    /// stands for the server itself. Empty keeps every host.
+    pub proxy_ids: Vec<String>,
+    /// Look up whether each problem's host is reachable.
+    pub host_availability: bool,
+    pub symptoms: SymptomMode,
+    /// Sources of the problem events fetched; trigger events only by default.
+    pub event_sources: Vec<EventSource>,
+    /// Objects kept among those of `event_sources`; empty keeps them all.
+    pub event_objects: Vec<EventObject>,
+    /// How long the event → business service map is reused; `None` skips
+    /// the service lookup.
+    #[serde_as(as = "Option<HumantimeDuration>")]
+    pub service_impact: Option<Duration>,
+    /// Trigger graph attached to toasts; `None` without frontend credentials.
+    pub graph: Option<GraphImage>,
     pub max_notif: usize,
     pub queue_capacity: usize,
     pub dedup_cache_size: usize,
+    /// Shortest time between two toasts of the same event, whatever its
+    /// `lastchange` does; `None` shows every change.
+    #[serde_as(as = "Option<HumantimeDuration>")]
+    pub notify_cooldown: Option<Duration>,
     pub rate_limit: RateLimit,
+    pub storm: Option<StormMode>,
+    #[serde_as(as = "HumantimeDuration")]
     pub poll_interval: Duration,
     pub open_url_fmt: Option<String>,
+    pub host_url_fmt: Option<String>,
+    /// Toast ids and the current mute; `state.json` in the state directory
+    /// unless set to an empty path.
+    pub state_file: Option<PathBuf>,
+    /// JSON Lines log of notified, dropped, acknowledged and resolved
+    /// problems, read by `alerting history`. Relative paths, like that of
+    /// `state_file`, are taken from the state directory.
+    pub history_file: Option<PathBuf>,
+    /// JSON snapshot of the daemon's health rewritten after every poll, for
+    /// status bars. Relative paths are taken from the state directory.
+    pub status_file: Option<PathBuf>,
+    pub history_retention: HistoryRetention,
+    /// Warn with a sticky toast when no poll succeeded for this long; failed
+    /// polls are then retried instead of stopping the daemon.
+    #[serde_as(as = "Option<HumantimeDuration>")]
+    pub watchdog_deadline: Option<Duration>,
     pub notify: NotifySettings,
+    pub display: DisplaySettings,
+    #[serde_as(as = "HumantimeDuration")]
     pub http_connect_timeout: Duration,
+    #[serde_as(as = "HumantimeDuration")]
     pub http_request_timeout: Duration,
+    /// How long an idle connection to Zabbix is kept for reuse.
+    #[serde_as(as = "HumantimeDuration")]
+    pub http_pool_idle_timeout: Duration,
+    /// Idle connections kept open; `None` does not cap them.
+    pub http_pool_max_idle_per_host: Option<usize>,
+    #[serde_as(as = "Option<HumantimeDuration>")]
+    pub http_tcp_keepalive: Option<Duration>,
+    /// Client-side cap on API requests, shared by polling and acks.
+    pub max_requests_per_second: Option<u32>,
+    pub max_in_flight: Option<usize>,
+    /// How long an event whose host lookup found nothing or failed is not
+    /// looked up again.
+    #[serde_as(as = "HumantimeDuration")]
+    pub host_miss_ttl: Duration,
+    /// Log JSON-RPC requests and responses at debug level, secrets masked.
+    pub log_rpc: bool,
+}
+
+/// How times are shown in the CLI output and exports.
+#[serde_as]
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct DisplaySettings {
+    /// IANA timezone, e.g. `Europe/Paris`; the system's when unset.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub timezone: Option<Tz>,
+    /// Lead with "5 min ago" and keep the absolute time in parentheses.
+    pub relative_time: bool,
+    /// strftime pattern replacing the built-in one of each output.
+    pub datetime_format: Option<String>,
+}
+
+impl DisplaySettings {
+    /// Unix time `unix` formatted with `datetime_format`, else with `fmt`,
+    /// or as a number when out of range.
+    #[must_use]
+    pub fn format(&self, unix: i64, fmt: &str) -> String {
+        let fmt = self.datetime_format.as_deref().unwrap_or(fmt);
+        self.at(unix)
+            .map_or_else(|| unix.to_string(), |at| at.format(fmt).to_string())
+    }
+
+    /// Unix time `unix` as RFC 3339, with the offset of the timezone.
+    #[must_use]
+    pub fn rfc3339(&self, unix: i64) -> String {
+        self.at(unix)
+            .map_or_else(|| unix.to_string(), |at| at.to_rfc3339())
+    }
+
+    /// `unix` as seen at `now`: "5 min ago (<absolute>)" or "in 2 h
+    /// (<absolute>)" with `relative_time`, else the absolute time alone.
+    #[must_use]
+    pub fn when(&self, unix: i64, now: i64, fmt: &str) -> String {
+        let absolute = self.format(unix, fmt);
+        if self.relative_time {
+            format!("{} ({absolute})", relative(now.saturating_sub(unix)))
+        } else {
+            absolute
+        }
+    }
+
+    fn at(&self, unix: i64) -> Option<DateTime<FixedOffset>> {
+        self.timezone.map_or_else(
+            || {
+                Local
+                    .timestamp_opt(unix, 0)
+                    .single()
+                    .map(|at| at.fixed_offset())
+            },
+            |tz| {
+                tz.timestamp_opt(unix, 0)
+                    .single()
+                    .map(|at| at.fixed_offset())
+            },
+        )
+    }
 }
 
-#[derive(Debug, Clone)]
+/// "just now", "5 min ago", "2 h ago", "3 d ago"; "in …" for `age < 0`.
+fn relative(age: i64) -> String {
+    const MINUTE: u64 = 60;
+    const HOUR: u64 = 60 * MINUTE;
+    const DAY: u64 = 24 * HOUR;
+    let (count, unit) = match age.unsigned_abs() {
+        secs if secs < MINUTE => return "just now".to_string(),
+        secs if secs < HOUR => (secs / MINUTE, "min"),
+        secs if secs < DAY => (secs / HOUR, "h"),
+        secs => (secs / DAY, "d"),
+    };
+    if age < 0 {
+        format!("in {count} {unit}")
+    } else {
+        format!("{count} {unit} ago")
+    }
+}
+
+#[allow(clippy::struct_excessive_bools)]
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
 pub struct NotifySettings {
     pub appname: String,
     pub sticky: bool,
+    #[serde_as(as = "Option<HumantimeDuration>")]
     pub timeout: Option<Duration>,
     pub default_timeout: bool,
+    /// Keep critical toasts in the notification server until the daemon
+    /// closes them on resolve or ack (Linux).
+    pub resident: bool,
+    /// Close the toast of a problem once a poll finds it acknowledged, by
+    /// anyone (Linux).
+    pub close_on_ack: bool,
+    /// Toast comments and acknowledgements others add to problems already
+    /// shown.
+    pub comment_updates: bool,
     pub icon: Option<PathBuf>,
+    /// Icon for problems already acknowledged; falls back to `icon`.
+    pub acked_icon: Option<PathBuf>,
+    /// `.wav` or `.ogg` played instead of the system notification sound.
+    pub sound_file: Option<PathBuf>,
     pub open_label: String,
     pub notify_acked: bool,
+    pub schedule: Option<UrgencySchedule>,
+    pub hero_image: Option<PathBuf>,
+    pub attribution: Option<String>,
+    pub group_by: ToastGrouping,
+    /// Emit one toast per host per poll listing its new problems.
+    pub batch_by_host: bool,
+    /// One toast per poll counting what changed, besides or instead of the
+    /// per-problem toasts.
+    pub poll_summary: PollSummary,
+    /// Put the severity's coloured dot in front of toast summaries.
+    pub severity_emoji: bool,
+    pub desktop_entry: Option<String>,
+    /// Command used to ask for an acknowledgement message; `{title}` and
+    /// `{text}` are substituted. Empty disables the prompt.
+    pub prompt_command: Vec<String>,
+    /// Which toasts ask for that message before acknowledging.
+    pub ack_prompt: AckPrompt,
+    /// Ask for a yes/no confirmation before acknowledging from a toast.
+    pub confirm_ack: bool,
+    /// Command asking that confirmation; exit status 0 means yes. Empty, or
+    /// a tool that is not installed, asks with a second toast instead.
+    pub confirm_command: Vec<String>,
+    /// Offer to turn a symptom into a cause from its toast (Zabbix 6.4+).
+    pub rank_action: bool,
+    /// Offer to add a message to the event without acknowledging it.
+    pub comment_action: bool,
+    /// Offer to acknowledge every unacknowledged problem of the host at
+    /// once, on hosts with several.
+    pub host_ack_action: bool,
+    /// Problems below this severity get no ack buttons (Linux).
+    pub ack_min_severity: Severity,
+    /// Severities below this one stay silent while `alerting mute` is on.
+    pub mute_floor: Severity,
+    /// Offer to mute for this long from problem toasts (Linux, needs
+    /// `app.state_file`).
+    pub mute_action: Option<Duration>,
+    pub max_body_lines: Option<usize>,
+    pub max_body_chars: Option<usize>,
+    /// Text around every toast summary, e.g. `[PROD] `, to tell apart
+    /// daemons watching different environments.
+    pub summary_prefix: String,
+    pub summary_suffix: String,
+    /// Per-severity presentation overrides (`[notify.severities.<name>]`).
+    pub severities: BTreeMap<Severity, SeverityStyle>,
+    pub detail: DetailLevel,
+    pub heartbeat: Option<Heartbeat>,
+    pub kdeconnect: Option<KdeConnect>,
+}
+
+/// Periodic "still running" notice, so a daemon that silently died is
+/// noticed by the missing heartbeat.
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
+pub struct Heartbeat {
+    /// Day of the weekly heartbeat; `None` sends one every day.
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub weekday: Option<Weekday>,
+    #[serde_as(as = "DisplayFromStr")]
+    pub at: NaiveTime,
+    pub backend: HeartbeatBackend,
+}
+
+/// Phone paired with KDE Connect that problem notifications are forwarded to.
+#[derive(Debug, Clone, Serialize)]
+pub struct KdeConnect {
+    /// Device id, as listed by `kdeconnect-cli --list-available --id-only`.
+    pub device: String,
+    /// Least severe problem forwarded.
+    pub min_severity: Severity,
+}
+
+/// Where the heartbeat goes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeartbeatBackend {
+    /// A quiet toast replacing the previous heartbeat.
+    Toast,
+    /// An info log line only, for log-based monitoring.
+    Log,
+}
+
+/// How toasts of one severity are presented, overriding the built-in mapping.
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct SeverityStyle {
+    /// Replaces [`Urgency::for_severity`]; the schedule still applies on top.
+    pub urgency: Option<Urgency>,
+    /// Keep the toast on screen until dismissed.
+    pub sticky: bool,
+    /// Force the alert sound on (`true`) or off (`false`); unset follows the
+    /// urgency.
+    pub sound: Option<bool>,
+    /// Accent colour of the toast: a coloured icon on Linux, a coloured
+    /// strip on Windows.
+    pub color: Option<Color>,
+}
+
+impl NotifySettings {
+    /// Style configured for `severity`, or the default one.
+    #[must_use]
+    pub fn style_for(&self, severity: Severity) -> SeverityStyle {
+        self.severities.get(&severity).copied().unwrap_or_default()
+    }
+}
+
+/// Key used to group toasts in the Windows Action Center.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToastGrouping {
+    Host,
+    Severity,
+}
+
+/// Whether a poll that changed something ends with a summary toast.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PollSummary {
+    Off,
+    /// Summary after the per-problem toasts.
+    Also,
+    /// Summary only; problems get no toast of their own.
+    Only,
+}
+
+/// When clicking "Acquitter" asks for a message first.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AckPrompt {
+    Always,
+    /// One-click acknowledgement, without message.
+    Never,
+    /// Only for toasts of critical urgency.
+    CriticalOnly,
+}
+
+impl AckPrompt {
+    #[must_use]
+    pub fn asks(self, urgency: Urgency) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::CriticalOnly => urgency == Urgency::Critical,
+        }
+    }
+}
+
+/// Severity→urgency overrides that depend on the local time of day.
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
+pub struct UrgencySchedule {
+    #[serde_as(as = "DisplayFromStr")]
+    pub work_start: NaiveTime,
+    #[serde_as(as = "DisplayFromStr")]
+    pub work_end: NaiveTime,
+    pub weekends_off: bool,
+    pub off_hours_sticky: bool,
+    pub work_hours: BTreeMap<Severity, Urgency>,
+    pub off_hours: BTreeMap<Severity, Urgency>,
 }
 
-#[derive(Debug, Clone)]
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
 pub struct RateLimit {
     pub max_events: usize,
+    #[serde_as(as = "HumantimeDuration")]
     pub per: Duration,
 }
 
+/// How much of `history_file` survives pruning; `None` sets no limit.
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryRetention {
+    #[serde_as(as = "Option<HumantimeDuration>")]
+    pub max_age: Option<Duration>,
+    pub max_rows: Option<usize>,
+}
+
+/// Alert flood detection: above `threshold` notifications within `window`,
+/// individual toasts give way to a single storm toast.
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
+pub struct StormMode {
+    pub threshold: usize,
+    #[serde_as(as = "HumantimeDuration")]
+    pub window: Duration,
+}
+
+/// Frontend sign-in used to download trigger graphs from `chart2.php`.
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphImage {
+    pub user: String,
+    #[serde(serialize_with = "self::serde::redact_secret")]
+    pub password: SecretString,
+    /// Time span shown by the graph, ending now.
+    #[serde_as(as = "HumantimeDuration")]
+    pub period: Duration,
+}
+
 impl Config {
     /// Load configuration from a file and the environment.
     ///
@@ -64,9 +447,119 @@ impl Config {
     /// when environment overrides are invalid, or when the resulting values
     /// fail validation.
     pub fn from_env_and_file(path: impl AsRef<Path>) -> Result<Self> {
-        let mut raw = raw::load(path).map_err(AlertError::from)?;
+        Self::load(path, LoadOptions::default())
+    }
+
+    /// Load configuration, overlaying the named profile on top of the file.
+    ///
+    /// When no profile is requested, the `ALERTING_PROFILE` environment
+    /// variable selects the profile, if set.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Config::from_env_and_file`], plus when the requested profile
+    /// is not defined in the configuration file or the `.env` file is invalid.
+    pub fn load(path: impl AsRef<Path>, options: LoadOptions<'_>) -> Result<Self> {
+        if options.dotenv {
+            env::load_dotenv(path.as_ref()).map_err(AlertError::from)?;
+        }
+        let profile = match options.profile {
+            Some(name) => Some(name.to_string()),
+            None => env::env_string("ALERTING_PROFILE").map_err(AlertError::from)?,
+        };
+        let mut raw =
+            raw::load(path, profile.as_deref(), options.format).map_err(AlertError::from)?;
         raw.apply_env_overrides().map_err(AlertError::from)?;
-        raw.validate_and_build()
+        if raw.zabbix.token.is_none() {
+            raw.zabbix.token = options.token.map(|token| token.expose_secret().to_string());
+        }
+        raw.validate_and_build(options.offline)
+    }
+}
+
+impl ConfigFormat {
+    const fn file_format(self) -> ::config::FileFormat {
+        match self {
+            Self::Toml => ::config::FileFormat::Toml,
+            Self::Yaml => ::config::FileFormat::Yaml,
+            Self::Json => ::config::FileFormat::Json,
+        }
+    }
+}
+
+impl FromStr for ConfigFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "toml" => Ok(Self::Toml),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            "json" => Ok(Self::Json),
+            other => Err(format!("unknown configuration format: {other}")),
+        }
+    }
+}
+
+impl FromStr for ToastGrouping {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "host" => Ok(Self::Host),
+            "severity" => Ok(Self::Severity),
+            other => Err(format!("unknown toast grouping: {other}")),
+        }
+    }
+}
+
+impl FromStr for PollSummary {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "off" => Ok(Self::Off),
+            "also" => Ok(Self::Also),
+            "only" => Ok(Self::Only),
+            other => Err(format!("unknown poll summary mode: {other}")),
+        }
+    }
+}
+
+impl FromStr for AckPrompt {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            "critical_only" => Ok(Self::CriticalOnly),
+            other => Err(format!("unknown ack prompt mode: {other}")),
+        }
+    }
+}
+
+impl FromStr for HeartbeatBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "toast" => Ok(Self::Toast),
+            "log" => Ok(Self::Log),
+            other => Err(format!("unknown heartbeat backend: {other}")),
+        }
+    }
+}
+
+impl Heartbeat {
+    /// First heartbeat strictly after `now`.
+    #[must_use]
+    pub fn next_after(&self, now: NaiveDateTime) -> NaiveDateTime {
+        now.date()
+            .iter_days()
+            .take(8)
+            .map(|day| day.and_time(self.at))
+            .find(|at| *at > now && self.weekday.is_none_or(|weekday| at.weekday() == weekday))
+            .unwrap_or(now)
     }
 }
 
@@ -77,11 +570,77 @@ impl RateLimit {
     }
 }
 
+impl UrgencySchedule {
+    /// Whether `now` falls outside the configured working hours.
+    #[must_use]
+    pub fn is_off_hours(&self, now: NaiveDateTime) -> bool {
+        if self.weekends_off && matches!(now.weekday(), Weekday::Sat | Weekday::Sun) {
+            return true;
+        }
+        let time = now.time();
+        let working = if self.work_start <= self.work_end {
+            time >= self.work_start && time < self.work_end
+        } else {
+            time >= self.work_start || time < self.work_end
+        };
+        !working
+    }
+
+    /// Urgency override for `severity` at `now`, with whether it should stick.
+    #[must_use]
+    pub fn urgency_at(&self, severity: Severity, now: NaiveDateTime) -> Option<(Urgency, bool)> {
+        if self.is_off_hours(now) {
+            self.off_hours.get(&severity).map(|urgency| {
+                let sticky = self.off_hours_sticky && *urgency == Urgency::Critical;
+                (*urgency, sticky)
+            })
+        } else {
+            self.work_hours
+                .get(&severity)
+                .map(|urgency| (*urgency, false))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::RateLimit;
+    use super::{
+        AckPrompt, Config, ConfigFormat, DisplaySettings, Heartbeat, HeartbeatBackend, LoadOptions,
+        RateLimit, UrgencySchedule,
+    };
+    use crate::error::{ConfigError, Error};
+    use crate::types::{EventObject, EventSource, Severity, Urgency};
+    use chrono::{NaiveDate, NaiveTime, Weekday};
+    use secrecy::ExposeSecret;
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+    use std::str::FromStr;
     use std::time::Duration;
 
+    /// Config file in the temp directory, removed on drop so a failed
+    /// assertion does not leave it behind.
+    struct TempFile(PathBuf);
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    /// Load `contents` from a `.{ext}` file with `options`.
+    fn load_with(ext: &str, contents: &str, options: LoadOptions<'_>) -> crate::Result<Config> {
+        let file =
+            TempFile(std::env::temp_dir().join(format!("alerting-{}.{ext}", uuid::Uuid::new_v4())));
+        std::fs::write(&file.0, contents)
+            .map_err(|err| Error::Config(ConfigError::Other(err.to_string())))?;
+        Config::load(&file.0, options)
+    }
+
+    /// Load `contents` from a `.{ext}` file with the default options.
+    fn load_str(ext: &str, contents: &str) -> crate::Result<Config> {
+        load_with(ext, contents, LoadOptions::default())
+    }
+
     #[test]
     fn allows_first_candidate_even_when_full() {
         let bucket = RateLimit {
@@ -91,4 +650,337 @@ mod tests {
         assert!(bucket.allows(0, 0));
         assert!(!bucket.allows(1, 1));
     }
+
+    #[test]
+    fn schedule_escalates_outside_working_hours() -> Result<(), Box<dyn std::error::Error>> {
+        let schedule = UrgencySchedule {
+            work_start: NaiveTime::from_hms_opt(8, 0, 0).ok_or("bad time")?,
+            work_end: NaiveTime::from_hms_opt(19, 0, 0).ok_or("bad time")?,
+            weekends_off: true,
+            off_hours_sticky: true,
+            work_hours: BTreeMap::from([(Severity::High, Urgency::Normal)]),
+            off_hours: BTreeMap::from([(Severity::High, Urgency::Critical)]),
+        };
+        // 2024-01-10 is a Wednesday, 2024-01-13 a Saturday.
+        let day = NaiveDate::from_ymd_opt(2024, 1, 10).ok_or("bad date")?;
+        let saturday = NaiveDate::from_ymd_opt(2024, 1, 13).ok_or("bad date")?;
+        let noon = NaiveTime::from_hms_opt(12, 0, 0).ok_or("bad time")?;
+        let night = NaiveTime::from_hms_opt(23, 30, 0).ok_or("bad time")?;
+
+        assert_eq!(
+            schedule.urgency_at(Severity::High, day.and_time(noon)),
+            Some((Urgency::Normal, false))
+        );
+        assert_eq!(
+            schedule.urgency_at(Severity::High, day.and_time(night)),
+            Some((Urgency::Critical, true))
+        );
+        assert_eq!(
+            schedule.urgency_at(Severity::High, saturday.and_time(noon)),
+            Some((Urgency::Critical, true))
+        );
+        assert_eq!(
+            schedule.urgency_at(Severity::Info, day.and_time(night)),
+            None
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn heartbeat_runs_daily_or_on_its_weekday() -> Result<(), Box<dyn std::error::Error>> {
+        let nine = NaiveTime::from_hms_opt(9, 0, 0).ok_or("bad time")?;
+        let mut heartbeat = Heartbeat {
+            weekday: None,
+            at: nine,
+            backend: HeartbeatBackend::Toast,
+        };
+        // 2024-01-10 is a Wednesday.
+        let wednesday = NaiveDate::from_ymd_opt(2024, 1, 10).ok_or("bad date")?;
+        let thursday = NaiveDate::from_ymd_opt(2024, 1, 11).ok_or("bad date")?;
+        let monday = NaiveDate::from_ymd_opt(2024, 1, 15).ok_or("bad date")?;
+        let early = NaiveTime::from_hms_opt(8, 0, 0).ok_or("bad time")?;
+
+        assert_eq!(
+            heartbeat.next_after(wednesday.and_time(early)),
+            wednesday.and_time(nine)
+        );
+        assert_eq!(
+            heartbeat.next_after(wednesday.and_time(nine)),
+            thursday.and_time(nine)
+        );
+        heartbeat.weekday = Some(Weekday::Mon);
+        assert_eq!(
+            heartbeat.next_after(wednesday.and_time(early)),
+            monday.and_time(nine)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn profile_overrides_top_level_settings() -> Result<(), Box<dyn std::error::Error>> {
+        let contents = r#"
+[zabbix]
+url = "https://work.example.com/api_jsonrpc.php"
+token = "work-token"
+limit = 10
+
+[profiles.home.zabbix]
+url = "https://home.example.com/api_jsonrpc.php"
+"#;
+        let profile = |name| LoadOptions {
+            profile: Some(name),
+            ..LoadOptions::default()
+        };
+
+        let base = load_str("toml", contents)?;
+        assert_eq!(base.base_url.host_str(), Some("work.example.com"));
+        let home = load_with("toml", contents, profile("home"))?;
+        assert_eq!(home.base_url.host_str(), Some("home.example.com"));
+        assert_eq!(home.limit, 10);
+        assert!(load_with("toml", contents, profile("missing")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn yaml_config_uses_explicit_format() -> Result<(), Box<dyn std::error::Error>> {
+        let options = LoadOptions {
+            format: Some(ConfigFormat::Yaml),
+            ..LoadOptions::default()
+        };
+        let config = load_with(
+            "conf",
+            "zabbix:\n  url: https://yaml.example.com/api_jsonrpc.php\n  token: t\n  limit: 7\n",
+            options,
+        )?;
+
+        assert_eq!(config.base_url.host_str(), Some("yaml.example.com"));
+        assert_eq!(config.limit, 7);
+        Ok(())
+    }
+
+    #[test]
+    fn fallback_token_fills_in_a_missing_one() -> Result<(), Box<dyn std::error::Error>> {
+        let contents = "[zabbix]\nurl = \"https://zbx.example.com\"\n";
+        let stored = secrecy::SecretString::from("from-keyring");
+        let options = LoadOptions {
+            token: Some(&stored),
+            ..LoadOptions::default()
+        };
+
+        assert!(matches!(
+            load_str("toml", contents),
+            Err(Error::Config(ConfigError::MissingField {
+                field: "zabbix.token"
+            }))
+        ));
+        let config = load_with("toml", contents, options)?;
+        assert_eq!(config.token.expose_secret(), "from-keyring");
+        Ok(())
+    }
+
+    #[test]
+    fn offline_load_needs_no_credentials() -> Result<(), Box<dyn std::error::Error>> {
+        let contents = "[zabbix]\nlimit = 5\n";
+        let options = LoadOptions {
+            offline: true,
+            ..LoadOptions::default()
+        };
+
+        assert!(load_str("toml", contents).is_err());
+        let config = load_with("toml", contents, options)?;
+        assert_eq!(config.base_url.host_str(), Some("zabbix.invalid"));
+        assert_eq!(config.limit, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn serialized_config_masks_token() -> Result<(), Box<dyn std::error::Error>> {
+        let config = load_str(
+            "toml",
+            "[zabbix]\nurl = \"https://zbx.example.com\"\ntoken = \"s3cr3t\"\ngraph_user = \"viewer\"\ngraph_password = \"pa55\"\n",
+        )?;
+
+        let rendered = serde_json::to_string(&config)?;
+        assert!(rendered.contains("<redacted>"));
+        assert!(!rendered.contains("s3cr3t"));
+        assert!(rendered.contains("viewer"));
+        assert!(!rendered.contains("pa55"));
+        Ok(())
+    }
+
+    #[test]
+    fn platform_appname_overrides_and_aumid_is_validated() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let load = |notify: &str| {
+            load_str(
+                "toml",
+                &format!(
+                    "[zabbix]\nurl = \"https://zbx.example.com\"\ntoken = \"t\"\n[notify]\n{notify}"
+                ),
+            )
+        };
+
+        let config = load(
+            "appname = \"Alerting\"\nappname_linux = \"alerting-linux\"\nappname_windows = \"Example.Alerting\"\n",
+        )?;
+        let expected = if cfg!(target_os = "linux") {
+            "alerting-linux"
+        } else if cfg!(target_os = "windows") {
+            "Example.Alerting"
+        } else {
+            "Alerting"
+        };
+        assert_eq!(config.notify.appname, expected);
+        assert!(load("appname_windows = \"Check Agent\"\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn severity_styles_override_builtin_mapping() -> Result<(), Box<dyn std::error::Error>> {
+        let config = load_str(
+            "toml",
+            r##"
+[zabbix]
+url = "https://zbx.example.com"
+token = "t"
+
+[notify.severities.average]
+urgency = "critical"
+sticky = true
+sound = false
+color = "#FFA059"
+"##,
+        )?;
+
+        let notify = config.notify;
+        let average = notify.style_for(Severity::Average);
+        assert_eq!(average.urgency, Some(Urgency::Critical));
+        assert!(average.sticky);
+        assert_eq!(average.sound, Some(false));
+        assert_eq!(
+            average.color.map(|c| c.to_string()).as_deref(),
+            Some("#ffa059")
+        );
+        assert_eq!(notify.style_for(Severity::Info).urgency, None);
+        Ok(())
+    }
+
+    #[test]
+    fn sound_file_must_be_wav_or_ogg() -> Result<(), Box<dyn std::error::Error>> {
+        let load = |sound_file: &str| {
+            load_str(
+                "toml",
+                &format!(
+                    "[zabbix]\nurl = \"https://zbx.example.com\"\ntoken = \"t\"\n[notify]\nsound_file = \"{sound_file}\"\n"
+                ),
+            )
+        };
+
+        let config = load("/usr/share/sounds/alarm.OGG")?;
+        assert_eq!(
+            config.notify.sound_file.as_deref(),
+            Some(std::path::Path::new("/usr/share/sounds/alarm.OGG"))
+        );
+        assert!(load("/usr/share/sounds/alarm.mp3").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn display_timezone_renders_times() -> Result<(), Box<dyn std::error::Error>> {
+        let load = |display: &str| {
+            load_str(
+                "toml",
+                &format!(
+                    "[zabbix]\nurl = \"https://zbx.example.com\"\ntoken = \"t\"\n[display]\n{display}\n"
+                ),
+            )
+        };
+
+        let display = load("timezone = \"America/New_York\"")?.display;
+        assert_eq!(
+            display.format(1_700_000_000, "%Y-%m-%d %H:%M"),
+            "2023-11-14 17:13"
+        );
+        assert_eq!(display.rfc3339(1_700_000_000), "2023-11-14T17:13:20-05:00");
+        assert!(load("timezone = \"\"")?.display.timezone.is_none());
+        assert!(load("timezone = \"Mars/Olympus_Mons\"").is_err());
+        assert!(load("datetime_format = \"%Y-%m-%d %Q\"").is_err());
+        let relative = DisplaySettings {
+            relative_time: true,
+            ..display.clone()
+        };
+        assert_eq!(
+            relative.when(1_700_000_000, 1_700_000_300, "%H:%M"),
+            "5 min ago (17:13)"
+        );
+        assert_eq!(
+            relative.when(1_700_000_000, 1_699_992_000, "%H:%M"),
+            "in 2 h (17:13)"
+        );
+        assert_eq!(display.when(1_700_000_000, 1_700_000_030, "%H:%M"), "17:13");
+        let custom = DisplaySettings {
+            datetime_format: Some("%d/%m/%Y %Hh%M".to_string()),
+            ..display
+        };
+        assert_eq!(custom.format(1_700_000_000, "%H:%M"), "14/11/2023 17h13");
+        Ok(())
+    }
+
+    #[test]
+    fn open_url_fmt_is_checked_at_load() -> Result<(), Box<dyn std::error::Error>> {
+        let load = |fmt: &str| {
+            load_str(
+                "toml",
+                &format!(
+                    "[zabbix]\nurl = \"https://zbx.example.com\"\ntoken = \"t\"\n[app]\nopen_url_fmt = \"{fmt}\"\n"
+                ),
+            )
+        };
+
+        let fmt = "https://zbx.example.com/zabbix.php?action=problem.view&filter_eventid={eventid}";
+        assert_eq!(load(fmt)?.open_url_fmt.as_deref(), Some(fmt));
+        assert!(load("https://zbx.example.com/zabbix.php?action=problem.view").is_err());
+        assert!(load("zbx.example.com/tr_events.php?eventid={eventid}").is_err());
+        assert!(load("mailto:{eventid}@example.com").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn ack_prompt_follows_urgency() {
+        assert_eq!(
+            AckPrompt::from_str("critical_only"),
+            Ok(AckPrompt::CriticalOnly)
+        );
+        assert!(AckPrompt::from_str("sometimes").is_err());
+        assert!(AckPrompt::CriticalOnly.asks(Urgency::Critical));
+        assert!(!AckPrompt::CriticalOnly.asks(Urgency::Normal));
+        assert!(AckPrompt::Always.asks(Urgency::Low));
+        assert!(!AckPrompt::Never.asks(Urgency::Critical));
+    }
+
+    #[test]
+    fn event_filter_needs_a_matching_object() -> Result<(), Box<dyn std::error::Error>> {
+        let load = |filter: &str| {
+            load_str(
+                "toml",
+                &format!("[zabbix]\nurl = \"https://zbx.example.com\"\ntoken = \"t\"\n{filter}\n"),
+            )
+        };
+
+        assert_eq!(load("")?.event_sources, [EventSource::Trigger]);
+        let config = load(
+            "event_sources = [\"trigger\", \"internal\"]\nevent_objects = [\"trigger\", \"lld_rule\"]",
+        )?;
+        assert_eq!(
+            config.event_sources,
+            [EventSource::Trigger, EventSource::Internal]
+        );
+        assert_eq!(
+            config.event_objects,
+            [EventObject::Trigger, EventObject::LldRule]
+        );
+        assert!(load("event_sources = [\"trigger\"]\nevent_objects = [\"item\"]").is_err());
+        assert!(load("event_sources = [\"discovery\"]").is_err());
+        Ok(())
+    }
 }
@@ -1,35 +1,74 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::time::Duration;
 
-use serde::Deserialize;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{NaiveTime, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use url::Url;
 
 use crate::Result;
 use crate::error::ConfigError;
-use crate::types::AckFilter;
+use crate::paths;
+use crate::types::{
+    AckFilter, Color, DetailLevel, EventObject, EventSource, Severity, SymptomMode, Urgency,
+    event_kinds,
+};
 
 use super::defaults::{
-    default_ack_filter, default_concurrency, default_dedup_cache_size, default_limit,
-    default_max_notif, default_notify_appname, default_open_label, default_poll_interval,
+    default_ack_filter, default_ack_min_severity, default_ack_prompt, default_concurrency,
+    default_confirm_command, default_connect_timeout, default_dedup_cache_size,
+    default_desktop_entry, default_event_sources, default_graph_period, default_group_by,
+    default_heartbeat_at, default_heartbeat_backend, default_heartbeat_every,
+    default_heartbeat_weekday, default_history_max_age, default_host_miss_ttl,
+    default_http_timeout, default_kdeconnect_min_severity, default_limit, default_max_notif,
+    default_max_pages, default_mute_floor, default_notify_appname, default_open_label,
+    default_poll_interval, default_poll_summary, default_pool_idle_timeout, default_prompt_command,
     default_queue_bound, default_rate_limit_max, default_rate_limit_window,
+    default_service_cache_ttl, default_storm_window, default_true, default_work_end,
+    default_work_start,
 };
-use super::env::{env_bool, env_duration, env_parse, env_string};
+use super::env::{env_bool, env_command, env_duration, env_parse, env_string};
+use super::schema::SchemaEnv;
 use super::{
-    Config, DEFAULT_CONNECT_TIMEOUT, DEFAULT_HTTP_TIMEOUT, HumantimeDuration, MAX_NOTIF_BOUNDS,
-    NotifySettings, RateLimit,
+    AckPrompt, Config, ConfigFormat, DisplaySettings, GraphImage, Heartbeat, HeartbeatBackend,
+    HistoryRetention, HumantimeDuration, KdeConnect, MAX_NOTIF_BOUNDS, NotifySettings, PollSummary,
+    RateLimit, SeverityStyle, StormMode, ToastGrouping, UrgencySchedule,
 };
 
-pub(super) fn load(path: impl AsRef<Path>) -> std::result::Result<RawConfig, ConfigError> {
-    let mut builder = ::config::Config::builder();
+/// Stand-ins for `zabbix.url` and `zabbix.token` when loading offline.
+const OFFLINE_URL: &str = "https://zabbix.invalid/api_jsonrpc.php";
+const OFFLINE_TOKEN: &str = "offline";
+
+pub(super) fn load(
+    path: impl AsRef<Path>,
+    profile: Option<&str>,
+    format: Option<ConfigFormat>,
+) -> std::result::Result<RawConfig, ConfigError> {
     let path = path.as_ref();
-    builder = builder.add_source(::config::File::from(path).required(false));
-    builder = builder.add_source(
-        ::config::Environment::with_prefix("ALERTING")
-            .separator("__")
-            .try_parsing(true),
-    );
+    let mut source = ::config::File::from(path).required(false);
+    if let Some(format) = format {
+        source = source.format(format.file_format());
+    }
+    let file = ::config::Config::builder()
+        .add_source(source)
+        .build()
+        .map_err(|err| ConfigError::Other(err.to_string()))?;
+
+    let mut builder = ::config::Config::builder().add_source(file.clone());
+    if let Some(name) = profile {
+        let table =
+            file.get_table(&format!("profiles.{name}"))
+                .map_err(|_| ConfigError::InvalidField {
+                    field: "profile",
+                    message: format!("profile {name:?} is not defined in {}", path.display()),
+                })?;
+        builder = builder.add_source(ProfileOverlay(table));
+    }
+    builder = builder.add_source(SchemaEnv::new());
 
     builder
         .build()
@@ -38,8 +77,24 @@ pub(super) fn load(path: impl AsRef<Path>) -> std::result::Result<RawConfig, Con
         .map_err(|err| ConfigError::Parse(err.to_string()))
 }
 
+/// `[profiles.<name>]` table merged over the top-level settings.
+#[derive(Clone, Debug)]
+struct ProfileOverlay(::config::Map<String, ::config::Value>);
+
+impl ::config::Source for ProfileOverlay {
+    fn clone_into_box(&self) -> Box<dyn ::config::Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(
+        &self,
+    ) -> std::result::Result<::config::Map<String, ::config::Value>, ::config::ConfigError> {
+        Ok(self.0.clone())
+    }
+}
+
 #[serde_as]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub(super) struct RawConfig {
     #[serde(default)]
     pub(super) zabbix: RawZabbix,
@@ -47,27 +102,95 @@ pub(super) struct RawConfig {
     pub(super) notify: RawNotify,
     #[serde(default)]
     pub(super) app: RawApp,
+    #[serde(default)]
+    pub(super) display: RawDisplay,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(super) struct RawDisplay {
+    #[serde(default)]
+    pub(super) timezone: Option<String>,
+    #[serde(default)]
+    pub(super) relative_time: bool,
+    #[serde(default)]
+    pub(super) datetime_format: Option<String>,
 }
 
 #[serde_as]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub(super) struct RawZabbix {
     pub(super) url: Option<String>,
+    #[serde(default)]
+    pub(super) fallback_urls: Vec<String>,
     pub(super) token: Option<String>,
     #[serde(default = "default_limit")]
     pub(super) limit: u32,
+    #[serde(default = "default_max_pages")]
+    pub(super) max_pages: u32,
     #[serde(default = "default_concurrency")]
     pub(super) concurrency: usize,
     #[serde(default)]
     pub(super) ack_filter: Option<String>,
+    #[serde(default)]
+    pub(super) proxy_ids: Vec<String>,
+    #[serde(default)]
+    pub(super) host_availability: bool,
+    #[serde(default)]
+    pub(super) symptoms: Option<String>,
+    #[serde(default = "default_event_sources")]
+    pub(super) event_sources: Vec<String>,
+    #[serde(default)]
+    pub(super) event_objects: Vec<String>,
+    #[serde(default)]
+    pub(super) service_impact: bool,
+    #[serde(default = "default_service_cache_ttl")]
+    #[serde_as(as = "HumantimeDuration")]
+    pub(super) service_cache_ttl: Duration,
+    #[serde(default)]
+    pub(super) graph_user: Option<String>,
+    #[serde(default)]
+    pub(super) graph_password: Option<String>,
+    #[serde(default = "default_graph_period")]
+    #[serde_as(as = "HumantimeDuration")]
+    pub(super) graph_period: Duration,
+    /// Whole JSON-RPC request, response body included.
+    #[serde(default = "default_http_timeout")]
+    #[serde_as(as = "HumantimeDuration")]
+    pub(super) http_timeout: Duration,
+    #[serde(default = "default_connect_timeout")]
+    #[serde_as(as = "HumantimeDuration")]
+    pub(super) connect_timeout: Duration,
+    #[serde(default = "default_pool_idle_timeout")]
+    #[serde_as(as = "HumantimeDuration")]
+    pub(super) pool_idle_timeout: Duration,
+    #[serde(default)]
+    pub(super) pool_max_idle_per_host: Option<usize>,
+    #[serde(default)]
+    #[serde_as(as = "Option<HumantimeDuration>")]
+    pub(super) tcp_keepalive: Option<Duration>,
+    #[serde(default)]
+    pub(super) max_requests_per_second: Option<u32>,
+    #[serde(default)]
+    pub(super) max_in_flight: Option<usize>,
+    /// `0s` looks up the host of every event on every poll.
+    #[serde(default = "default_host_miss_ttl")]
+    #[serde_as(as = "HumantimeDuration")]
+    pub(super) host_miss_ttl: Duration,
+    #[serde(default)]
+    pub(super) log_rpc: bool,
 }
 
+#[allow(clippy::struct_excessive_bools)]
 #[serde_as]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub(super) struct RawNotify {
     #[serde(default = "default_notify_appname")]
     pub(super) appname: String,
     #[serde(default)]
+    pub(super) appname_linux: Option<String>,
+    #[serde(default)]
+    pub(super) appname_windows: Option<String>,
+    #[serde(default)]
     pub(super) sticky: bool,
     #[serde(default)]
     #[serde_as(as = "Option<HumantimeDuration>")]
@@ -75,15 +198,127 @@ pub(super) struct RawNotify {
     #[serde(default)]
     pub(super) default_timeout: bool,
     #[serde(default)]
+    pub(super) resident: bool,
+    #[serde(default = "default_true")]
+    pub(super) close_on_ack: bool,
+    #[serde(default)]
+    pub(super) comment_updates: bool,
+    #[serde(default)]
     pub(super) icon: Option<PathBuf>,
+    #[serde(default)]
+    pub(super) acked_icon: Option<PathBuf>,
+    #[serde(default)]
+    pub(super) sound_file: Option<PathBuf>,
     #[serde(default = "default_open_label")]
     pub(super) open_label: String,
     #[serde(default)]
     pub(super) notify_acked: bool,
+    #[serde(default)]
+    pub(super) schedule: Option<RawSchedule>,
+    #[serde(default)]
+    pub(super) hero_image: Option<PathBuf>,
+    #[serde(default)]
+    pub(super) attribution: Option<String>,
+    #[serde(default = "default_group_by")]
+    pub(super) group_by: String,
+    #[serde(default)]
+    pub(super) batch_by_host: bool,
+    #[serde(default = "default_poll_summary")]
+    pub(super) poll_summary: String,
+    #[serde(default)]
+    pub(super) severity_emoji: bool,
+    #[serde(default = "default_desktop_entry")]
+    pub(super) desktop_entry: String,
+    #[serde(default = "default_prompt_command")]
+    pub(super) prompt_command: Vec<String>,
+    #[serde(default = "default_ack_prompt")]
+    pub(super) ack_prompt: String,
+    #[serde(default)]
+    pub(super) confirm_ack: bool,
+    #[serde(default = "default_confirm_command")]
+    pub(super) confirm_command: Vec<String>,
+    #[serde(default)]
+    pub(super) rank_action: bool,
+    #[serde(default)]
+    pub(super) comment_action: bool,
+    #[serde(default)]
+    pub(super) host_ack_action: bool,
+    #[serde(default = "default_ack_min_severity")]
+    pub(super) ack_min_severity: String,
+    #[serde(default = "default_mute_floor")]
+    pub(super) mute_floor: String,
+    #[serde(default)]
+    #[serde_as(as = "Option<HumantimeDuration>")]
+    pub(super) mute_action: Option<Duration>,
+    #[serde(default)]
+    pub(super) max_body_lines: Option<usize>,
+    #[serde(default)]
+    pub(super) max_body_chars: Option<usize>,
+    #[serde(default)]
+    pub(super) summary_prefix: String,
+    #[serde(default)]
+    pub(super) summary_suffix: String,
+    #[serde(default)]
+    pub(super) severities: BTreeMap<String, RawSeverityStyle>,
+    #[serde(default)]
+    pub(super) detail: Option<String>,
+    #[serde(default)]
+    pub(super) heartbeat: Option<RawHeartbeat>,
+    #[serde(default)]
+    pub(super) kdeconnect: Option<RawKdeConnect>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(super) struct RawKdeConnect {
+    pub(super) device: String,
+    #[serde(default = "default_kdeconnect_min_severity")]
+    pub(super) min_severity: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(super) struct RawHeartbeat {
+    /// `daily` or `weekly`.
+    #[serde(default = "default_heartbeat_every")]
+    pub(super) every: String,
+    /// Day of a weekly heartbeat.
+    #[serde(default = "default_heartbeat_weekday")]
+    pub(super) weekday: String,
+    #[serde(default = "default_heartbeat_at")]
+    pub(super) at: String,
+    #[serde(default = "default_heartbeat_backend")]
+    pub(super) backend: String,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(super) struct RawSeverityStyle {
+    #[serde(default)]
+    pub(super) urgency: Option<String>,
+    #[serde(default)]
+    pub(super) sticky: bool,
+    #[serde(default)]
+    pub(super) sound: Option<bool>,
+    #[serde(default)]
+    pub(super) color: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(super) struct RawSchedule {
+    #[serde(default = "default_work_start")]
+    pub(super) work_start: String,
+    #[serde(default = "default_work_end")]
+    pub(super) work_end: String,
+    #[serde(default = "default_true")]
+    pub(super) weekends_off: bool,
+    #[serde(default = "default_true")]
+    pub(super) off_hours_sticky: bool,
+    #[serde(default)]
+    pub(super) work_hours: BTreeMap<String, String>,
+    #[serde(default)]
+    pub(super) off_hours: BTreeMap<String, String>,
 }
 
 #[serde_as]
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub(super) struct RawApp {
     #[serde(default = "default_max_notif")]
     pub(super) max_notif: usize,
@@ -91,35 +326,46 @@ pub(super) struct RawApp {
     pub(super) queue_bound: usize,
     #[serde(default = "default_dedup_cache_size")]
     pub(super) dedup_cache_size: usize,
+    #[serde(default)]
+    #[serde_as(as = "Option<HumantimeDuration>")]
+    pub(super) notify_cooldown: Option<Duration>,
     #[serde(default = "default_rate_limit_max")]
     pub(super) rate_limit_max: usize,
     #[serde(default = "default_rate_limit_window")]
     #[serde_as(as = "HumantimeDuration")]
     pub(super) rate_limit_window: Duration,
+    #[serde(default)]
+    pub(super) storm_threshold: Option<usize>,
+    #[serde(default = "default_storm_window")]
+    #[serde_as(as = "HumantimeDuration")]
+    pub(super) storm_window: Duration,
     #[serde(default = "default_poll_interval")]
     #[serde_as(as = "HumantimeDuration")]
     pub(super) poll_interval: Duration,
     #[serde(default)]
     pub(super) open_url_fmt: Option<String>,
+    #[serde(default)]
+    pub(super) host_url_fmt: Option<String>,
+    #[serde(default)]
+    pub(super) state_file: Option<PathBuf>,
+    #[serde(default)]
+    pub(super) history_file: Option<PathBuf>,
+    #[serde(default)]
+    pub(super) status_file: Option<PathBuf>,
+    /// `0s` keeps entries of any age.
+    #[serde(default = "default_history_max_age")]
+    #[serde_as(as = "HumantimeDuration")]
+    pub(super) history_max_age: Duration,
+    #[serde(default)]
+    pub(super) history_max_rows: Option<usize>,
+    #[serde(default)]
+    #[serde_as(as = "Option<HumantimeDuration>")]
+    pub(super) watchdog_deadline: Option<Duration>,
 }
 
 impl RawConfig {
     pub(super) fn apply_env_overrides(&mut self) -> std::result::Result<(), ConfigError> {
-        if let Some(url) = env_string("ZBX_URL")? {
-            self.zabbix.url = Some(url);
-        }
-        if let Some(token) = env_string("ZBX_TOKEN")? {
-            self.zabbix.token = Some(token);
-        }
-        if let Some(limit) = env_parse::<u32>("LIMIT")? {
-            self.zabbix.limit = limit;
-        }
-        if let Some(concurrency) = env_parse::<usize>("CONCURRENCY")? {
-            self.zabbix.concurrency = concurrency;
-        }
-        if let Some(filter) = env_string("ACK_FILTER")? {
-            self.zabbix.ack_filter = Some(filter);
-        }
+        self.zabbix.apply_env_overrides()?;
         if let Some(max_notif) = env_parse::<usize>("MAX_NOTIF")? {
             self.app.max_notif = max_notif;
         }
@@ -129,43 +375,75 @@ impl RawConfig {
         if let Some(dedup) = env_parse::<usize>("DEDUPE_CACHE_SIZE")? {
             self.app.dedup_cache_size = dedup;
         }
+        if let Some(cooldown) = env_duration("NOTIFY_COOLDOWN")? {
+            self.app.notify_cooldown = Some(cooldown);
+        }
         if let Some(rate_max) = env_parse::<usize>("RATE_LIMIT_MAX")? {
             self.app.rate_limit_max = rate_max;
         }
         if let Some(rate_window) = env_duration("RATE_LIMIT_WINDOW")? {
             self.app.rate_limit_window = rate_window;
         }
+        if let Some(threshold) = env_parse::<usize>("STORM_THRESHOLD")? {
+            self.app.storm_threshold = Some(threshold);
+        }
+        if let Some(window) = env_duration("STORM_WINDOW")? {
+            self.app.storm_window = window;
+        }
         if let Some(interval) = env_duration("POLL_INTERVAL")? {
             self.app.poll_interval = interval;
         }
         if let Some(fmt) = env_string("ZBX_OPEN_URL_FMT")? {
             self.app.open_url_fmt = Some(fmt);
         }
-        if let Some(appname) = env_string("NOTIFY_APPNAME")? {
-            self.notify.appname = appname;
+        if let Some(fmt) = env_string("ZBX_HOST_URL_FMT")? {
+            self.app.host_url_fmt = Some(fmt);
         }
-        if let Some(sticky) = env_bool("NOTIFY_STICKY")? {
-            self.notify.sticky = sticky;
+        if let Some(path) = env_string("STATE_FILE")? {
+            // Kept even when empty: that disables the default state file.
+            self.app.state_file = Some(PathBuf::from(path));
         }
-        if let Some(timeout) = env_duration("NOTIFY_TIMEOUT")? {
-            self.notify.timeout = Some(timeout);
+        if let Some(path) = env_string("HISTORY_FILE")? {
+            self.app.history_file = Some(PathBuf::from(path)).filter(|p| !p.as_os_str().is_empty());
         }
-        if let Some(default_timeout) = env_bool("NOTIFY_TIMEOUT_DEFAULT")? {
-            self.notify.default_timeout = default_timeout;
+        if let Some(path) = env_string("STATUS_FILE")? {
+            self.app.status_file = Some(PathBuf::from(path)).filter(|p| !p.as_os_str().is_empty());
         }
-        if let Some(icon) = env_string("NOTIFY_ICON")? {
-            self.notify.icon = Some(PathBuf::from(icon));
+        if let Some(age) = env_duration("HISTORY_MAX_AGE")? {
+            self.app.history_max_age = age;
         }
-        if let Some(open_label) = env_string("NOTIFY_OPEN_LABEL")? {
-            self.notify.open_label = open_label;
+        if let Some(rows) = env_parse::<usize>("HISTORY_MAX_ROWS")? {
+            self.app.history_max_rows = Some(rows);
         }
-        if let Some(notify_acked) = env_bool("NOTIFY_ACKED")? {
-            self.notify.notify_acked = notify_acked;
+        if let Some(deadline) = env_duration("WATCHDOG_DEADLINE")? {
+            self.app.watchdog_deadline = Some(deadline);
         }
-        Ok(())
+        if let Some(timezone) = env_string("DISPLAY_TIMEZONE")? {
+            self.display.timezone = Some(timezone);
+        }
+        if let Some(relative) = env_bool("DISPLAY_RELATIVE_TIME")? {
+            self.display.relative_time = relative;
+        }
+        if let Some(datetime_format) = env_string("DISPLAY_DATETIME_FORMAT")? {
+            self.display.datetime_format = Some(datetime_format);
+        }
+        self.notify.apply_env_overrides()
     }
 
-    pub(super) fn validate_and_build(self) -> Result<Config> {
+    pub(super) fn validate_and_build(mut self, offline: bool) -> Result<Config> {
+        let graph = self.zabbix.graph_image()?;
+        self.zabbix.validate()?;
+        let symptoms = self.zabbix.symptom_mode()?;
+        let (event_sources, event_objects) = self.zabbix.event_filter()?;
+        let fallback_urls = self.zabbix.fallback_urls()?;
+        if offline {
+            self.zabbix
+                .url
+                .get_or_insert_with(|| OFFLINE_URL.to_string());
+            self.zabbix
+                .token
+                .get_or_insert_with(|| OFFLINE_TOKEN.to_string());
+        }
         let url_str = self.zabbix.url.ok_or(ConfigError::MissingField {
             field: "zabbix.url",
         })?;
@@ -192,92 +470,851 @@ impl RawConfig {
             }
         })?;
 
-        if !MAX_NOTIF_BOUNDS.contains(&self.app.max_notif) {
+        self.app.validate()?;
+        let notify = self.notify.build()?;
+
+        Ok(Config {
+            base_url,
+            fallback_urls,
+            token: token.into(),
+            limit: self.zabbix.limit,
+            max_pages: self.zabbix.max_pages,
+            concurrency: self.zabbix.concurrency.max(1),
+            ack_filter,
+            proxy_ids: self.zabbix.proxy_ids,
+            host_availability: self.zabbix.host_availability,
+            symptoms,
+            event_sources,
+            event_objects,
+            service_impact: self
+                .zabbix
+                .service_impact
+                .then_some(self.zabbix.service_cache_ttl),
+            graph,
+            max_notif: self.app.max_notif,
+            queue_capacity: self.app.queue_bound,
+            dedup_cache_size: self.app.dedup_cache_size,
+            notify_cooldown: self
+                .app
+                .notify_cooldown
+                .filter(|cooldown| !cooldown.is_zero()),
+            rate_limit: RateLimit {
+                max_events: self.app.rate_limit_max,
+                per: self.app.rate_limit_window,
+            },
+            storm: self.app.storm_threshold.map(|threshold| StormMode {
+                threshold,
+                window: self.app.storm_window,
+            }),
+            poll_interval: self.app.poll_interval,
+            open_url_fmt: self.app.open_url_fmt,
+            host_url_fmt: self.app.host_url_fmt,
+            state_file: match self.app.state_file {
+                None => paths::state_dir().map(|dir| dir.join(paths::STATE_FILE)),
+                Some(path) if path.as_os_str().is_empty() => None,
+                Some(path) => Some(paths::in_state_dir(&path)),
+            },
+            history_file: self.app.history_file.as_deref().map(paths::in_state_dir),
+            status_file: self.app.status_file.as_deref().map(paths::in_state_dir),
+            history_retention: HistoryRetention {
+                max_age: Some(self.app.history_max_age).filter(|age| !age.is_zero()),
+                max_rows: self.app.history_max_rows,
+            },
+            watchdog_deadline: self.app.watchdog_deadline,
+            notify,
+            display: self.display.build()?,
+            http_connect_timeout: self.zabbix.connect_timeout,
+            http_request_timeout: self.zabbix.http_timeout,
+            http_pool_idle_timeout: self.zabbix.pool_idle_timeout,
+            http_pool_max_idle_per_host: self.zabbix.pool_max_idle_per_host,
+            http_tcp_keepalive: self.zabbix.tcp_keepalive,
+            max_requests_per_second: self.zabbix.max_requests_per_second,
+            max_in_flight: self.zabbix.max_in_flight,
+            host_miss_ttl: self.zabbix.host_miss_ttl,
+            log_rpc: self.zabbix.log_rpc,
+        })
+    }
+}
+
+impl RawDisplay {
+    fn build(self) -> std::result::Result<DisplaySettings, ConfigError> {
+        let timezone = self
+            .timezone
+            .filter(|name| !name.trim().is_empty())
+            .map(|name| {
+                Tz::from_str(name.trim()).map_err(|_| ConfigError::InvalidField {
+                    field: "display.timezone",
+                    message: format!("unknown timezone {name:?} (IANA name, e.g. Europe/Paris)"),
+                })
+            })
+            .transpose()?;
+        let datetime_format = self
+            .datetime_format
+            .filter(|fmt| !fmt.trim().is_empty())
+            .map(|fmt| {
+                if StrftimeItems::new(&fmt).any(|item| item == Item::Error) {
+                    Err(ConfigError::InvalidField {
+                        field: "display.datetime_format",
+                        message: format!("invalid strftime pattern {fmt:?}"),
+                    })
+                } else {
+                    Ok(fmt)
+                }
+            })
+            .transpose()?;
+        Ok(DisplaySettings {
+            timezone,
+            relative_time: self.relative_time,
+            datetime_format,
+        })
+    }
+}
+
+impl RawZabbix {
+    fn apply_env_overrides(&mut self) -> std::result::Result<(), ConfigError> {
+        if let Some(url) = env_string("ZBX_URL")? {
+            self.url = Some(url);
+        }
+        if let Some(urls) = env_string("ZBX_FALLBACK_URLS")? {
+            self.fallback_urls = urls
+                .split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Some(token) = env_string("ZBX_TOKEN")? {
+            self.token = Some(token);
+        }
+        if let Some(limit) = env_parse::<u32>("LIMIT")? {
+            self.limit = limit;
+        }
+        if let Some(pages) = env_parse::<u32>("ZBX_MAX_PAGES")? {
+            self.max_pages = pages;
+        }
+        if let Some(concurrency) = env_parse::<usize>("CONCURRENCY")? {
+            self.concurrency = concurrency;
+        }
+        if let Some(filter) = env_string("ACK_FILTER")? {
+            self.ack_filter = Some(filter);
+        }
+        if let Some(proxies) = env_string("ZBX_PROXY_IDS")? {
+            self.proxy_ids = proxies
+                .split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Some(enabled) = env_bool("ZBX_HOST_AVAILABILITY")? {
+            self.host_availability = enabled;
+        }
+        if let Some(mode) = env_string("ZBX_SYMPTOMS")? {
+            self.symptoms = Some(mode);
+        }
+        if let Some(sources) = env_string("ZBX_EVENT_SOURCES")? {
+            self.event_sources = sources
+                .split(',')
+                .map(str::trim)
+                .filter(|source| !source.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Some(objects) = env_string("ZBX_EVENT_OBJECTS")? {
+            self.event_objects = objects
+                .split(',')
+                .map(str::trim)
+                .filter(|object| !object.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Some(enabled) = env_bool("ZBX_SERVICE_IMPACT")? {
+            self.service_impact = enabled;
+        }
+        if let Some(user) = env_string("ZBX_GRAPH_USER")? {
+            self.graph_user = Some(user);
+        }
+        if let Some(password) = env_string("ZBX_GRAPH_PASSWORD")? {
+            self.graph_password = Some(password);
+        }
+        if let Some(period) = env_duration("ZBX_GRAPH_PERIOD")? {
+            self.graph_period = period;
+        }
+        if let Some(timeout) = env_duration("ZBX_POOL_IDLE_TIMEOUT")? {
+            self.pool_idle_timeout = timeout;
+        }
+        if let Some(max_idle) = env_parse::<usize>("ZBX_POOL_MAX_IDLE_PER_HOST")? {
+            self.pool_max_idle_per_host = Some(max_idle);
+        }
+        if let Some(interval) = env_duration("ZBX_TCP_KEEPALIVE")? {
+            self.tcp_keepalive = Some(interval);
+        }
+        if let Some(rate) = env_parse::<u32>("ZBX_MAX_REQUESTS_PER_SECOND")? {
+            self.max_requests_per_second = Some(rate);
+        }
+        if let Some(in_flight) = env_parse::<usize>("ZBX_MAX_IN_FLIGHT")? {
+            self.max_in_flight = Some(in_flight);
+        }
+        if let Some(ttl) = env_duration("ZBX_HOST_MISS_TTL")? {
+            self.host_miss_ttl = ttl;
+        }
+        if let Some(log_rpc) = env_bool("ZBX_LOG_RPC")? {
+            self.log_rpc = log_rpc;
+        }
+        Ok(())
+    }
+
+    /// Graph downloads are enabled by naming the frontend user.
+    fn graph_image(&mut self) -> std::result::Result<Option<GraphImage>, ConfigError> {
+        let Some(user) = self
+            .graph_user
+            .take()
+            .filter(|user| !user.trim().is_empty())
+        else {
+            return Ok(None);
+        };
+        let password = self
+            .graph_password
+            .take()
+            .ok_or(ConfigError::MissingField {
+                field: "zabbix.graph_password",
+            })?;
+        if self.graph_period.is_zero() {
+            return Err(ConfigError::InvalidField {
+                field: "zabbix.graph_period",
+                message: "graph period must be greater than zero".to_string(),
+            });
+        }
+        Ok(Some(GraphImage {
+            user,
+            password: password.into(),
+            period: self.graph_period,
+        }))
+    }
+
+    fn fallback_urls(&self) -> std::result::Result<Vec<Url>, ConfigError> {
+        self.fallback_urls
+            .iter()
+            .map(|url| {
+                Url::parse(url).map_err(|err| ConfigError::InvalidField {
+                    field: "zabbix.fallback_urls",
+                    message: format!("{url}: {err}"),
+                })
+            })
+            .collect()
+    }
+
+    fn symptom_mode(&self) -> std::result::Result<SymptomMode, ConfigError> {
+        self.symptoms
+            .as_deref()
+            .map_or(Ok(SymptomMode::Show), SymptomMode::from_str)
+            .map_err(|message| ConfigError::InvalidField {
+                field: "zabbix.symptoms",
+                message,
+            })
+    }
+
+    /// Parsed `event_sources` and `event_objects`, which must leave at least
+    /// one kind of event to ask for.
+    fn event_filter(
+        &self,
+    ) -> std::result::Result<(Vec<EventSource>, Vec<EventObject>), ConfigError> {
+        fn parse<T: FromStr<Err = String>>(
+            field: &'static str,
+            names: &[String],
+        ) -> std::result::Result<Vec<T>, ConfigError> {
+            names
+                .iter()
+                .map(|name| T::from_str(name.trim()))
+                .collect::<std::result::Result<_, _>>()
+                .map_err(|message| ConfigError::InvalidField { field, message })
+        }
+        let sources = parse("zabbix.event_sources", &self.event_sources)?;
+        let objects = parse("zabbix.event_objects", &self.event_objects)?;
+        if event_kinds(&sources, &objects).is_empty() {
+            return Err(ConfigError::InvalidField {
+                field: "zabbix.event_objects",
+                message: "no listed object belongs to zabbix.event_sources".to_string(),
+            });
+        }
+        Ok((sources, objects))
+    }
+
+    fn validate(&self) -> std::result::Result<(), ConfigError> {
+        for (field, timeout) in [
+            ("zabbix.http_timeout", self.http_timeout),
+            ("zabbix.connect_timeout", self.connect_timeout),
+        ] {
+            if timeout.is_zero() {
+                return Err(ConfigError::InvalidField {
+                    field,
+                    message: "timeout must be greater than zero".to_string(),
+                });
+            }
+        }
+        if self.pool_idle_timeout.is_zero() {
+            return Err(ConfigError::InvalidField {
+                field: "zabbix.pool_idle_timeout",
+                message: "idle timeout must be greater than zero".to_string(),
+            });
+        }
+        if self
+            .tcp_keepalive
+            .is_some_and(|interval| interval.is_zero())
+        {
+            return Err(ConfigError::InvalidField {
+                field: "zabbix.tcp_keepalive",
+                message: "keepalive interval must be greater than zero".to_string(),
+            });
+        }
+        if self.max_pages == 0 {
+            return Err(ConfigError::InvalidField {
+                field: "zabbix.max_pages",
+                message: "at least one page must be fetched".to_string(),
+            });
+        }
+        if self.max_requests_per_second == Some(0) {
+            return Err(ConfigError::InvalidField {
+                field: "zabbix.max_requests_per_second",
+                message: "request rate must be greater than zero".to_string(),
+            });
+        }
+        if self.max_in_flight == Some(0) {
+            return Err(ConfigError::InvalidField {
+                field: "zabbix.max_in_flight",
+                message: "at least one request must be allowed in flight".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl RawNotify {
+    fn apply_env_overrides(&mut self) -> std::result::Result<(), ConfigError> {
+        if let Some(appname) = env_string("NOTIFY_APPNAME")? {
+            self.appname = appname;
+        }
+        if let Some(appname) = env_string("NOTIFY_APPNAME_LINUX")? {
+            self.appname_linux = Some(appname);
+        }
+        if let Some(appname) = env_string("NOTIFY_APPNAME_WINDOWS")? {
+            self.appname_windows = Some(appname);
+        }
+        if let Some(sticky) = env_bool("NOTIFY_STICKY")? {
+            self.sticky = sticky;
+        }
+        if let Some(timeout) = env_duration("NOTIFY_TIMEOUT")? {
+            self.timeout = Some(timeout);
+        }
+        if let Some(default_timeout) = env_bool("NOTIFY_TIMEOUT_DEFAULT")? {
+            self.default_timeout = default_timeout;
+        }
+        if let Some(resident) = env_bool("NOTIFY_RESIDENT")? {
+            self.resident = resident;
+        }
+        if let Some(close_on_ack) = env_bool("NOTIFY_CLOSE_ON_ACK")? {
+            self.close_on_ack = close_on_ack;
+        }
+        if let Some(comment_updates) = env_bool("NOTIFY_COMMENT_UPDATES")? {
+            self.comment_updates = comment_updates;
+        }
+        if let Some(icon) = env_string("NOTIFY_ICON")? {
+            self.icon = Some(PathBuf::from(icon));
+        }
+        if let Some(icon) = env_string("NOTIFY_ACKED_ICON")? {
+            self.acked_icon = Some(PathBuf::from(icon));
+        }
+        if let Some(sound) = env_string("NOTIFY_SOUND_FILE")? {
+            self.sound_file =
+                Some(PathBuf::from(sound)).filter(|path| !path.as_os_str().is_empty());
+        }
+        if let Some(hero) = env_string("NOTIFY_HERO_IMAGE")? {
+            self.hero_image = Some(PathBuf::from(hero));
+        }
+        if let Some(attribution) = env_string("NOTIFY_ATTRIBUTION")? {
+            self.attribution = Some(attribution);
+        }
+        if let Some(lines) = env_parse::<usize>("NOTIFY_MAX_BODY_LINES")? {
+            self.max_body_lines = Some(lines);
+        }
+        if let Some(chars) = env_parse::<usize>("NOTIFY_MAX_BODY_CHARS")? {
+            self.max_body_chars = Some(chars);
+        }
+        if let Some(prefix) = env_string("NOTIFY_SUMMARY_PREFIX")? {
+            self.summary_prefix = prefix;
+        }
+        if let Some(suffix) = env_string("NOTIFY_SUMMARY_SUFFIX")? {
+            self.summary_suffix = suffix;
+        }
+        self.apply_action_env_overrides()?;
+        if let Some(desktop_entry) = env_string("NOTIFY_DESKTOP_ENTRY")? {
+            self.desktop_entry = desktop_entry;
+        }
+        if let Some(group_by) = env_string("NOTIFY_GROUP_BY")? {
+            self.group_by = group_by;
+        }
+        if let Some(open_label) = env_string("NOTIFY_OPEN_LABEL")? {
+            self.open_label = open_label;
+        }
+        if let Some(notify_acked) = env_bool("NOTIFY_ACKED")? {
+            self.notify_acked = notify_acked;
+        }
+        if let Some(batch) = env_bool("NOTIFY_BATCH_BY_HOST")? {
+            self.batch_by_host = batch;
+        }
+        if let Some(poll_summary) = env_string("NOTIFY_POLL_SUMMARY")? {
+            self.poll_summary = poll_summary;
+        }
+        if let Some(emoji) = env_bool("NOTIFY_SEVERITY_EMOJI")? {
+            self.severity_emoji = emoji;
+        }
+        if let Some(detail) = env_string("NOTIFY_DETAIL")? {
+            self.detail = Some(detail);
+        }
+        if let Some(device) = env_string("NOTIFY_KDECONNECT_DEVICE")? {
+            match &mut self.kdeconnect {
+                Some(kdeconnect) => kdeconnect.device = device,
+                None => {
+                    self.kdeconnect = Some(RawKdeConnect {
+                        device,
+                        min_severity: default_kdeconnect_min_severity(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Overrides of the toast actions and of what they run.
+    fn apply_action_env_overrides(&mut self) -> std::result::Result<(), ConfigError> {
+        if let Some(command) = env_command("NOTIFY_PROMPT_COMMAND")? {
+            self.prompt_command = command;
+        }
+        if let Some(mode) = env_string("NOTIFY_ACK_PROMPT")? {
+            self.ack_prompt = mode;
+        }
+        if let Some(confirm) = env_bool("NOTIFY_CONFIRM_ACK")? {
+            self.confirm_ack = confirm;
+        }
+        if let Some(command) = env_command("NOTIFY_CONFIRM_COMMAND")? {
+            self.confirm_command = command;
+        }
+        if let Some(rank_action) = env_bool("NOTIFY_RANK_ACTION")? {
+            self.rank_action = rank_action;
+        }
+        if let Some(comment_action) = env_bool("NOTIFY_COMMENT_ACTION")? {
+            self.comment_action = comment_action;
+        }
+        if let Some(host_ack_action) = env_bool("NOTIFY_HOST_ACK_ACTION")? {
+            self.host_ack_action = host_ack_action;
+        }
+        if let Some(severity) = env_string("NOTIFY_ACK_MIN_SEVERITY")? {
+            self.ack_min_severity = severity;
+        }
+        if let Some(floor) = env_string("NOTIFY_MUTE_FLOOR")? {
+            self.mute_floor = floor;
+        }
+        if let Some(mute_for) = env_duration("NOTIFY_MUTE_ACTION")? {
+            self.mute_action = Some(mute_for);
+        }
+        Ok(())
+    }
+}
+
+impl RawApp {
+    fn validate(&self) -> std::result::Result<(), ConfigError> {
+        if !MAX_NOTIF_BOUNDS.contains(&self.max_notif) {
             return Err(ConfigError::InvalidField {
                 field: "app.max_notif",
                 message: format!(
                     "expected between {} and {}, got {}",
                     MAX_NOTIF_BOUNDS.start(),
                     MAX_NOTIF_BOUNDS.end(),
-                    self.app.max_notif
+                    self.max_notif
                 ),
-            }
-            .into());
+            });
         }
-        if self.app.queue_bound == 0 {
+        if self.queue_bound == 0 {
             return Err(ConfigError::InvalidField {
                 field: "app.queue_bound",
                 message: "queue bound must be greater than zero".to_string(),
-            }
-            .into());
+            });
         }
-        if self.app.dedup_cache_size == 0 {
+        if self.dedup_cache_size == 0 {
             return Err(ConfigError::InvalidField {
                 field: "app.dedup_cache_size",
                 message: "dedup cache size must be greater than zero".to_string(),
-            }
-            .into());
+            });
         }
-        if self.app.rate_limit_max == 0 {
+        if self.rate_limit_max == 0 {
             return Err(ConfigError::InvalidField {
                 field: "app.rate_limit_max",
                 message: "rate limit must allow at least one event".to_string(),
-            }
-            .into());
+            });
         }
-        if self.app.rate_limit_window.is_zero() {
+        if self.rate_limit_window.is_zero() {
             return Err(ConfigError::InvalidField {
                 field: "app.rate_limit_window",
                 message: "window duration must be greater than zero".to_string(),
-            }
-            .into());
+            });
+        }
+        if self.storm_threshold == Some(0) {
+            return Err(ConfigError::InvalidField {
+                field: "app.storm_threshold",
+                message: "storm threshold must be greater than zero".to_string(),
+            });
+        }
+        if self.history_max_rows == Some(0) {
+            return Err(ConfigError::InvalidField {
+                field: "app.history_max_rows",
+                message: "history must keep at least one row".to_string(),
+            });
         }
-        if self.app.poll_interval.is_zero() {
+        if self.storm_window.is_zero() {
+            return Err(ConfigError::InvalidField {
+                field: "app.storm_window",
+                message: "window duration must be greater than zero".to_string(),
+            });
+        }
+        if self.poll_interval.is_zero() {
             return Err(ConfigError::InvalidField {
                 field: "app.poll_interval",
                 message: "poll interval must be greater than zero".to_string(),
-            }
-            .into());
+            });
+        }
+        if self
+            .watchdog_deadline
+            .is_some_and(|deadline| deadline <= self.poll_interval)
+        {
+            return Err(ConfigError::InvalidField {
+                field: "app.watchdog_deadline",
+                message: "watchdog deadline must be longer than the poll interval".to_string(),
+            });
         }
+        if let Some(fmt) = &self.open_url_fmt {
+            validate_open_url_fmt(fmt)?;
+        }
+        Ok(())
+    }
+}
 
-        Ok(Config {
-            base_url,
-            token: token.into(),
-            limit: self.zabbix.limit,
-            concurrency: self.zabbix.concurrency.max(1),
-            ack_filter,
-            max_notif: self.app.max_notif,
-            queue_capacity: self.app.queue_bound,
-            dedup_cache_size: self.app.dedup_cache_size,
-            rate_limit: RateLimit {
-                max_events: self.app.rate_limit_max,
-                per: self.app.rate_limit_window,
-            },
-            poll_interval: self.app.poll_interval,
-            open_url_fmt: self.app.open_url_fmt,
-            notify: NotifySettings {
-                appname: self.notify.appname,
-                sticky: self.notify.sticky,
-                timeout: self.notify.timeout,
-                default_timeout: self.notify.default_timeout,
-                icon: self.notify.icon,
-                open_label: self.notify.open_label,
-                notify_acked: self.notify.notify_acked,
-            },
-            http_connect_timeout: DEFAULT_CONNECT_TIMEOUT,
-            http_request_timeout: DEFAULT_HTTP_TIMEOUT,
+/// The Open link template must name the event and render to an absolute
+/// URL, else every toast would carry a broken link.
+fn validate_open_url_fmt(fmt: &str) -> std::result::Result<(), ConfigError> {
+    const FIELD: &str = "app.open_url_fmt";
+    const PLACEHOLDER: &str = "{eventid}";
+    if !fmt.contains(PLACEHOLDER) {
+        return Err(ConfigError::InvalidField {
+            field: FIELD,
+            message: format!("template must contain the {PLACEHOLDER} placeholder"),
+        });
+    }
+    let sample = fmt.replace(PLACEHOLDER, "1");
+    match Url::parse(&sample) {
+        Ok(url) if url.cannot_be_a_base() => Err(ConfigError::InvalidField {
+            field: FIELD,
+            message: format!("{sample} is not a web address"),
+        }),
+        Ok(_) => Ok(()),
+        Err(err) => Err(ConfigError::InvalidField {
+            field: FIELD,
+            message: format!("{sample}: {err}"),
+        }),
+    }
+}
+
+impl RawNotify {
+    fn build(self) -> std::result::Result<NotifySettings, ConfigError> {
+        if self.max_body_lines == Some(0) {
+            return Err(ConfigError::InvalidField {
+                field: "notify.max_body_lines",
+                message: "body line limit must be greater than zero".to_string(),
+            });
+        }
+        if self.max_body_chars == Some(0) {
+            return Err(ConfigError::InvalidField {
+                field: "notify.max_body_chars",
+                message: "body length limit must be greater than zero".to_string(),
+            });
+        }
+
+        if let Some(aumid) = &self.appname_windows {
+            validate_aumid(aumid)?;
+        }
+        if let Some(sound) = &self.sound_file {
+            validate_sound_file(sound)?;
+        }
+        let platform_appname = if cfg!(target_os = "windows") {
+            self.appname_windows
+        } else if cfg!(target_os = "linux") {
+            self.appname_linux
+        } else {
+            None
+        };
+
+        Ok(NotifySettings {
+            appname: platform_appname.unwrap_or(self.appname),
+            sticky: self.sticky,
+            timeout: self.timeout,
+            default_timeout: self.default_timeout,
+            resident: self.resident,
+            close_on_ack: self.close_on_ack,
+            comment_updates: self.comment_updates,
+            icon: self.icon,
+            acked_icon: self.acked_icon,
+            sound_file: self.sound_file,
+            open_label: self.open_label,
+            notify_acked: self.notify_acked,
+            schedule: self.schedule.map(build_schedule).transpose()?,
+            heartbeat: self.heartbeat.as_ref().map(build_heartbeat).transpose()?,
+            kdeconnect: self.kdeconnect.as_ref().map(build_kdeconnect).transpose()?,
+            hero_image: self.hero_image,
+            attribution: self.attribution.filter(|text| !text.trim().is_empty()),
+            group_by: ToastGrouping::from_str(&self.group_by).map_err(|message| {
+                ConfigError::InvalidField {
+                    field: "notify.group_by",
+                    message,
+                }
+            })?,
+            batch_by_host: self.batch_by_host,
+            poll_summary: PollSummary::from_str(&self.poll_summary).map_err(|message| {
+                ConfigError::InvalidField {
+                    field: "notify.poll_summary",
+                    message,
+                }
+            })?,
+            severity_emoji: self.severity_emoji,
+            desktop_entry: Some(self.desktop_entry).filter(|entry| !entry.trim().is_empty()),
+            prompt_command: self.prompt_command,
+            ack_prompt: AckPrompt::from_str(&self.ack_prompt).map_err(|message| {
+                ConfigError::InvalidField {
+                    field: "notify.ack_prompt",
+                    message,
+                }
+            })?,
+            confirm_ack: self.confirm_ack,
+            confirm_command: self.confirm_command,
+            rank_action: self.rank_action,
+            comment_action: self.comment_action,
+            host_ack_action: self.host_ack_action,
+            ack_min_severity: Severity::from_str(&self.ack_min_severity).map_err(|message| {
+                ConfigError::InvalidField {
+                    field: "notify.ack_min_severity",
+                    message,
+                }
+            })?,
+            mute_floor: Severity::from_str(&self.mute_floor).map_err(|message| {
+                ConfigError::InvalidField {
+                    field: "notify.mute_floor",
+                    message,
+                }
+            })?,
+            mute_action: self.mute_action.filter(|mute_for| !mute_for.is_zero()),
+            max_body_lines: self.max_body_lines,
+            max_body_chars: self.max_body_chars,
+            summary_prefix: self.summary_prefix,
+            summary_suffix: self.summary_suffix,
+            severities: parse_severity_styles(self.severities)?,
+            detail: self
+                .detail
+                .as_deref()
+                .map_or(Ok(DetailLevel::Normal), DetailLevel::from_str)
+                .map_err(|message| ConfigError::InvalidField {
+                    field: "notify.detail",
+                    message,
+                })?,
         })
     }
 }
 
+fn parse_severity_styles(
+    raw: BTreeMap<String, RawSeverityStyle>,
+) -> std::result::Result<BTreeMap<Severity, SeverityStyle>, ConfigError> {
+    const FIELD: &str = "notify.severities";
+    raw.into_iter()
+        .map(|(severity, style)| {
+            let severity =
+                Severity::from_str(&severity).map_err(|message| ConfigError::InvalidField {
+                    field: FIELD,
+                    message,
+                })?;
+            let urgency = style
+                .urgency
+                .as_deref()
+                .map(Urgency::from_str)
+                .transpose()
+                .map_err(|message| ConfigError::InvalidField {
+                    field: FIELD,
+                    message,
+                })?;
+            let color = style
+                .color
+                .as_deref()
+                .map(Color::from_str)
+                .transpose()
+                .map_err(|message| ConfigError::InvalidField {
+                    field: FIELD,
+                    message,
+                })?;
+            Ok((
+                severity,
+                SeverityStyle {
+                    urgency,
+                    sticky: style.sticky,
+                    sound: style.sound,
+                    color,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Windows `AppUserModelID`s are at most 128 characters and contain no spaces.
+/// Empty is allowed and selects the `PowerShell` fallback identity.
+fn validate_aumid(aumid: &str) -> std::result::Result<(), ConfigError> {
+    const MAX_AUMID_LEN: usize = 128;
+    let message = if aumid.chars().count() > MAX_AUMID_LEN {
+        format!("AppUserModelID must be at most {MAX_AUMID_LEN} characters")
+    } else if aumid.chars().any(char::is_whitespace) {
+        "AppUserModelID cannot contain spaces (e.g. \"Company.Alerting\")".to_string()
+    } else {
+        return Ok(());
+    };
+    Err(ConfigError::InvalidField {
+        field: "notify.appname_windows",
+        message,
+    })
+}
+
+/// Notification servers and the Windows fallback player only know these.
+fn validate_sound_file(path: &Path) -> std::result::Result<(), ConfigError> {
+    let supported = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ["wav", "ogg", "oga"].contains(&ext.to_ascii_lowercase().as_str()));
+    if supported {
+        Ok(())
+    } else {
+        Err(ConfigError::InvalidField {
+            field: "notify.sound_file",
+            message: format!("expected a .wav or .ogg file, got {}", path.display()),
+        })
+    }
+}
+
+fn build_heartbeat(raw: &RawHeartbeat) -> std::result::Result<Heartbeat, ConfigError> {
+    let weekday =
+        match raw.every.trim().to_ascii_lowercase().as_str() {
+            "daily" => None,
+            "weekly" => Some(Weekday::from_str(raw.weekday.trim()).map_err(|_| {
+                ConfigError::InvalidField {
+                    field: "notify.heartbeat.weekday",
+                    message: format!("expected a day such as \"mon\", got {:?}", raw.weekday),
+                }
+            })?),
+            other => {
+                return Err(ConfigError::InvalidField {
+                    field: "notify.heartbeat.every",
+                    message: format!("expected daily or weekly, got {other:?}"),
+                });
+            }
+        };
+    Ok(Heartbeat {
+        weekday,
+        at: parse_time_of_day("notify.heartbeat.at", &raw.at)?,
+        backend: HeartbeatBackend::from_str(&raw.backend).map_err(|message| {
+            ConfigError::InvalidField {
+                field: "notify.heartbeat.backend",
+                message,
+            }
+        })?,
+    })
+}
+
+fn build_kdeconnect(raw: &RawKdeConnect) -> std::result::Result<KdeConnect, ConfigError> {
+    let device = raw.device.trim();
+    if device.is_empty() {
+        return Err(ConfigError::InvalidField {
+            field: "notify.kdeconnect.device",
+            message: "device id cannot be empty".to_string(),
+        });
+    }
+    Ok(KdeConnect {
+        device: device.to_string(),
+        min_severity: Severity::from_str(&raw.min_severity).map_err(|message| {
+            ConfigError::InvalidField {
+                field: "notify.kdeconnect.min_severity",
+                message,
+            }
+        })?,
+    })
+}
+
+fn build_schedule(raw: RawSchedule) -> std::result::Result<UrgencySchedule, ConfigError> {
+    Ok(UrgencySchedule {
+        work_start: parse_time_of_day("notify.schedule.work_start", &raw.work_start)?,
+        work_end: parse_time_of_day("notify.schedule.work_end", &raw.work_end)?,
+        weekends_off: raw.weekends_off,
+        off_hours_sticky: raw.off_hours_sticky,
+        work_hours: parse_urgency_map("notify.schedule.work_hours", raw.work_hours)?,
+        off_hours: parse_urgency_map("notify.schedule.off_hours", raw.off_hours)?,
+    })
+}
+
+fn parse_time_of_day(
+    field: &'static str,
+    value: &str,
+) -> std::result::Result<NaiveTime, ConfigError> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").map_err(|err| ConfigError::InvalidField {
+        field,
+        message: format!("expected HH:MM, got {value:?}: {err}"),
+    })
+}
+
+fn parse_urgency_map(
+    field: &'static str,
+    raw: BTreeMap<String, String>,
+) -> std::result::Result<BTreeMap<Severity, Urgency>, ConfigError> {
+    raw.into_iter()
+        .map(|(severity, urgency)| {
+            let severity = Severity::from_str(&severity)
+                .map_err(|message| ConfigError::InvalidField { field, message })?;
+            let urgency = Urgency::from_str(&urgency)
+                .map_err(|message| ConfigError::InvalidField { field, message })?;
+            Ok((severity, urgency))
+        })
+        .collect()
+}
+
 impl Default for RawZabbix {
     fn default() -> Self {
         Self {
             url: None,
+            fallback_urls: Vec::new(),
             token: None,
             limit: default_limit(),
+            max_pages: default_max_pages(),
             concurrency: default_concurrency(),
             ack_filter: Some(default_ack_filter()),
+            proxy_ids: Vec::new(),
+            host_availability: false,
+            symptoms: None,
+            event_sources: default_event_sources(),
+            event_objects: Vec::new(),
+            service_impact: false,
+            service_cache_ttl: default_service_cache_ttl(),
+            graph_user: None,
+            graph_password: None,
+            graph_period: default_graph_period(),
+            http_timeout: default_http_timeout(),
+            connect_timeout: default_connect_timeout(),
+            pool_idle_timeout: default_pool_idle_timeout(),
+            pool_max_idle_per_host: None,
+            tcp_keepalive: None,
+            max_requests_per_second: None,
+            max_in_flight: None,
+            host_miss_ttl: default_host_miss_ttl(),
+            log_rpc: false,
         }
     }
 }
@@ -286,12 +1323,45 @@ impl Default for RawNotify {
     fn default() -> Self {
         Self {
             appname: default_notify_appname(),
+            appname_linux: None,
+            appname_windows: None,
             sticky: false,
             timeout: None,
             default_timeout: false,
+            resident: false,
+            close_on_ack: true,
+            comment_updates: false,
             icon: None,
+            acked_icon: None,
+            sound_file: None,
             open_label: default_open_label(),
             notify_acked: false,
+            schedule: None,
+            heartbeat: None,
+            kdeconnect: None,
+            hero_image: None,
+            attribution: None,
+            group_by: default_group_by(),
+            batch_by_host: false,
+            poll_summary: default_poll_summary(),
+            severity_emoji: false,
+            desktop_entry: default_desktop_entry(),
+            prompt_command: default_prompt_command(),
+            ack_prompt: default_ack_prompt(),
+            confirm_ack: false,
+            confirm_command: default_confirm_command(),
+            rank_action: false,
+            comment_action: false,
+            host_ack_action: false,
+            ack_min_severity: default_ack_min_severity(),
+            mute_floor: default_mute_floor(),
+            mute_action: None,
+            max_body_lines: None,
+            max_body_chars: None,
+            summary_prefix: String::new(),
+            summary_suffix: String::new(),
+            severities: BTreeMap::new(),
+            detail: None,
         }
     }
 }
@@ -302,10 +1372,20 @@ impl Default for RawApp {
             max_notif: default_max_notif(),
             queue_bound: default_queue_bound(),
             dedup_cache_size: default_dedup_cache_size(),
+            notify_cooldown: None,
             rate_limit_max: default_rate_limit_max(),
             rate_limit_window: default_rate_limit_window(),
+            storm_threshold: None,
+            storm_window: default_storm_window(),
             poll_interval: default_poll_interval(),
             open_url_fmt: None,
+            host_url_fmt: None,
+            state_file: None,
+            history_file: None,
+            status_file: None,
+            history_max_age: default_history_max_age(),
+            history_max_rows: None,
+            watchdog_deadline: None,
         }
     }
 }
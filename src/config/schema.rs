@@ -0,0 +1,253 @@
+//! `ALERTING__<SECTION>__<FIELD>` overrides for every setting of the file,
+//! derived from the raw configuration structure rather than listed by hand
+//! like the legacy variables in `env.rs`.
+
+use serde_json::{Value, json};
+
+use super::env::parse_command;
+use super::raw::RawConfig;
+
+/// Prefix of the schema-derived variables; `__` also separates the keys.
+const PREFIX: &str = "ALERTING__";
+const SEPARATOR: &str = "__";
+
+/// Lists holding a command and its arguments rather than separate items.
+const COMMAND_KEYS: &[&str] = &["notify.confirm_command", "notify.prompt_command"];
+
+/// How a variable's value is read.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EnvKind {
+    /// One value, parsed like the same field in the file.
+    Value,
+    /// Comma-separated list.
+    List,
+    /// Command line, split with shell quoting rules, or a JSON array.
+    Command,
+    /// Table keyed by user-chosen names, e.g. `notify.severities.<severity>`:
+    /// any variable below it is accepted.
+    Table,
+}
+
+/// One `ALERTING__…` variable and the setting it overrides.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EnvVariable {
+    /// Dotted key in the file, e.g. `zabbix.http_timeout`.
+    pub key: String,
+    pub kind: EnvKind,
+}
+
+impl EnvVariable {
+    /// Variable name, e.g. `ALERTING__ZABBIX__HTTP_TIMEOUT`.
+    #[must_use]
+    pub fn name(&self) -> String {
+        format!(
+            "{PREFIX}{}",
+            self.key.replace('.', SEPARATOR).to_ascii_uppercase()
+        )
+    }
+}
+
+/// Every variable the schema accepts, sorted by key.
+///
+/// The optional tables are filled in with their defaults first so that their
+/// fields are listed too.
+#[must_use]
+pub fn env_variables() -> Vec<EnvVariable> {
+    let sample = json!({
+        "notify": {
+            "schedule": {},
+            "heartbeat": {},
+            "kdeconnect": { "device": "" },
+        },
+    });
+    let mut variables = Vec::new();
+    if let Ok(value) = serde_json::from_value::<RawConfig>(sample).and_then(serde_json::to_value) {
+        collect("", &value, &mut variables);
+    }
+    variables
+}
+
+fn collect(prefix: &str, value: &Value, out: &mut Vec<EnvVariable>) {
+    let Value::Object(fields) = value else {
+        return;
+    };
+    for (name, field) in fields {
+        let key = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{prefix}.{name}")
+        };
+        let kind = match field {
+            Value::Object(children) if !children.is_empty() => {
+                collect(&key, field, out);
+                continue;
+            }
+            Value::Object(_) => EnvKind::Table,
+            Value::Array(_) if COMMAND_KEYS.contains(&key.as_str()) => EnvKind::Command,
+            Value::Array(_) => EnvKind::List,
+            _ => EnvKind::Value,
+        };
+        out.push(EnvVariable { key, kind });
+    }
+}
+
+/// Configuration source reading the `ALERTING__…` variables. Unknown names
+/// are rejected, so a typo does not silently leave the setting unchanged.
+#[derive(Clone, Debug)]
+pub(super) struct SchemaEnv {
+    variables: Vec<EnvVariable>,
+}
+
+impl SchemaEnv {
+    pub(super) fn new() -> Self {
+        Self {
+            variables: env_variables(),
+        }
+    }
+
+    /// The value of `key`, `None` for an unknown key, or why `raw` is
+    /// malformed.
+    fn value(
+        &self,
+        key: &str,
+        raw: String,
+    ) -> Option<std::result::Result<::config::ValueKind, String>> {
+        let variable = self.variables.iter().find(|variable| match variable.kind {
+            EnvKind::Table => key
+                .strip_prefix(variable.key.as_str())
+                .is_some_and(|rest| rest.starts_with('.')),
+            EnvKind::Value | EnvKind::List | EnvKind::Command => variable.key == key,
+        })?;
+        Some(Ok(match variable.kind {
+            EnvKind::List => ::config::ValueKind::Array(
+                raw.split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(|item| ::config::Value::from(item.to_string()))
+                    .collect(),
+            ),
+            EnvKind::Command => match parse_command(&raw) {
+                Ok(args) => ::config::ValueKind::Array(
+                    args.into_iter().map(::config::Value::from).collect(),
+                ),
+                Err(message) => return Some(Err(message)),
+            },
+            EnvKind::Value | EnvKind::Table => ::config::ValueKind::String(raw),
+        }))
+    }
+}
+
+impl ::config::Source for SchemaEnv {
+    fn clone_into_box(&self) -> Box<dyn ::config::Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(
+        &self,
+    ) -> std::result::Result<::config::Map<String, ::config::Value>, ::config::ConfigError> {
+        let mut values = ::config::Map::new();
+        for (name, raw) in std::env::vars() {
+            let Some(rest) = name.strip_prefix(PREFIX) else {
+                continue;
+            };
+            let key = rest.to_ascii_lowercase().replace(SEPARATOR, ".");
+            let value = match self.value(&key, raw) {
+                Some(Ok(value)) => value,
+                Some(Err(message)) => {
+                    return Err(::config::ConfigError::Message(format!("{name}: {message}")));
+                }
+                None => {
+                    return Err(::config::ConfigError::Message(format!(
+                        "unknown setting {name} (`alerting config env` lists them)"
+                    )));
+                }
+            };
+            values.insert(key, ::config::Value::new(Some(&name), value));
+        }
+        Ok(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EnvKind, EnvVariable, SchemaEnv, env_variables};
+
+    fn find(key: &str) -> Option<EnvVariable> {
+        env_variables()
+            .into_iter()
+            .find(|variable| variable.key == key)
+    }
+
+    #[test]
+    fn every_section_is_covered() {
+        let timeout = find("zabbix.http_timeout");
+        assert_eq!(
+            timeout.as_ref().map(EnvVariable::name).as_deref(),
+            Some("ALERTING__ZABBIX__HTTP_TIMEOUT")
+        );
+        assert_eq!(
+            find("zabbix.fallback_urls").map(|v| v.kind),
+            Some(EnvKind::List)
+        );
+        assert_eq!(
+            find("notify.heartbeat.every").map(|v| v.kind),
+            Some(EnvKind::Value)
+        );
+        assert_eq!(
+            find("notify.severities").map(|v| v.kind),
+            Some(EnvKind::Table)
+        );
+        assert!(find("app.poll_interval").is_some());
+        assert_eq!(
+            find("notify.prompt_command").map(|v| v.kind),
+            Some(EnvKind::Command)
+        );
+    }
+
+    #[test]
+    fn values_follow_the_field_kind() {
+        let env = SchemaEnv::new();
+        assert_eq!(
+            env.value("zabbix.proxy_ids", "10084, 10085".to_string()),
+            Some(Ok(::config::ValueKind::Array(vec![
+                ::config::Value::from("10084".to_string()),
+                ::config::Value::from("10085".to_string()),
+            ])))
+        );
+        assert_eq!(
+            env.value("notify.severities.high.sticky", "true".to_string()),
+            Some(Ok(::config::ValueKind::String("true".to_string())))
+        );
+        assert_eq!(env.value("zabbix.htp_timeout", "5s".to_string()), None);
+    }
+
+    #[test]
+    fn commands_keep_quoted_arguments() {
+        let env = SchemaEnv::new();
+        let args = |args: &[&str]| {
+            Some(Ok(::config::ValueKind::Array(
+                args.iter()
+                    .map(|arg| ::config::Value::from((*arg).to_string()))
+                    .collect(),
+            )))
+        };
+        assert_eq!(
+            env.value(
+                "notify.prompt_command",
+                r#"kdialog --inputbox "{text}, merci""#.to_string()
+            ),
+            args(&["kdialog", "--inputbox", "{text}, merci"])
+        );
+        assert_eq!(
+            env.value(
+                "notify.confirm_command",
+                r#"["zenity", "--question", "--text", "{text}"]"#.to_string()
+            ),
+            args(&["zenity", "--question", "--text", "{text}"])
+        );
+        assert!(matches!(
+            env.value("notify.prompt_command", "kdialog 'open".to_string()),
+            Some(Err(_))
+        ));
+    }
+}
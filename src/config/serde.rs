@@ -1,6 +1,7 @@
 use std::time::Duration;
 
 use humantime::{format_duration, parse_duration};
+use secrecy::SecretString;
 use serde::Deserialize;
 use serde_with::{DeserializeAs, SerializeAs};
 
@@ -25,6 +26,16 @@ impl SerializeAs<Duration> for HumantimeDuration {
     }
 }
 
+const REDACTED: &str = "<redacted>";
+
+/// Serialize a secret as a fixed placeholder so it never leaks into output.
+pub fn redact_secret<S>(_: &SecretString, serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(REDACTED)
+}
+
 #[cfg(test)]
 mod tests {
     use super::HumantimeDuration;
@@ -12,6 +12,28 @@ pub enum Error {
     Notify(#[from] NotifyError),
     #[error("telemetry initialization failed: {0}")]
     Telemetry(String),
+    #[error("autostart setup failed: {0}")]
+    Autostart(String),
+    #[error("failed to write PID file {path}")]
+    PidFile {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("another alerting daemon holds PID file {path} (pid {pid})")]
+    AlreadyRunning { path: PathBuf, pid: String },
+    #[error("failed to access history file {path}")]
+    History {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("failed to write state file {path}")]
+    StateFile {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
 }
 
 #[derive(Debug, Error)]
@@ -60,10 +82,29 @@ pub enum ZbxError {
     Api { code: i64, message: String },
     #[error("missing field in API response: {field}")]
     MissingField { field: &'static str },
+    #[error("no recorded response at {path}")]
+    Tape {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("preflight check failed: {message}")]
+    Preflight {
+        message: String,
+        #[source]
+        source: Box<Error>,
+    },
+    #[error("frontend request failed: {message}")]
+    Frontend { message: String },
     #[error("retry budget exhausted")]
     RetryExhausted {
         #[source]
-        source: Box<ZbxError>,
+        source: Box<Self>,
+    },
+    #[error("failed to start the blocking client runtime")]
+    Runtime {
+        #[source]
+        source: std::io::Error,
     },
 }
 
@@ -71,6 +112,10 @@ pub enum ZbxError {
 pub enum NotifyError {
     #[error("notification backend failed")]
     Backend,
+    /// The notification server could not be reached (Linux: daemon
+    /// restarting or session bus gone); the toast may be shown later.
+    #[error("notification server unreachable")]
+    Unreachable,
     #[error("invalid notification payload: {0}")]
     InvalidPayload(String),
 }
@@ -87,6 +132,23 @@ impl From<reqwest::Error> for ZbxError {
 }
 
 impl Error {
+    /// Stable, machine-readable identifier of the error kind, for log
+    /// dashboards and alerting on the alerter. Codes are never renamed.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Config(err) => err.code(),
+            Self::Zabbix(err) => err.code(),
+            Self::Notify(err) => err.code(),
+            Self::Telemetry(_) => "TELEMETRY_INIT",
+            Self::Autostart(_) => "AUTOSTART_FAILED",
+            Self::PidFile { .. } => "PID_FILE_WRITE",
+            Self::AlreadyRunning { .. } => "ALREADY_RUNNING",
+            Self::History { .. } => "HISTORY_IO",
+            Self::StateFile { .. } => "STATE_FILE_WRITE",
+        }
+    }
+
     #[must_use]
     pub const fn is_retriable(&self) -> bool {
         matches!(
@@ -97,3 +159,92 @@ impl Error {
         )
     }
 }
+
+impl ConfigError {
+    /// Stable code of this error, see [`Error::code`].
+    #[must_use]
+    pub const fn code(&self) -> &'static str {
+        match self {
+            Self::FileRead { .. } => "CONFIG_FILE_READ",
+            Self::Parse(_) => "CONFIG_PARSE",
+            Self::MissingField { .. } => "CONFIG_MISSING_FIELD",
+            Self::InvalidField { .. } => "CONFIG_INVALID_FIELD",
+            Self::Other(_) => "CONFIG_OTHER",
+        }
+    }
+}
+
+impl ZbxError {
+    /// Stable code of this error, see [`Error::code`]. A failed preflight
+    /// reports the code of the check that failed.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Client { .. } | Self::Runtime { .. } => "ZBX_CLIENT_BUILD",
+            Self::Request { .. } => "ZBX_TRANSPORT",
+            Self::HttpStatus { .. } => "ZBX_HTTP_STATUS",
+            Self::Json { .. } => "ZBX_BAD_JSON",
+            Self::InvalidField { .. } => "ZBX_INVALID_FIELD",
+            Self::Api { .. } if self.is_auth_error() => "ZBX_API_AUTH",
+            Self::Api { .. } => "ZBX_API",
+            Self::MissingField { .. } => "ZBX_MISSING_FIELD",
+            Self::Tape { .. } => "ZBX_TAPE",
+            Self::Preflight { source, .. } => source.code(),
+            Self::Frontend { .. } => "ZBX_FRONTEND",
+            Self::RetryExhausted { .. } => "ZBX_RETRY_EXHAUSTED",
+        }
+    }
+
+    /// `true` when the API rejected the token or session.
+    #[must_use]
+    pub fn is_auth_error(&self) -> bool {
+        matches!(
+            self,
+            Self::Api { message, .. }
+                if message.contains("Not authori") || message.contains("Session terminated")
+        )
+    }
+}
+
+impl NotifyError {
+    /// Stable code of this error, see [`Error::code`].
+    #[must_use]
+    pub const fn code(&self) -> &'static str {
+        match self {
+            Self::Backend | Self::Unreachable => "NOTIFY_BACKEND_UNAVAILABLE",
+            Self::InvalidPayload(_) => "NOTIFY_INVALID_PAYLOAD",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConfigError, Error, NotifyError, ZbxError};
+
+    #[test]
+    fn codes_identify_the_underlying_cause() {
+        let auth = ZbxError::Api {
+            code: -32602,
+            message: "Invalid params. – Not authorized.".to_string(),
+        };
+        assert_eq!(auth.code(), "ZBX_API_AUTH");
+
+        let preflight = Error::from(ZbxError::Preflight {
+            message: "token is invalid or expired".to_string(),
+            source: Box::new(Error::from(auth)),
+        });
+        assert_eq!(preflight.code(), "ZBX_API_AUTH");
+
+        assert_eq!(
+            Error::from(NotifyError::Backend).code(),
+            "NOTIFY_BACKEND_UNAVAILABLE"
+        );
+        assert_eq!(
+            Error::from(ConfigError::MissingField {
+                field: "zabbix.token"
+            })
+            .code(),
+            "CONFIG_MISSING_FIELD"
+        );
+    }
+}
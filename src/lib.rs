@@ -3,7 +3,10 @@
 
 pub mod config;
 pub mod error;
+pub mod paths;
 pub mod telemetry;
+#[cfg(feature = "test-util")]
+pub mod testing;
 pub mod types;
 pub mod zbx_client;
 
@@ -0,0 +1,158 @@
+//! Per-user directories: the XDG base directories on Linux, `%APPDATA%` and
+//! `%LOCALAPPDATA%` on Windows. Files the daemon owns live there rather than
+//! in whatever directory it was started from.
+
+use std::path::{Path, PathBuf};
+
+use directories::ProjectDirs;
+
+/// Name of the default configuration file in [`config_dir`].
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// Name of the default `app.state_file` in [`state_dir`].
+pub const STATE_FILE: &str = "state.json";
+
+fn project() -> Option<ProjectDirs> {
+    ProjectDirs::from("", "", "alerting")
+}
+
+/// `$XDG_CONFIG_HOME/alerting`, or `%APPDATA%\alerting\config`.
+#[must_use]
+pub fn config_dir() -> Option<PathBuf> {
+    project().map(|dirs| dirs.config_dir().to_path_buf())
+}
+
+/// Where the configuration is looked for when `--config` is not given, in
+/// order: the configuration directory, `/etc/alerting` (Unix), the
+/// executable's directory (Windows), then the working directory.
+#[must_use]
+pub fn config_candidates() -> Vec<PathBuf> {
+    let mut candidates: Vec<PathBuf> = config_dir()
+        .map(|dir| dir.join(CONFIG_FILE))
+        .into_iter()
+        .collect();
+    #[cfg(unix)]
+    candidates.push(Path::new("/etc/alerting").join(CONFIG_FILE));
+    #[cfg(windows)]
+    if let Some(dir) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+    {
+        candidates.push(dir.join(CONFIG_FILE));
+    }
+    candidates.push(PathBuf::from(CONFIG_FILE));
+    candidates
+}
+
+/// `$XDG_STATE_HOME/alerting`, or `%LOCALAPPDATA%\alerting\data` where the
+/// platform has no state directory.
+#[must_use]
+pub fn state_dir() -> Option<PathBuf> {
+    project().map(|dirs| {
+        dirs.state_dir()
+            .unwrap_or_else(|| dirs.data_local_dir())
+            .to_path_buf()
+    })
+}
+
+/// `$XDG_CACHE_HOME/alerting`, or `%LOCALAPPDATA%\alerting\cache`.
+#[must_use]
+pub fn cache_dir() -> Option<PathBuf> {
+    project().map(|dirs| dirs.cache_dir().to_path_buf())
+}
+
+/// `path` as is when absolute, else inside [`state_dir`]. Falls back to
+/// the working directory when no home directory is known.
+#[must_use]
+pub fn in_state_dir(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    state_dir().map_or_else(|| path.to_path_buf(), |dir| dir.join(path))
+}
+
+/// Create the parent directory of `path`, so a first write into a fresh
+/// [`state_dir`] does not fail.
+///
+/// # Errors
+///
+/// Returns the I/O error when the directory cannot be created.
+pub fn create_parent(path: &Path) -> std::io::Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => std::fs::create_dir_all(parent),
+        _ => Ok(()),
+    }
+}
+
+/// Write `data` to a fresh file at `path`, creating its parent. Whatever
+/// was at `path` is unlinked first and the file is created exclusively, so
+/// a planted symlink is removed rather than written through.
+///
+/// # Errors
+///
+/// Returns the I/O error when the directory or the file cannot be written.
+pub fn write_new(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    use std::io::Write as _;
+
+    create_parent(path)?;
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)?
+        .write_all(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::{CONFIG_FILE, config_candidates, config_dir, in_state_dir, state_dir};
+
+    #[cfg(unix)]
+    #[test]
+    fn write_new_replaces_symlinks() -> std::io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("alerting-paths-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir)?;
+        let target = dir.join("target");
+        std::fs::write(&target, b"keep")?;
+        let link = dir.join("42.png");
+        std::os::unix::fs::symlink(&target, &link)?;
+
+        super::write_new(&link, b"png")?;
+        assert_eq!(std::fs::read(&target)?, b"keep");
+        assert_eq!(std::fs::read(&link)?, b"png");
+        assert!(!std::fs::symlink_metadata(&link)?.file_type().is_symlink());
+        std::fs::remove_dir_all(dir)
+    }
+
+    #[test]
+    fn user_config_comes_first_and_working_directory_last() {
+        let candidates = config_candidates();
+        assert_eq!(
+            candidates.first(),
+            Some(&config_dir().map_or_else(|| CONFIG_FILE.into(), |dir| dir.join(CONFIG_FILE)))
+        );
+        assert_eq!(
+            candidates.last().map(PathBuf::as_path),
+            Some(Path::new(CONFIG_FILE))
+        );
+        #[cfg(unix)]
+        assert!(candidates.contains(&Path::new("/etc/alerting/config.toml").to_path_buf()));
+    }
+
+    #[test]
+    fn relative_paths_land_in_the_state_dir() {
+        let absolute = std::env::temp_dir().join("history.jsonl");
+        assert_eq!(in_state_dir(&absolute), absolute);
+        if let Some(dir) = state_dir() {
+            assert_eq!(
+                in_state_dir(Path::new("history.jsonl")),
+                dir.join("history.jsonl")
+            );
+        }
+    }
+}
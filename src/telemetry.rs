@@ -32,6 +32,7 @@ pub fn init_tracing(explicit_filter: Option<&str>, use_json: bool) -> Result<()>
                 .with_target(true)
                 .with_file(true)
                 .with_line_number(true)
+                .with_writer(std::io::stderr)
                 .json()
                 .flatten_event(true),
         );
@@ -51,7 +52,8 @@ pub fn init_tracing(explicit_filter: Option<&str>, use_json: bool) -> Result<()>
         tracing_subscriber::fmt::layer()
             .with_target(true)
             .with_file(true)
-            .with_line_number(true),
+            .with_line_number(true)
+            .with_writer(std::io::stderr),
     );
     tracing::subscriber::set_global_default(subscriber)
         .map_err(|err| Error::Telemetry(err.to_string()))
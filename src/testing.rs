@@ -0,0 +1,287 @@
+//! Mock Zabbix JSON-RPC server for integration tests (`test-util` feature).
+//!
+//! ```no_run
+//! # async fn demo() -> alerting::Result<()> {
+//! use alerting::testing::{MockProblem, MockZabbix};
+//! use alerting::types::{AckFilter, Severity};
+//!
+//! let zabbix = MockZabbix::start().await;
+//! zabbix.set_problems(vec![MockProblem::new("42", "Disk full", Severity::High, "srv01")]);
+//! let problems = zabbix.client()?.active_problems(10, AckFilter::All).await?;
+//! assert_eq!(problems[0].event_id, "42");
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use secrecy::SecretString;
+use serde_json::{Value, json};
+use url::Url;
+use wiremock::matchers::method;
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+use crate::Result;
+use crate::error::{ConfigError, Error};
+use crate::types::Severity;
+use crate::zbx_client::ZbxClient;
+
+/// Version reported by `apiinfo.version`; the token is a super admin's.
+const API_VERSION: &str = "7.0.0";
+
+/// Problem served by [`MockZabbix`], together with its host.
+#[derive(Clone, Debug)]
+pub struct MockProblem {
+    pub event_id: String,
+    pub name: String,
+    pub severity: Severity,
+    pub clock: i64,
+    pub acknowledged: bool,
+    pub host: String,
+    /// Proxy monitoring the host; `"0"` for the server itself.
+    pub proxy_id: String,
+}
+
+impl MockProblem {
+    pub fn new(
+        event_id: impl Into<String>,
+        name: impl Into<String>,
+        severity: Severity,
+        host: impl Into<String>,
+    ) -> Self {
+        Self {
+            event_id: event_id.into(),
+            name: name.into(),
+            severity,
+            clock: 1_700_000_000,
+            acknowledged: false,
+            host: host.into(),
+            proxy_id: "0".to_string(),
+        }
+    }
+}
+
+/// Failure injected ahead of the canned responses.
+#[derive(Clone, Debug)]
+pub enum Fault {
+    /// Reply with this HTTP status and an empty body.
+    Status(u16),
+    /// Reply with a JSON-RPC error object.
+    RpcError { code: i64, message: String },
+    /// Reply with a body that is not valid JSON.
+    MalformedBody,
+    /// Reply normally, but only after this delay.
+    Delay(Duration),
+}
+
+/// wiremock server answering `problem.get`, `event.get`, `host.get` and
+/// `event.acknowledge` from an in-memory problem list.
+pub struct MockZabbix {
+    server: MockServer,
+    problems: Arc<Mutex<Vec<MockProblem>>>,
+}
+
+impl MockZabbix {
+    pub async fn start() -> Self {
+        let server = MockServer::start().await;
+        let problems = Arc::new(Mutex::new(Vec::new()));
+        Mock::given(method("POST"))
+            .respond_with(CannedResponder {
+                problems: Arc::clone(&problems),
+            })
+            .with_priority(u8::MAX)
+            .mount(&server)
+            .await;
+        Self { server, problems }
+    }
+
+    /// Endpoint URL (plain HTTP, so clients need `insecure_http`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server address cannot be parsed as a URL.
+    pub fn url(&self) -> Result<Url> {
+        Url::parse(&self.server.uri())
+            .map_err(|err| Error::Config(ConfigError::Other(err.to_string())))
+    }
+
+    /// Client pointed at this server with short timeouts.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the client cannot be built.
+    pub fn client(&self) -> Result<ZbxClient> {
+        ZbxClient::new(
+            self.url()?,
+            SecretString::from("mock-token"),
+            Duration::from_secs(2),
+            Duration::from_secs(1),
+            true,
+        )
+    }
+
+    /// Replace the problems returned by `problem.get`.
+    pub fn set_problems(&self, problems: Vec<MockProblem>) {
+        *self.problems.lock().unwrap_or_else(PoisonError::into_inner) = problems;
+    }
+
+    /// Current problem list, including acknowledgements made through the API.
+    pub fn problems(&self) -> Vec<MockProblem> {
+        self.problems
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Answer the next `times` requests with `fault` instead of the canned data.
+    pub async fn inject_fault(&self, fault: Fault, times: u64) {
+        let template = match fault {
+            Fault::Status(status) => ResponseTemplate::new(status),
+            Fault::RpcError { code, message } => ResponseTemplate::new(200).set_body_json(json!({
+                "jsonrpc": "2.0",
+                "error": { "code": code, "message": message },
+                "id": 1
+            })),
+            Fault::MalformedBody => ResponseTemplate::new(200).set_body_string("{not json"),
+            Fault::Delay(delay) => ResponseTemplate::new(200)
+                .set_body_json(json!({ "jsonrpc": "2.0", "result": [], "id": 1 }))
+                .set_delay(delay),
+        };
+        Mock::given(method("POST"))
+            .respond_with(template)
+            .up_to_n_times(times)
+            .with_priority(1)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Bodies of every request received so far.
+    pub async fn requests(&self) -> Vec<Value> {
+        self.server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|req| serde_json::from_slice(&req.body).ok())
+            .collect()
+    }
+}
+
+struct CannedResponder {
+    problems: Arc<Mutex<Vec<MockProblem>>>,
+}
+
+impl Respond for CannedResponder {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let Ok(body) = serde_json::from_slice::<Value>(&request.body) else {
+            return ResponseTemplate::new(400);
+        };
+        let id = body.get("id").cloned().unwrap_or(Value::Null);
+        let params = body.get("params").cloned().unwrap_or(Value::Null);
+        let event_ids: Vec<String> = params
+            .get("eventids")
+            .and_then(Value::as_array)
+            .map(|ids| {
+                ids.iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut problems = self.problems.lock().unwrap_or_else(PoisonError::into_inner);
+        let result = match body.get("method").and_then(Value::as_str) {
+            Some("problem.get") => {
+                let ack = params.get("acknowledged").and_then(Value::as_bool);
+                let host_ids = string_list(&params, "hostids");
+                Value::Array(
+                    problems
+                        .iter()
+                        .filter(|p| ack.is_none_or(|ack| p.acknowledged == ack))
+                        .filter(|p| host_ids.as_ref().is_none_or(|ids| ids.contains(&p.host)))
+                        .map(|p| {
+                            json!({
+                                "eventid": p.event_id,
+                                "clock": p.clock.to_string(),
+                                "lastchange": p.clock.to_string(),
+                                "severity": p.severity.as_zabbix_code().to_string(),
+                                "name": p.name,
+                                "acknowledged": if p.acknowledged { "1" } else { "0" },
+                            })
+                        })
+                        .collect(),
+                )
+            }
+            Some("event.get") => Value::Array(
+                problems
+                    .iter()
+                    .filter(|p| event_ids.contains(&p.event_id))
+                    .map(|p| {
+                        json!({
+                            "hosts": [{ "hostid": p.host, "host": p.host, "name": p.host, "status": "0" }]
+                        })
+                    })
+                    .collect(),
+            ),
+            Some("host.get") => {
+                let host_ids = string_list(&params, "hostids");
+                let proxy_ids = string_list(&params, "proxyids");
+                let mut hosts: Vec<&MockProblem> = problems
+                    .iter()
+                    .filter(|p| host_ids.as_ref().is_none_or(|ids| ids.contains(&p.host)))
+                    .filter(|p| proxy_ids.as_ref().is_none_or(|ids| ids.contains(&p.proxy_id)))
+                    .collect();
+                hosts.sort_by_key(|p| &p.host);
+                hosts.dedup_by_key(|p| &p.host);
+                Value::Array(
+                    hosts
+                        .into_iter()
+                        .map(|p| json!({ "hostid": p.host, "host": p.host, "proxyid": p.proxy_id }))
+                        .collect(),
+                )
+            }
+            Some("apiinfo.version") => json!(API_VERSION),
+            Some("user.checkAuthentication") => {
+                json!({ "userid": "1", "type": "3", "roleid": "3" })
+            }
+            Some("event.acknowledge") => {
+                // Only the acknowledge (2) and unacknowledge (16) bits move
+                // the flag; a message or severity change leaves it alone.
+                let action = params.get("action").and_then(Value::as_i64).unwrap_or(0);
+                let acknowledged = match (action & 2 != 0, action & 16 != 0) {
+                    (true, _) => Some(true),
+                    (false, true) => Some(false),
+                    (false, false) => None,
+                };
+                for problem in problems
+                    .iter_mut()
+                    .filter(|p| event_ids.contains(&p.event_id))
+                {
+                    problem.acknowledged = acknowledged.unwrap_or(problem.acknowledged);
+                }
+                json!({ "eventids": event_ids })
+            }
+            _ => {
+                return ResponseTemplate::new(200).set_body_json(json!({
+                    "jsonrpc": "2.0",
+                    "error": { "code": -32601, "message": "Method not found" },
+                    "id": id
+                }));
+            }
+        };
+        drop(problems);
+        ResponseTemplate::new(200)
+            .set_body_json(json!({ "jsonrpc": "2.0", "result": result, "id": id }))
+    }
+}
+
+/// String ids of `params[key]`; `None` when the filter is absent.
+fn string_list(params: &Value, key: &str) -> Option<Vec<String>> {
+    params.get(key).and_then(Value::as_array).map(|ids| {
+        ids.iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect()
+    })
+}
@@ -41,6 +41,223 @@ impl FromStr for AckFilter {
     }
 }
 
+/// How symptom events (Zabbix 6.4+ cause/symptom correlation) are handled.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymptomMode {
+    /// Ignore the correlation; symptoms are notified like any problem.
+    Show,
+    /// Nest symptoms under their cause in digests.
+    Group,
+    /// Do not notify symptoms at all.
+    Skip,
+}
+
+impl SymptomMode {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Show => "show",
+            Self::Group => "group",
+            Self::Skip => "skip",
+        }
+    }
+}
+
+impl Display for SymptomMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SymptomMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "show" => Ok(Self::Show),
+            "group" => Ok(Self::Group),
+            "skip" => Ok(Self::Skip),
+            other => Err(format!("unknown symptom mode: {other}")),
+        }
+    }
+}
+
+/// What raised an event (`source` of `problem.get`).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventSource {
+    /// A trigger went into problem state.
+    Trigger,
+    /// Zabbix itself, e.g. an item became unsupported.
+    Internal,
+    /// A business service changed status.
+    Service,
+}
+
+impl EventSource {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Trigger => "trigger",
+            Self::Internal => "internal",
+            Self::Service => "service",
+        }
+    }
+
+    #[must_use]
+    pub const fn as_zabbix_code(self) -> i64 {
+        match self {
+            Self::Trigger => 0,
+            Self::Internal => 3,
+            Self::Service => 4,
+        }
+    }
+
+    /// Objects the events of this source can be about.
+    #[must_use]
+    pub const fn objects(self) -> &'static [EventObject] {
+        match self {
+            Self::Trigger => &[EventObject::Trigger],
+            Self::Internal => &[
+                EventObject::Trigger,
+                EventObject::Item,
+                EventObject::LldRule,
+            ],
+            Self::Service => &[EventObject::Service],
+        }
+    }
+}
+
+impl Display for EventSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EventSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "trigger" => Ok(Self::Trigger),
+            "internal" => Ok(Self::Internal),
+            "service" => Ok(Self::Service),
+            other => Err(format!("unknown event source: {other}")),
+        }
+    }
+}
+
+/// What an event is about (`object` of `problem.get`).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventObject {
+    Trigger,
+    Item,
+    LldRule,
+    Service,
+}
+
+impl EventObject {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Trigger => "trigger",
+            Self::Item => "item",
+            Self::LldRule => "lld_rule",
+            Self::Service => "service",
+        }
+    }
+
+    #[must_use]
+    pub const fn as_zabbix_code(self) -> i64 {
+        match self {
+            Self::Trigger => 0,
+            Self::Item => 4,
+            Self::LldRule => 5,
+            Self::Service => 6,
+        }
+    }
+}
+
+impl Display for EventObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EventObject {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "trigger" => Ok(Self::Trigger),
+            "item" => Ok(Self::Item),
+            "lld_rule" | "lld" => Ok(Self::LldRule),
+            "service" => Ok(Self::Service),
+            other => Err(format!("unknown event object: {other}")),
+        }
+    }
+}
+
+/// Source and object pairs to ask `problem.get` for: every object of each
+/// source, or only those listed in `objects` when it is not empty.
+#[must_use]
+pub fn event_kinds(
+    sources: &[EventSource],
+    objects: &[EventObject],
+) -> Vec<(EventSource, EventObject)> {
+    sources
+        .iter()
+        .flat_map(|&source| source.objects().iter().map(move |&object| (source, object)))
+        .filter(|(_, object)| objects.is_empty() || objects.contains(object))
+        .collect()
+}
+
+/// How much of the problem a toast body shows.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DetailLevel {
+    /// Event id and acknowledgement state only.
+    Minimal,
+    /// Adds the other problems on the host and the impacted services.
+    #[default]
+    Normal,
+    /// Adds the trigger id and expression and the event tags, for tuning
+    /// templates and filters.
+    Debug,
+}
+
+impl DetailLevel {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Minimal => "minimal",
+            Self::Normal => "normal",
+            Self::Debug => "debug",
+        }
+    }
+}
+
+impl Display for DetailLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DetailLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "minimal" => Ok(Self::Minimal),
+            "normal" => Ok(Self::Normal),
+            "debug" => Ok(Self::Debug),
+            other => Err(format!("unknown detail level: {other}")),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Severity {
@@ -52,6 +269,15 @@ pub enum Severity {
 }
 
 impl Severity {
+    /// Every severity, from the least to the most severe.
+    pub const ALL: [Self; 5] = [
+        Self::Info,
+        Self::Warning,
+        Self::Average,
+        Self::High,
+        Self::Disaster,
+    ];
+
     #[must_use]
     pub const fn from_zabbix(code: i64) -> Option<Self> {
         match code {
@@ -64,6 +290,17 @@ impl Severity {
         }
     }
 
+    /// Coloured dot close to the frontend's severity colour.
+    #[must_use]
+    pub const fn emoji(self) -> &'static str {
+        match self {
+            Self::Info => "🔵",
+            Self::Warning => "🟡",
+            Self::Average | Self::High => "🟠",
+            Self::Disaster => "🔴",
+        }
+    }
+
     #[must_use]
     pub const fn as_zabbix_code(self) -> i64 {
         match self {
@@ -103,9 +340,89 @@ impl FromStr for Severity {
     }
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Urgency {
+    Low,
+    Normal,
+    Critical,
+}
+
+impl Urgency {
+    #[must_use]
+    pub const fn for_severity(severity: Severity) -> Self {
+        match severity {
+            Severity::Disaster | Severity::High => Self::Critical,
+            Severity::Average | Severity::Warning => Self::Normal,
+            Severity::Info => Self::Low,
+        }
+    }
+}
+
+impl Display for Urgency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::Critical => "critical",
+        })
+    }
+}
+
+impl FromStr for Urgency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "low" => Ok(Self::Low),
+            "normal" => Ok(Self::Normal),
+            "critical" => Ok(Self::Critical),
+            other => Err(format!("unknown urgency: {other}")),
+        }
+    }
+}
+
+/// RGB colour written `#RRGGBB`, as in the Zabbix severity settings.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        let channel = |at: usize| {
+            hex.get(at..at + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+        };
+        match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(r), Some(g), Some(b)) => Ok(Self { r, g, b }),
+            _ => Err(format!("invalid colour {s} (expected #RRGGBB)")),
+        }
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{AckFilter, Severity};
+    use super::{
+        AckFilter, Color, DetailLevel, EventObject, EventSource, Severity, SymptomMode, event_kinds,
+    };
     use std::str::FromStr;
 
     #[test]
@@ -116,6 +433,57 @@ mod tests {
         assert!(AckFilter::from_str("maybe").is_err());
     }
 
+    #[test]
+    fn symptom_mode_from_str_accepts_variants() {
+        assert_eq!(SymptomMode::from_str("Group"), Ok(SymptomMode::Group));
+        assert_eq!(SymptomMode::from_str("skip"), Ok(SymptomMode::Skip));
+        assert!(SymptomMode::from_str("hide").is_err());
+    }
+
+    #[test]
+    fn event_kinds_pair_sources_with_their_objects() {
+        assert_eq!(EventSource::from_str("Internal"), Ok(EventSource::Internal));
+        assert_eq!(EventObject::from_str("lld"), Ok(EventObject::LldRule));
+        assert!(EventObject::from_str("host").is_err());
+
+        assert_eq!(
+            event_kinds(&[EventSource::Trigger], &[]),
+            vec![(EventSource::Trigger, EventObject::Trigger)]
+        );
+        assert_eq!(
+            event_kinds(
+                &[EventSource::Trigger, EventSource::Internal],
+                &[EventObject::LldRule]
+            ),
+            vec![(EventSource::Internal, EventObject::LldRule)]
+        );
+        assert!(event_kinds(&[EventSource::Service], &[EventObject::Item]).is_empty());
+    }
+
+    #[test]
+    fn detail_level_from_str_accepts_variants() {
+        assert_eq!(DetailLevel::from_str("DEBUG"), Ok(DetailLevel::Debug));
+        assert_eq!(DetailLevel::from_str("minimal"), Ok(DetailLevel::Minimal));
+        assert!(DetailLevel::from_str("verbose").is_err());
+    }
+
+    #[test]
+    fn color_from_str_accepts_hex_triplets() {
+        let color = Color::from_str("#E97659");
+        assert_eq!(
+            color,
+            Ok(Color {
+                r: 0xe9,
+                g: 0x76,
+                b: 0x59
+            })
+        );
+        assert_eq!(Color::from_str("e97659"), color);
+        assert_eq!(color.map(|c| c.to_string()).as_deref(), Ok("#e97659"));
+        assert!(Color::from_str("#E9765").is_err());
+        assert!(Color::from_str("#GG7659").is_err());
+    }
+
     #[test]
     fn severity_from_zabbix_parses_known_codes() {
         assert_eq!(Severity::from_zabbix(4), Some(Severity::High));
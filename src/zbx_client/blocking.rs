@@ -0,0 +1,214 @@
+//! Blocking flavour of [`ZbxClient`](super::ZbxClient), for UI callbacks and
+//! scripts that run no Tokio runtime of their own.
+//!
+//! Calls are driven by a current-thread runtime owned by the client and
+//! shared by its clones. Like `reqwest::blocking`, they panic when made from
+//! inside an async context.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use secrecy::SecretString;
+use tokio::runtime::Runtime;
+use url::Url;
+
+use crate::Result;
+use crate::error::ZbxError;
+use crate::types::AckFilter;
+
+use super::{EventRank, EventUpdate, HostMeta, Preflight, Problem, ProblemQuery};
+
+#[derive(Clone)]
+pub struct ZbxClient {
+    inner: super::ZbxClient,
+    runtime: Arc<Runtime>,
+}
+
+impl ZbxClient {
+    /// Build a client with the parameters of [`super::ZbxClient::new`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the async client cannot be built or the runtime
+    /// cannot start.
+    pub fn new(
+        base: Url,
+        token: SecretString,
+        timeout: Duration,
+        connect_timeout: Duration,
+        insecure_http: bool,
+    ) -> Result<Self> {
+        Self::from_async(super::ZbxClient::new(
+            base,
+            token,
+            timeout,
+            connect_timeout,
+            insecure_http,
+        )?)
+    }
+
+    /// Wrap an async client, keeping what its builder methods set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the runtime cannot start.
+    pub fn from_async(inner: super::ZbxClient) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|source| ZbxError::Runtime { source })?;
+        Ok(Self {
+            inner,
+            runtime: Arc::new(runtime),
+        })
+    }
+
+    /// The async client behind this one.
+    #[must_use]
+    pub const fn as_async(&self) -> &super::ZbxClient {
+        &self.inner
+    }
+
+    /// See [`super::ZbxClient::preflight`].
+    ///
+    /// # Errors
+    ///
+    /// Same as the async call.
+    pub fn preflight(&self) -> Result<Preflight> {
+        self.runtime.block_on(self.inner.preflight())
+    }
+
+    /// See [`super::ZbxClient::active_problems`].
+    ///
+    /// # Errors
+    ///
+    /// Same as the async call.
+    pub fn active_problems(&self, limit: u32, ack: AckFilter) -> Result<Vec<Problem>> {
+        self.runtime
+            .block_on(self.inner.active_problems(limit, ack))
+    }
+
+    /// See [`super::ZbxClient::find_problems`].
+    ///
+    /// # Errors
+    ///
+    /// Same as the async call.
+    pub fn find_problems(&self, query: &ProblemQuery) -> Result<Vec<Problem>> {
+        self.runtime.block_on(self.inner.find_problems(query))
+    }
+
+    /// See [`super::ZbxClient::host_ids_by_name`].
+    ///
+    /// # Errors
+    ///
+    /// Same as the async call.
+    pub fn host_ids_by_name(&self, name: &str) -> Result<Vec<String>> {
+        self.runtime.block_on(self.inner.host_ids_by_name(name))
+    }
+
+    /// See [`super::ZbxClient::resolve_hosts`].
+    ///
+    /// # Errors
+    ///
+    /// Same as the async call.
+    pub fn resolve_hosts(
+        &self,
+        event_ids: &[String],
+        concurrency: usize,
+    ) -> Result<Vec<Option<HostMeta>>> {
+        self.runtime
+            .block_on(self.inner.resolve_hosts(event_ids, concurrency))
+    }
+
+    /// See [`super::ZbxClient::services_by_event`].
+    ///
+    /// # Errors
+    ///
+    /// Same as the async call.
+    pub fn services_by_event(&self) -> Result<HashMap<String, Vec<String>>> {
+        self.runtime.block_on(self.inner.services_by_event())
+    }
+
+    /// See [`super::ZbxClient::event_updates`].
+    ///
+    /// # Errors
+    ///
+    /// Same as the async call.
+    pub fn event_updates(&self, event_ids: &[String]) -> Result<HashMap<String, Vec<EventUpdate>>> {
+        self.runtime.block_on(self.inner.event_updates(event_ids))
+    }
+
+    /// See [`super::ZbxClient::token_user_id`].
+    ///
+    /// # Errors
+    ///
+    /// Same as the async call.
+    pub fn token_user_id(&self) -> Result<String> {
+        self.runtime.block_on(self.inner.token_user_id())
+    }
+
+    /// See [`super::ZbxClient::ack_event`].
+    ///
+    /// # Errors
+    ///
+    /// Same as the async call.
+    pub fn ack_event(&self, eventid: &str, message: Option<String>) -> Result<()> {
+        self.runtime
+            .block_on(self.inner.ack_event(eventid, message))
+    }
+
+    /// See [`super::ZbxClient::ack_events`].
+    ///
+    /// # Errors
+    ///
+    /// Same as the async call.
+    pub fn ack_events(&self, eventids: &[&str], message: Option<String>) -> Result<()> {
+        self.runtime
+            .block_on(self.inner.ack_events(eventids, message))
+    }
+
+    /// See [`super::ZbxClient::unack_event`].
+    ///
+    /// # Errors
+    ///
+    /// Same as the async call.
+    pub fn unack_event(&self, eventid: &str, message: Option<String>) -> Result<()> {
+        self.runtime
+            .block_on(self.inner.unack_event(eventid, message))
+    }
+
+    /// See [`super::ZbxClient::comment_event`].
+    ///
+    /// # Errors
+    ///
+    /// Same as the async call.
+    pub fn comment_event(&self, eventid: &str, message: &str) -> Result<()> {
+        self.runtime
+            .block_on(self.inner.comment_event(eventid, message))
+    }
+
+    /// See [`super::ZbxClient::suppress_event`].
+    ///
+    /// # Errors
+    ///
+    /// Same as the async call.
+    pub fn suppress_event(
+        &self,
+        eventid: &str,
+        until: Option<i64>,
+        message: Option<String>,
+    ) -> Result<()> {
+        self.runtime
+            .block_on(self.inner.suppress_event(eventid, until, message))
+    }
+
+    /// See [`super::ZbxClient::rank_event`].
+    ///
+    /// # Errors
+    ///
+    /// Same as the async call.
+    pub fn rank_event(&self, eventid: &str, rank: &EventRank) -> Result<()> {
+        self.runtime.block_on(self.inner.rank_event(eventid, rank))
+    }
+}
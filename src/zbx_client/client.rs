@@ -1,4 +1,7 @@
 use std::fmt::Write as FmtWrite;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
 
 use reqwest::StatusCode;
@@ -7,14 +10,20 @@ use secrecy::{ExposeSecret, SecretString};
 use serde::de::DeserializeOwned;
 use serde_json::Value;
 use tokio::time::sleep;
-use tracing::{debug, warn};
+use tracing::{Instrument, debug, debug_span, info, warn};
 use url::Url;
 use uuid::Uuid;
 
 use crate::Result;
 use crate::error::{Error, ZbxError};
+use crate::types::{EventObject, EventSource, event_kinds};
 
-use super::rpc::{RpcEnvelope, RpcRequest, body_preview};
+use super::host_misses::HostMisses;
+use super::metrics::{CallMetrics, CallStats};
+use super::preflight::UNAUTHENTICATED_METHODS;
+use super::rpc::{RpcEnvelope, RpcRequest, body_preview, redacted};
+use super::tape::{Tape, TapeMode};
+use super::throttle::Throttle;
 
 const MAX_ATTEMPTS: usize = 3;
 const CORRELATION_HEADER: &str = "x-correlation-id";
@@ -64,12 +73,51 @@ impl RetryBackoff {
     }
 }
 
+/// Connection reuse of the JSON-RPC HTTP client.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HttpPool {
+    /// How long an idle connection is kept for the next request.
+    pub idle_timeout: Duration,
+    /// Idle connections kept per host; `0` opens a new one for every request.
+    pub max_idle_per_host: usize,
+    /// Interval of TCP keepalive probes, so middleboxes do not drop idle
+    /// connections; `None` sends none.
+    pub tcp_keepalive: Option<Duration>,
+}
+
+impl Default for HttpPool {
+    fn default() -> Self {
+        Self {
+            idle_timeout: Duration::from_secs(30),
+            max_idle_per_host: usize::MAX,
+            tcp_keepalive: None,
+        }
+    }
+}
+
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Clone)]
 pub struct ZbxClient {
     http: reqwest::Client,
-    base: Url,
-    token: SecretString,
+    /// JSON-RPC endpoint followed by its fallbacks, tried in order.
+    bases: Arc<[Url]>,
+    /// Index in `bases` of the endpoint that answered last, shared by clones.
+    active: Arc<AtomicUsize>,
+    https_only: bool,
+    pub(super) token: SecretString,
     timeout: Duration,
+    connect_timeout: Duration,
+    tape: Option<Arc<Tape>>,
+    throttle: Option<Arc<Throttle>>,
+    pub(super) host_misses: Option<Arc<HostMisses>>,
+    metrics: Arc<CallMetrics>,
+    correlation_id: Option<Arc<str>>,
+    pub(super) cause_events: bool,
+    pub(super) event_tags: bool,
+    /// Log every request and response at debug level, secrets masked.
+    log_rpc: bool,
+    /// Source and object of the problems fetched, one `problem.get` each.
+    pub(super) event_kinds: Arc<[(EventSource, EventObject)]>,
 }
 
 impl ZbxClient {
@@ -86,70 +134,222 @@ impl ZbxClient {
         connect_timeout: Duration,
         insecure_http: bool,
     ) -> Result<Self> {
-        if base.scheme() != "https" && !insecure_http {
-            return Err(Error::Config(crate::error::ConfigError::InvalidField {
-                field: "zabbix.url",
-                message: "only https URLs are accepted without --insecure".to_string(),
-            }));
-        }
-
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            CONTENT_TYPE,
-            HeaderValue::from_static("application/json-rpc"),
-        );
-        headers.insert(
-            reqwest::header::ACCEPT,
-            HeaderValue::from_static("application/json"),
-        );
-
-        let mut builder = reqwest::Client::builder()
-            .default_headers(headers)
-            .connect_timeout(connect_timeout)
-            .timeout(timeout)
-            .user_agent(concat!("alerting/", env!("CARGO_PKG_VERSION")))
-            .pool_idle_timeout(Duration::from_secs(30));
-
-        if !insecure_http {
-            builder = builder.https_only(true);
-        }
-
-        let http = builder
-            .build()
-            .map_err(|err| ZbxError::Client { source: err })?;
+        check_scheme(&base, !insecure_http, "zabbix.url")?;
+        let http = http_client(
+            timeout,
+            connect_timeout,
+            !insecure_http,
+            &HttpPool::default(),
+        )?;
 
         Ok(Self {
             http,
-            base,
+            bases: Arc::from([base]),
+            active: Arc::default(),
+            https_only: !insecure_http,
             token,
             timeout,
+            connect_timeout,
+            tape: None,
+            throttle: None,
+            host_misses: None,
+            metrics: Arc::default(),
+            correlation_id: None,
+            cause_events: false,
+            event_tags: false,
+            log_rpc: false,
+            event_kinds: Arc::from([(EventSource::Trigger, EventObject::Trigger)]),
         })
     }
 
-    #[allow(clippy::too_many_lines)]
+    /// Other URLs of the same Zabbix (e.g. internal DNS name and VPN
+    /// address), tried in order when the current one cannot be connected to.
+    /// The first that answers is kept until it fails in turn.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a URL uses HTTP while HTTPS is required.
+    pub fn with_fallback_urls(mut self, urls: &[Url]) -> Result<Self> {
+        for url in urls {
+            check_scheme(url, self.https_only, "zabbix.fallback_urls")?;
+        }
+        self.bases = self.bases[..1].iter().chain(urls).cloned().collect();
+        self.active = Arc::default();
+        Ok(self)
+    }
+
+    /// Rebuild the HTTP client with other connection reuse settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the underlying HTTP client fails to build.
+    pub fn with_http_pool(mut self, pool: &HttpPool) -> Result<Self> {
+        self.http = http_client(self.timeout, self.connect_timeout, self.https_only, pool)?;
+        Ok(self)
+    }
+
+    /// Send at most `per_second` requests per second and keep at most
+    /// `in_flight` calls running at once, across all clones of this client,
+    /// so a shared Zabbix frontend is not hammered by bursts.
+    #[must_use]
+    pub fn with_request_limits(
+        mut self,
+        per_second: Option<u32>,
+        in_flight: Option<usize>,
+    ) -> Self {
+        self.throttle = (per_second.is_some() || in_flight.is_some())
+            .then(|| Arc::new(Throttle::new(per_second, in_flight)));
+        self
+    }
+
+    /// Remember for `ttl` the events whose host lookup found nothing or
+    /// failed, and resolve them to no host meanwhile instead of asking again.
+    /// A zero `ttl` looks every event up on every call.
+    #[must_use]
+    pub fn with_host_miss_ttl(mut self, ttl: Duration) -> Self {
+        self.host_misses = (!ttl.is_zero()).then(|| Arc::new(HostMisses::new(ttl)));
+        self
+    }
+
+    /// Save every raw JSON-RPC response under `dir`, one directory per poll.
+    #[must_use]
+    pub fn record_to(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.tape = Some(Arc::new(Tape::new(TapeMode::Record, dir.into())));
+        self
+    }
+
+    /// Serve responses from a directory written by [`Self::record_to`]
+    /// instead of contacting the server.
+    #[must_use]
+    pub fn replay_from(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.tape = Some(Arc::new(Tape::new(TapeMode::Replay, dir.into())));
+        self
+    }
+
+    /// Send `id` as the correlation id of every request made through this
+    /// handle instead of a fresh one per request, so calls made on behalf of
+    /// a notification (e.g. its ack) can be matched with it.
+    #[must_use]
+    pub fn with_correlation_id(mut self, id: &str) -> Self {
+        self.correlation_id = Some(Arc::from(id));
+        self
+    }
+
+    /// Ask `problem.get` for `cause_eventid` so symptom events can be told
+    /// from their cause. Needs Zabbix 6.4 or later.
+    #[must_use]
+    pub const fn with_cause_events(mut self) -> Self {
+        self.cause_events = true;
+        self
+    }
+
+    /// Ask `problem.get` for the tags of each problem event.
+    #[must_use]
+    pub const fn with_event_tags(mut self) -> Self {
+        self.event_tags = true;
+        self
+    }
+
+    /// Log the full body of every JSON-RPC request and response at debug
+    /// level, with the token, passwords and session ids masked.
+    #[must_use]
+    pub const fn with_rpc_logging(mut self) -> Self {
+        self.log_rpc = true;
+        self
+    }
+
+    /// Fetch problems of these event sources, keeping only the listed
+    /// objects when `objects` is not empty, instead of trigger problems
+    /// only. Each source and object pair costs its own `problem.get` call.
+    /// Pairs that leave nothing are ignored.
+    #[must_use]
+    pub fn with_event_filter(mut self, sources: &[EventSource], objects: &[EventObject]) -> Self {
+        let kinds = event_kinds(sources, objects);
+        if !kinds.is_empty() {
+            self.event_kinds = kinds.into();
+        }
+        self
+    }
+
+    /// Mark the start of a poll so recorded responses are grouped per poll.
+    pub fn begin_poll(&self) {
+        if let Some(tape) = &self.tape {
+            tape.begin_poll();
+        }
+    }
+
+    /// `true` once a replay has served every recorded poll.
+    #[must_use]
+    pub fn replay_exhausted(&self) -> bool {
+        self.tape.as_ref().is_some_and(|tape| tape.exhausted())
+    }
+
+    /// Latency histogram and error count per JSON-RPC method since the
+    /// client was built, shared by all its clones.
+    #[must_use]
+    pub fn call_stats(&self) -> Vec<CallStats> {
+        self.metrics.snapshot()
+    }
+
     pub(super) async fn call<T>(&self, method: &str, params: Value) -> Result<T>
     where
         T: DeserializeOwned,
     {
+        let started = Instant::now();
+        let result = self
+            .call_uncounted(method, params)
+            .instrument(debug_span!("rpc", method))
+            .await;
+        self.metrics
+            .record(method, started.elapsed(), result.is_ok());
+        result
+    }
+
+    #[allow(clippy::too_many_lines)]
+    async fn call_uncounted<T>(&self, method: &str, params: Value) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        if let Some(tape) = self.tape.as_ref().filter(|t| t.mode() == TapeMode::Replay) {
+            let body = tape.replay(method, &params)?;
+            let envelope: RpcEnvelope<T> =
+                serde_json::from_slice(&body).map_err(|err| ZbxError::Json {
+                    message: format!("error decoding recorded response: {err}"),
+                })?;
+            return into_result(envelope);
+        }
+        let _permit = match &self.throttle {
+            Some(throttle) => throttle.permit().await,
+            None => None,
+        };
+
         let mut backoff = RetryBackoff::new(self.timeout);
 
         for attempt in 1..=MAX_ATTEMPTS {
-            let correlation_id = Uuid::now_v7().to_string();
+            let correlation_id = self
+                .correlation_id
+                .as_deref()
+                .map_or_else(|| Uuid::now_v7().to_string(), str::to_string);
             let started = Instant::now();
             let payload = RpcRequest {
                 jsonrpc: "2.0",
                 method,
                 params: params.clone(),
                 id: attempt as u64,
-                auth: self.token.expose_secret(),
+                auth: (!UNAUTHENTICATED_METHODS.contains(&method))
+                    .then(|| self.token.expose_secret()),
             };
-            let request = self
-                .http
-                .post(self.base.clone())
-                .header(CORRELATION_HEADER, &correlation_id)
-                .json(&payload);
-
-            let response = match request.send().await {
+            if self.log_rpc {
+                let body = serde_json::to_vec(&payload).unwrap_or_default();
+                debug!(
+                    method,
+                    %correlation_id,
+                    attempt,
+                    request = %redacted(&body, self.token.expose_secret()),
+                    "zabbix request"
+                );
+            }
+            let response = match self.post(&payload, &correlation_id).await {
                 Ok(resp) => resp,
                 Err(err) => {
                     let zerr = ZbxError::from(err);
@@ -229,6 +429,20 @@ impl ZbxClient {
                 }
             };
 
+            if self.log_rpc {
+                debug!(
+                    method,
+                    %correlation_id,
+                    attempt,
+                    %status,
+                    response = %redacted(&body, self.token.expose_secret()),
+                    "zabbix response"
+                );
+            }
+            if let Some(tape) = &self.tape {
+                tape.record(method, &params, &body);
+            }
+
             let envelope: RpcEnvelope<T> = match serde_json::from_slice(&body) {
                 Ok(env) => env,
                 Err(err) => {
@@ -259,19 +473,8 @@ impl ZbxClient {
                 }
             };
 
-            if let Some(err) = envelope.error {
-                let mut message = err.message;
-                if let Some(data) = err.data {
-                    let _ = FmtWrite::write_fmt(&mut message, format_args!(" – {data}"));
-                }
-                return Err(ZbxError::Api {
-                    code: err.code,
-                    message,
-                }
-                .into());
-            }
-
-            if let Some(result) = envelope.result {
+            let result = into_result(envelope);
+            if result.is_ok() {
                 debug!(
                     method,
                     %correlation_id,
@@ -279,11 +482,114 @@ impl ZbxClient {
                     latency_ms = started.elapsed().as_millis(),
                     "zabbix call succeeded"
                 );
-                return Ok(result);
             }
-
-            return Err(ZbxError::MissingField { field: "result" }.into());
+            return result;
         }
         unreachable!("retry loop should have returned before reaching this point")
     }
+
+    /// Send `payload` to the active endpoint, moving on to the next one
+    /// when it cannot be connected to; the one that answers becomes active.
+    async fn post(
+        &self,
+        payload: &RpcRequest<'_>,
+        correlation_id: &str,
+    ) -> reqwest::Result<reqwest::Response> {
+        if let Some(throttle) = &self.throttle {
+            throttle.wait_turn().await;
+        }
+        let count = self.bases.len();
+        let first = self.active.load(Ordering::Relaxed);
+        for offset in 0..count {
+            let index = (first + offset) % count;
+            let url = &self.bases[index];
+            let sent = self
+                .http
+                .post(url.clone())
+                .header(CORRELATION_HEADER, correlation_id)
+                .json(payload)
+                .send()
+                .await;
+            match sent {
+                Err(err) if err.is_connect() && offset + 1 < count => {
+                    warn!(%url, error = %err, "Zabbix URL unreachable, trying the next one");
+                }
+                sent => {
+                    if sent.is_ok() && index != first {
+                        self.active.store(index, Ordering::Relaxed);
+                        info!(%url, "switched to another Zabbix URL");
+                    }
+                    return sent;
+                }
+            }
+        }
+        unreachable!("the client always has at least one URL")
+    }
+}
+
+fn http_client(
+    timeout: Duration,
+    connect_timeout: Duration,
+    https_only: bool,
+    pool: &HttpPool,
+) -> Result<reqwest::Client> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("application/json-rpc"),
+    );
+    headers.insert(
+        reqwest::header::ACCEPT,
+        HeaderValue::from_static("application/json"),
+    );
+
+    let mut builder = reqwest::Client::builder()
+        .default_headers(headers)
+        .connect_timeout(connect_timeout)
+        .timeout(timeout)
+        .user_agent(concat!("alerting/", env!("CARGO_PKG_VERSION")))
+        .pool_idle_timeout(pool.idle_timeout)
+        .pool_max_idle_per_host(pool.max_idle_per_host)
+        .tcp_keepalive(pool.tcp_keepalive);
+
+    if https_only {
+        builder = builder.https_only(true);
+    }
+    // Advertises `Accept-Encoding` and decodes the body transparently;
+    // `problem.get` answers shrink several times over slow VPN links.
+    #[cfg(feature = "compression")]
+    {
+        builder = builder.gzip(true).brotli(true);
+    }
+
+    Ok(builder
+        .build()
+        .map_err(|err| ZbxError::Client { source: err })?)
+}
+
+fn check_scheme(url: &Url, https_only: bool, field: &'static str) -> Result<()> {
+    if url.scheme() != "https" && https_only {
+        return Err(Error::Config(crate::error::ConfigError::InvalidField {
+            field,
+            message: "only https URLs are accepted without --insecure".to_string(),
+        }));
+    }
+    Ok(())
+}
+
+fn into_result<T>(envelope: RpcEnvelope<T>) -> Result<T> {
+    if let Some(err) = envelope.error {
+        let mut message = err.message;
+        if let Some(data) = err.data {
+            let _ = FmtWrite::write_fmt(&mut message, format_args!(" – {data}"));
+        }
+        return Err(ZbxError::Api {
+            code: err.code,
+            message,
+        }
+        .into());
+    }
+    envelope
+        .result
+        .ok_or_else(|| ZbxError::MissingField { field: "result" }.into())
 }
@@ -0,0 +1,207 @@
+use std::time::Duration;
+
+use reqwest::header::{ACCEPT, CONTENT_TYPE, COOKIE, HeaderValue, SET_COOKIE};
+use reqwest::redirect::Policy;
+use secrecy::{ExposeSecret, SecretString};
+use tokio::sync::Mutex;
+use tracing::debug;
+use url::Url;
+
+use crate::Result;
+use crate::error::{ConfigError, Error, ZbxError};
+
+/// Cookie names of a frontend session: `zbx_session` since Zabbix 5.4,
+/// `zbx_sessionid` before.
+const SESSION_COOKIES: &[&str] = &["zbx_session", "zbx_sessionid"];
+const GRAPH_WIDTH: u32 = 600;
+const GRAPH_HEIGHT: u32 = 200;
+
+/// Web frontend session used for pages the JSON-RPC API does not cover,
+/// such as the `chart2.php` graph images.
+///
+/// API tokens are not accepted there, so it signs in with a user name and
+/// password. Downloads may run concurrently; they share one session.
+pub struct Frontend {
+    http: reqwest::Client,
+    base: Url,
+    user: String,
+    password: SecretString,
+    /// Held while signing in, so concurrent downloads sign in once.
+    cookie: Mutex<Option<SecretString>>,
+}
+
+impl Frontend {
+    /// Build a frontend session next to the JSON-RPC endpoint `base`. Nothing
+    /// is sent until the first request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if HTTPS is required but the URL uses HTTP, or if the
+    /// underlying HTTP client fails to build.
+    pub fn new(
+        base: Url,
+        user: String,
+        password: SecretString,
+        timeout: Duration,
+        connect_timeout: Duration,
+        insecure_http: bool,
+    ) -> Result<Self> {
+        if base.scheme() != "https" && !insecure_http {
+            return Err(Error::Config(ConfigError::InvalidField {
+                field: "zabbix.url",
+                message: "only https URLs are accepted without --insecure".to_string(),
+            }));
+        }
+        // The session cookie is set on the redirect answering the login form,
+        // so redirects must not be followed.
+        let http = reqwest::Client::builder()
+            .redirect(Policy::none())
+            .connect_timeout(connect_timeout)
+            .timeout(timeout)
+            .user_agent(concat!("alerting/", env!("CARGO_PKG_VERSION")))
+            .https_only(!insecure_http)
+            .build()
+            .map_err(|err| ZbxError::Client { source: err })?;
+        Ok(Self {
+            http,
+            base,
+            user,
+            password,
+            cookie: Mutex::new(None),
+        })
+    }
+
+    /// Render the last `period` of graph `graph_id` as a PNG. Signs in on
+    /// first use and once more when the session has expired.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when signing in fails or the frontend does not
+    /// answer with an image.
+    pub async fn graph_png(&self, graph_id: &str, period: Duration) -> Result<Vec<u8>> {
+        let cookie = self.session(None).await?;
+        if let Some(png) = self.fetch_graph(&cookie, graph_id, period).await? {
+            return Ok(png);
+        }
+        debug!("frontend session expired, signing in again");
+        let cookie = self.session(Some(&cookie)).await?;
+        self.fetch_graph(&cookie, graph_id, period)
+            .await?
+            .ok_or_else(|| {
+                ZbxError::Frontend {
+                    message: "chart2.php did not return an image".to_string(),
+                }
+                .into()
+            })
+    }
+
+    /// The session cookie, signing in when there is none yet or when it is
+    /// still the `expired` one; another download may have renewed it.
+    async fn session(&self, expired: Option<&SecretString>) -> Result<SecretString> {
+        let mut cookie = self.cookie.lock().await;
+        if let Some(current) = cookie.as_ref() {
+            let renewed =
+                expired.is_none_or(|expired| expired.expose_secret() != current.expose_secret());
+            if renewed {
+                return Ok(current.clone());
+            }
+        }
+        *cookie = None;
+        let fresh = self.login().await?;
+        *cookie = Some(fresh.clone());
+        drop(cookie);
+        Ok(fresh)
+    }
+
+    async fn login(&self) -> Result<SecretString> {
+        let form = [
+            ("name", self.user.as_str()),
+            ("password", self.password.expose_secret()),
+            ("autologin", "1"),
+            ("enter", "Sign in"),
+        ];
+        let response = self
+            .http
+            .post(self.page("index.php")?)
+            .form(&form)
+            .send()
+            .await
+            .map_err(ZbxError::from)?;
+        // A successful sign-in redirects to the dashboard; a refused one
+        // renders the login form again, possibly with a guest session.
+        let cookie = response
+            .headers()
+            .get_all(SET_COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| value.split(';').next())
+            .find(|pair| {
+                pair.split_once('=').is_some_and(|(name, value)| {
+                    SESSION_COOKIES.contains(&name) && !value.is_empty()
+                })
+            })
+            .filter(|_| response.status().is_redirection())
+            .ok_or_else(|| ZbxError::Frontend {
+                message: format!(
+                    "sign-in as {} was refused (HTTP {})",
+                    self.user,
+                    response.status()
+                ),
+            })?;
+        Ok(cookie.to_string().into())
+    }
+
+    /// `None` when the frontend answers with something else than an image,
+    /// which is how it reacts to a missing or expired session.
+    async fn fetch_graph(
+        &self,
+        cookie: &SecretString,
+        graph_id: &str,
+        period: Duration,
+    ) -> Result<Option<Vec<u8>>> {
+        let mut url = self.page("chart2.php")?;
+        url.query_pairs_mut()
+            .append_pair("graphid", graph_id)
+            .append_pair("from", &format!("now-{}s", period.as_secs().max(60)))
+            .append_pair("to", "now")
+            .append_pair("width", &GRAPH_WIDTH.to_string())
+            .append_pair("height", &GRAPH_HEIGHT.to_string())
+            .append_pair("legend", "0");
+        let cookie =
+            HeaderValue::from_str(cookie.expose_secret()).map_err(|err| ZbxError::Frontend {
+                message: format!("unusable session cookie: {err}"),
+            })?;
+        let response = self
+            .http
+            .get(url)
+            .header(COOKIE, cookie)
+            .header(ACCEPT, "image/png")
+            .send()
+            .await
+            .map_err(ZbxError::from)?;
+        let status = response.status();
+        if !status.is_success() && !status.is_redirection() {
+            return Err(ZbxError::HttpStatus { status }.into());
+        }
+        let is_image = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("image/"));
+        if !is_image {
+            return Ok(None);
+        }
+        let body = response.bytes().await.map_err(ZbxError::from)?;
+        Ok(Some(body.to_vec()))
+    }
+
+    /// Frontend page next to the JSON-RPC endpoint.
+    fn page(&self, name: &str) -> Result<Url> {
+        self.base.join(name).map_err(|err| {
+            ZbxError::Frontend {
+                message: format!("cannot build {name} URL: {err}"),
+            }
+            .into()
+        })
+    }
+}
@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Events whose host lookup found no host or failed, not looked up again
+/// until their entry expires so one bad event does not cost a call and a
+/// warning on every poll.
+#[derive(Debug)]
+pub(super) struct HostMisses {
+    ttl: Duration,
+    expiry: Mutex<HashMap<String, Instant>>,
+}
+
+impl HostMisses {
+    pub(super) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            expiry: Mutex::default(),
+        }
+    }
+
+    pub(super) const fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub(super) fn contains(&self, event_id: &str) -> bool {
+        let mut expiry = self.expiry.lock().unwrap_or_else(PoisonError::into_inner);
+        match expiry.get(event_id) {
+            Some(until) if *until > Instant::now() => true,
+            Some(_) => {
+                expiry.remove(event_id);
+                false
+            }
+            None => false,
+        }
+    }
+
+    pub(super) fn insert(&self, event_id: &str) {
+        let now = Instant::now();
+        let mut expiry = self.expiry.lock().unwrap_or_else(PoisonError::into_inner);
+        expiry.retain(|_, until| *until > now);
+        expiry.insert(event_id.to_string(), now + self.ttl);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::HostMisses;
+
+    #[test]
+    fn misses_expire_after_their_ttl() {
+        let misses = HostMisses::new(Duration::from_millis(20));
+        misses.insert("42");
+        assert!(misses.contains("42"));
+        assert!(!misses.contains("43"));
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(!misses.contains("42"));
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Upper bounds (inclusive, in milliseconds) of the latency histogram buckets.
+/// Slower calls land in a final overflow bucket.
+pub const LATENCY_BUCKETS_MS: [u64; 10] = [5, 10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000];
+
+/// Latency histogram and error count of one JSON-RPC method.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct CallStats {
+    pub method: String,
+    pub calls: u64,
+    pub errors: u64,
+    pub total_ms: u64,
+    pub max_ms: u64,
+    /// Counts per [`LATENCY_BUCKETS_MS`] bucket, plus the overflow bucket.
+    pub buckets: [u64; LATENCY_BUCKETS_MS.len() + 1],
+}
+
+impl CallStats {
+    /// Share of calls that ended in an error, between 0 and 1.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn error_rate(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.calls as f64
+        }
+    }
+
+    /// Latency under which `quantile` of the calls completed, rounded up to
+    /// a bucket bound (or the slowest call for the overflow bucket).
+    #[must_use]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    pub fn quantile_ms(&self, quantile: f64) -> u64 {
+        let rank = (self.calls as f64 * quantile.clamp(0.0, 1.0))
+            .ceil()
+            .max(1.0) as u64;
+        let mut seen = 0;
+        for (bound, count) in LATENCY_BUCKETS_MS.iter().zip(&self.buckets) {
+            seen += count;
+            if seen >= rank {
+                return (*bound).min(self.max_ms);
+            }
+        }
+        self.max_ms
+    }
+
+    fn record(&mut self, elapsed: Duration, ok: bool) {
+        let ms = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+        self.calls += 1;
+        if !ok {
+            self.errors += 1;
+        }
+        self.total_ms = self.total_ms.saturating_add(ms);
+        self.max_ms = self.max_ms.max(ms);
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|bound| ms <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.buckets[bucket] += 1;
+    }
+}
+
+/// Per-method call statistics shared by all clones of a client.
+#[derive(Debug, Default)]
+pub(super) struct CallMetrics {
+    methods: Mutex<BTreeMap<String, CallStats>>,
+}
+
+impl CallMetrics {
+    pub(super) fn record(&self, method: &str, elapsed: Duration, ok: bool) {
+        let mut methods = self.methods.lock().unwrap_or_else(PoisonError::into_inner);
+        methods
+            .entry(method.to_string())
+            .or_insert_with(|| CallStats {
+                method: method.to_string(),
+                ..CallStats::default()
+            })
+            .record(elapsed, ok);
+    }
+
+    pub(super) fn snapshot(&self) -> Vec<CallStats> {
+        self.methods
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::CallMetrics;
+
+    #[test]
+    fn records_latency_buckets_and_errors() {
+        let metrics = CallMetrics::default();
+        for ms in [3, 8, 40, 40, 7_000] {
+            metrics.record("problem.get", Duration::from_millis(ms), ms < 5_000);
+        }
+        metrics.record("event.get", Duration::from_millis(12), true);
+
+        let stats = metrics.snapshot();
+        assert_eq!(stats.len(), 2);
+        let problem_get = &stats[1];
+        assert_eq!(problem_get.method, "problem.get");
+        assert_eq!(problem_get.calls, 5);
+        assert_eq!(problem_get.errors, 1);
+        assert_eq!(problem_get.max_ms, 7_000);
+        assert_eq!(problem_get.quantile_ms(0.5), 50);
+        assert_eq!(problem_get.quantile_ms(1.0), 7_000);
+        assert!((problem_get.error_rate() - 0.2).abs() < f64::EPSILON);
+    }
+}
@@ -1,7 +1,19 @@
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub(crate) mod client;
+pub(crate) mod frontend;
+pub(crate) mod host_misses;
+pub(crate) mod metrics;
 pub(crate) mod models;
 pub(crate) mod ops;
+pub(crate) mod preflight;
 pub(crate) mod rpc;
+pub(crate) mod tape;
+pub(crate) mod throttle;
 
-pub use client::ZbxClient;
-pub use models::{HostMeta, Problem};
+pub use client::{HttpPool, ZbxClient};
+pub use frontend::Frontend;
+pub use metrics::{CallStats, LATENCY_BUCKETS_MS};
+pub use models::{Availability, EventRank, EventTag, EventUpdate, HostMeta, Problem};
+pub use ops::ProblemQuery;
+pub use preflight::Preflight;
@@ -11,13 +11,121 @@ pub struct Problem {
     pub name: String,
     pub severity: Severity,
     pub acknowledged: bool,
+    /// Cause of this problem when it is a symptom; requested with
+    /// [`ZbxClient::with_cause_events`](super::ZbxClient::with_cause_events).
+    pub cause_event_id: Option<String>,
+    /// Trigger that raised the problem (`objectid`); `None` for events
+    /// about items, LLD rules and services.
+    pub trigger_id: Option<String>,
+    /// Requested with [`ZbxClient::with_event_tags`](super::ZbxClient::with_event_tags).
+    pub tags: Vec<EventTag>,
+}
+
+/// Place of an event in Zabbix's cause/symptom correlation, set with
+/// [`ZbxClient::rank_event`](super::ZbxClient::rank_event).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum EventRank {
+    Cause,
+    Symptom { cause_event_id: String },
+}
+
+/// Tag of a problem event, e.g. `scope: availability`.
+#[derive(Debug, Clone, Deserialize, Eq, PartialEq)]
+pub struct EventTag {
+    pub tag: String,
+    #[serde(default)]
+    pub value: String,
+}
+
+/// Acknowledgement, message or other update of an event, as listed by
+/// [`ZbxClient::event_updates`](super::ZbxClient::event_updates).
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventUpdate {
+    /// Increases with every update across the server.
+    #[serde(rename = "acknowledgeid", deserialize_with = "deserialize_i64")]
+    pub update_id: i64,
+    #[serde(rename = "userid")]
+    pub user_id: String,
+    #[serde(deserialize_with = "deserialize_i64")]
+    pub clock: i64,
+    /// `event.acknowledge` action bits (2 acknowledge, 4 message...).
+    #[serde(deserialize_with = "deserialize_i64")]
+    pub action: i64,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub surname: Option<String>,
+}
+
+impl EventUpdate {
+    /// Whether the update acknowledged the event.
+    #[must_use]
+    pub const fn acknowledges(&self) -> bool {
+        self.action & 2 != 0
+    }
+
+    /// "First Last", else the login, else the user id.
+    #[must_use]
+    pub fn author(&self) -> String {
+        let full_name = [self.name.as_deref(), self.surname.as_deref()]
+            .into_iter()
+            .flatten()
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !full_name.is_empty() {
+            return full_name;
+        }
+        self.username
+            .clone()
+            .filter(|username| !username.is_empty())
+            .unwrap_or_else(|| format!("user {}", self.user_id))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct EventUpdatesRow {
+    #[serde(rename = "eventid")]
+    pub(crate) event_id: String,
+    #[serde(default)]
+    pub(crate) acknowledges: Vec<EventUpdate>,
 }
 
 #[derive(Debug, Clone)]
 pub struct HostMeta {
+    pub host_id: Option<String>,
     pub host: Option<String>,
     pub display_name: String,
     pub status: Option<u8>,
+    /// Filled in by [`ZbxClient::host_availability`](super::ZbxClient::host_availability).
+    pub availability: Option<Availability>,
+}
+
+/// Whether Zabbix can currently reach a host through its interfaces.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Availability {
+    Available,
+    Unavailable,
+    Unknown,
+}
+
+impl Availability {
+    /// Combine Zabbix availability codes (0 unknown, 1 available,
+    /// 2 unavailable): one unreachable interface makes the host unreachable.
+    pub(super) fn from_codes(codes: impl IntoIterator<Item = u8>) -> Self {
+        codes
+            .into_iter()
+            .fold(Self::Unknown, |acc, code| match (acc, code) {
+                (Self::Unavailable, _) | (_, 2) => Self::Unavailable,
+                (Self::Available, _) | (_, 1) => Self::Available,
+                _ => Self::Unknown,
+            })
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -37,6 +145,12 @@ pub(super) struct RawProblem {
     pub(crate) name: String,
     #[serde(default, deserialize_with = "deserialize_bool")]
     pub(crate) acknowledged: bool,
+    #[serde(default, rename = "cause_eventid")]
+    pub(crate) cause_event_id: Option<String>,
+    #[serde(default, rename = "objectid")]
+    pub(crate) trigger_id: Option<String>,
+    #[serde(default)]
+    pub(crate) tags: Vec<EventTag>,
 }
 
 impl TryFrom<RawProblem> for Problem {
@@ -56,6 +170,9 @@ impl TryFrom<RawProblem> for Problem {
             name: value.name,
             severity,
             acknowledged: value.acknowledged,
+            cause_event_id: value.cause_event_id.filter(|id| id != "0"),
+            trigger_id: value.trigger_id,
+            tags: value.tags,
         })
     }
 }
@@ -68,6 +185,8 @@ pub(super) struct EventWithHosts {
 
 #[derive(Debug, Deserialize)]
 pub(super) struct HostRow {
+    #[serde(default, rename = "hostid")]
+    host_id: Option<String>,
     #[serde(default)]
     host: Option<String>,
     #[serde(default)]
@@ -78,19 +197,105 @@ pub(super) struct HostRow {
 
 impl From<HostRow> for HostMeta {
     fn from(value: HostRow) -> Self {
-        let HostRow { host, name, status } = value;
+        let HostRow {
+            host_id,
+            host,
+            name,
+            status,
+        } = value;
         let display_name = match (&name, &host) {
             (Some(value), _) | (None, Some(value)) => value.clone(),
             (None, None) => "<unknown host>".to_string(),
         };
         Self {
+            host_id,
             host,
             display_name,
             status,
+            availability: None,
         }
     }
 }
 
+/// Host as returned by `host.get`; the proxy field was renamed from
+/// `proxy_hostid` to `proxyid` in Zabbix 7.0.
+#[derive(Debug, Deserialize)]
+pub(super) struct HostProxyRow {
+    #[serde(rename = "hostid")]
+    pub(crate) host_id: String,
+    #[serde(default, rename = "proxy_hostid", alias = "proxyid")]
+    pub(crate) proxy_id: Option<String>,
+}
+
+/// Business service as returned by `service.get` with its root-cause
+/// problem events.
+#[derive(Debug, Deserialize)]
+pub(super) struct ServiceRow {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) problem_events: Vec<ServiceEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct TriggerRow {
+    #[serde(rename = "triggerid")]
+    pub(crate) trigger_id: String,
+    pub(crate) expression: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct HostIdRow {
+    #[serde(rename = "hostid")]
+    pub(crate) host_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct ItemIdRow {
+    #[serde(rename = "itemid")]
+    pub(crate) item_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct GraphIdRow {
+    #[serde(rename = "graphid")]
+    pub(crate) graph_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct ServiceEvent {
+    #[serde(rename = "eventid")]
+    pub(crate) event_id: String,
+}
+
+/// Host as returned by `host.get` with its interfaces. `active_available`
+/// (Zabbix 6.4+) covers active agent checks, which have no interface.
+#[derive(Debug, Deserialize)]
+pub(super) struct HostAvailabilityRow {
+    #[serde(rename = "hostid")]
+    pub(crate) host_id: String,
+    #[serde(default, deserialize_with = "deserialize_opt_u8")]
+    pub(crate) active_available: Option<u8>,
+    #[serde(default)]
+    pub(crate) interfaces: Vec<InterfaceRow>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct InterfaceRow {
+    #[serde(default, deserialize_with = "deserialize_opt_u8")]
+    pub(crate) available: Option<u8>,
+}
+
+impl HostAvailabilityRow {
+    pub(super) fn availability(&self) -> Availability {
+        Availability::from_codes(
+            self.interfaces
+                .iter()
+                .filter_map(|interface| interface.available)
+                .chain(self.active_available),
+        )
+    }
+}
+
 fn deserialize_i64<'de, D>(de: D) -> std::result::Result<i64, D::Error>
 where
     D: serde::Deserializer<'de>,
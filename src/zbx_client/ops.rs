@@ -1,16 +1,40 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use serde_json::{Value, json};
 use tokio::sync::Semaphore;
 use tokio::task::JoinSet;
-use tracing::warn;
+use tracing::{Instrument, debug, warn};
 
 use crate::Result;
 use crate::error::Error;
-use crate::types::AckFilter;
+use crate::types::{AckFilter, EventObject, EventSource, Severity};
 
 use super::ZbxClient;
-use super::models::{EventWithHosts, HostMeta, Problem, RawProblem};
+use super::models::{
+    Availability, EventRank, EventUpdate, EventUpdatesRow, EventWithHosts, GraphIdRow,
+    HostAvailabilityRow, HostIdRow, HostMeta, HostProxyRow, ItemIdRow, Problem, RawProblem,
+    ServiceRow, TriggerRow,
+};
+
+/// `event.acknowledge` action bits.
+const ACTION_ACK: u32 = 2;
+const ACTION_MESSAGE: u32 = 4;
+const ACTION_UNACK: u32 = 16;
+const ACTION_SUPPRESS: u32 = 32;
+const ACTION_CHANGE_TO_CAUSE: u32 = 128;
+const ACTION_CHANGE_TO_SYMPTOM: u32 = 256;
+
+/// Filters of [`ZbxClient::find_problems`]; empty lists do not filter.
+#[derive(Debug, Clone)]
+pub struct ProblemQuery {
+    pub ack: AckFilter,
+    pub event_ids: Vec<String>,
+    pub host_ids: Vec<String>,
+    pub severities: Vec<Severity>,
+    /// Only problems that started at or before this Unix time.
+    pub started_before: Option<i64>,
+}
 
 impl ZbxClient {
     /// Fetch all active problems.
@@ -20,24 +44,227 @@ impl ZbxClient {
     /// Returns an error when the RPC call fails, the response cannot be
     /// deserialised, or it misses expected fields.
     pub async fn active_problems(&self, limit: u32, ack: AckFilter) -> Result<Vec<Problem>> {
+        self.active_problems_paged(limit, 1, ack).await
+    }
+
+    /// Fetch active problems newest first, `limit` per call, following with
+    /// older pages while they come back full, up to `max_pages` calls per
+    /// event source and object.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when any RPC call fails, a response cannot be
+    /// deserialised, or it misses expected fields.
+    pub async fn active_problems_paged(
+        &self,
+        limit: u32,
+        max_pages: u32,
+        ack: AckFilter,
+    ) -> Result<Vec<Problem>> {
+        self.paged_problems(limit, max_pages, ack, None).await
+    }
+
+    /// Like [`Self::active_problems_paged`], but only the problems of
+    /// `host_ids`, so problems of other hosts do not use up `limit`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when any RPC call fails, a response cannot be
+    /// deserialised, or it misses expected fields.
+    pub async fn active_problems_on_hosts(
+        &self,
+        host_ids: &[String],
+        limit: u32,
+        max_pages: u32,
+        ack: AckFilter,
+    ) -> Result<Vec<Problem>> {
+        // An empty `hostids` would not filter at all.
+        if host_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.paged_problems(limit, max_pages, ack, Some(host_ids))
+            .await
+    }
+
+    async fn paged_problems(
+        &self,
+        limit: u32,
+        max_pages: u32,
+        ack: AckFilter,
+        host_ids: Option<&[String]>,
+    ) -> Result<Vec<Problem>> {
+        let mut problems = Vec::new();
+        for &kind in self.event_kinds.iter() {
+            let mut params = self.problem_params(Some(limit), ack, kind);
+            if let Some(host_ids) = host_ids {
+                params["hostids"] = json!(host_ids);
+            }
+            problems.extend(self.problem_pages(params, limit, max_pages, kind).await?);
+        }
+        if self.event_kinds.len() > 1 {
+            problems.sort_by_key(|problem| std::cmp::Reverse(event_order(problem)));
+        }
+        Ok(problems)
+    }
+
+    async fn problem_pages(
+        &self,
+        mut params: Value,
+        limit: u32,
+        max_pages: u32,
+        kind: (EventSource, EventObject),
+    ) -> Result<Vec<Problem>> {
+        let mut problems = Vec::new();
+        for page in 1..=max_pages.max(1) {
+            let batch = self.problems(params.clone(), kind).await?;
+            let full = batch.len() >= usize::try_from(limit).unwrap_or(usize::MAX);
+            // Pages are sorted by event id, newest first: the next one starts
+            // below the oldest event of this one.
+            let cursor = batch
+                .last()
+                .and_then(|problem| problem.event_id.parse::<u64>().ok())
+                .and_then(|id| id.checked_sub(1));
+            problems.extend(batch);
+            let Some(cursor) = cursor.filter(|_| full) else {
+                break;
+            };
+            if page == max_pages {
+                debug!(
+                    pages = max_pages,
+                    count = problems.len(),
+                    "more active problems than fetched, older ones ignored"
+                );
+                break;
+            }
+            params["eventid_till"] = json!(cursor.to_string());
+        }
+        Ok(problems)
+    }
+
+    /// Fetch every active problem matching `query`, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the RPC call fails, the response cannot be
+    /// deserialised, or it misses expected fields.
+    pub async fn find_problems(&self, query: &ProblemQuery) -> Result<Vec<Problem>> {
+        let mut problems = Vec::new();
+        for &kind in self.event_kinds.iter() {
+            problems.extend(self.find_problems_of(query, kind).await?);
+        }
+        if self.event_kinds.len() > 1 {
+            problems.sort_by_key(event_order);
+        }
+        Ok(problems)
+    }
+
+    async fn find_problems_of(
+        &self,
+        query: &ProblemQuery,
+        kind: (EventSource, EventObject),
+    ) -> Result<Vec<Problem>> {
+        let mut params = self.problem_params(None, query.ack, kind);
+        params["sortorder"] = json!("ASC");
+        if !query.event_ids.is_empty() {
+            params["eventids"] = json!(query.event_ids);
+        }
+        if !query.host_ids.is_empty() {
+            params["hostids"] = json!(query.host_ids);
+        }
+        if !query.severities.is_empty() {
+            let codes: Vec<i64> = query
+                .severities
+                .iter()
+                .map(|severity| severity.as_zabbix_code())
+                .collect();
+            params["severities"] = json!(codes);
+        }
+        if let Some(clock) = query.started_before {
+            params["time_till"] = json!(clock);
+        }
+        self.problems(params, kind).await
+    }
+
+    /// Ids of the hosts whose technical name is `name`, or failing that
+    /// whose visible name is.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the RPC call fails or the response cannot be
+    /// deserialised.
+    pub async fn host_ids_by_name(&self, name: &str) -> Result<Vec<String>> {
+        for field in ["host", "name"] {
+            let params = json!({
+                "output": ["hostid"],
+                "filter": { field: [name] },
+            });
+            let raw: Vec<HostIdRow> = self.call("host.get", params).await?;
+            if !raw.is_empty() {
+                return Ok(raw.into_iter().map(|row| row.host_id).collect());
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    fn problem_params(
+        &self,
+        limit: Option<u32>,
+        ack: AckFilter,
+        (source, object): (EventSource, EventObject),
+    ) -> Value {
         let mut params = json!({
-            "output": ["eventid","name","severity","clock","lastchange","acknowledged"],
+            "output": ["eventid","name","severity","clock","lastchange","acknowledged","objectid"],
             "recent": false,
-            "limit": limit,
             "sortfield": ["eventid"],
             "sortorder": "DESC"
         });
+        if let Some(limit) = limit {
+            params["limit"] = json!(limit);
+        }
+        // Trigger events of triggers are what `problem.get` returns unasked.
+        if (source, object) != (EventSource::Trigger, EventObject::Trigger) {
+            params["source"] = json!(source.as_zabbix_code());
+            params["object"] = json!(object.as_zabbix_code());
+        }
+        if self.cause_events
+            && let Some(output) = params["output"].as_array_mut()
+        {
+            output.push(json!("cause_eventid"));
+        }
+        if self.event_tags {
+            params["selectTags"] = json!(["tag", "value"]);
+        }
         match ack {
             AckFilter::Acked => params["acknowledged"] = json!(true),
             AckFilter::Unacked => params["acknowledged"] = json!(false),
             AckFilter::All => {}
         }
+        params
+    }
 
+    async fn problems(
+        &self,
+        params: Value,
+        (source, object): (EventSource, EventObject),
+    ) -> Result<Vec<Problem>> {
         let raw: Vec<RawProblem> = self.call("problem.get", params).await?;
-        let problems = raw
+        let mut problems = raw
             .into_iter()
-            .map(Problem::try_from)
+            .map(|mut raw| {
+                // Internal and service events are "not classified"; show
+                // them as the least severe level instead of failing the poll.
+                if source != EventSource::Trigger && raw.severity == 0 {
+                    raw.severity = 1;
+                }
+                Problem::try_from(raw)
+            })
             .collect::<std::result::Result<Vec<_>, Error>>()?;
+        // `objectid` of item and LLD rule events is not a trigger.
+        if object != EventObject::Trigger {
+            for problem in &mut problems {
+                problem.trigger_id = None;
+            }
+        }
         Ok(problems)
     }
 
@@ -47,7 +274,19 @@ impl ZbxClient {
     ///
     /// Propagates errors coming from the underlying RPC call.
     pub async fn ack_event(&self, eventid: &str, message: Option<String>) -> Result<()> {
-        self.event_update(eventid, true, message).await
+        self.event_update(&[eventid], ACTION_ACK, message).await
+    }
+
+    /// Acknowledge several Zabbix events in one `event.acknowledge` call.
+    ///
+    /// # Errors
+    ///
+    /// Propagates errors coming from the underlying RPC call.
+    pub async fn ack_events(&self, eventids: &[&str], message: Option<String>) -> Result<()> {
+        if eventids.is_empty() {
+            return Ok(());
+        }
+        self.event_update(eventids, ACTION_ACK, message).await
     }
 
     /// Remove an acknowledgement from a Zabbix event.
@@ -56,14 +295,70 @@ impl ZbxClient {
     ///
     /// Propagates errors coming from the underlying RPC call.
     pub async fn unack_event(&self, eventid: &str, message: Option<String>) -> Result<()> {
-        self.event_update(eventid, false, message).await
+        self.event_update(&[eventid], ACTION_UNACK, message).await
+    }
+
+    /// Add a message to a Zabbix event without acknowledging it. An empty
+    /// message is not sent.
+    ///
+    /// # Errors
+    ///
+    /// Propagates errors coming from the underlying RPC call.
+    pub async fn comment_event(&self, eventid: &str, message: &str) -> Result<()> {
+        if message.trim().is_empty() {
+            return Ok(());
+        }
+        self.event_update(&[eventid], 0, Some(message.to_string()))
+            .await
+    }
+
+    /// Suppress a problem until the Unix time `until`, or until it is
+    /// unsuppressed when `None`.
+    ///
+    /// # Errors
+    ///
+    /// Propagates errors coming from the underlying RPC call.
+    pub async fn suppress_event(
+        &self,
+        eventid: &str,
+        until: Option<i64>,
+        message: Option<String>,
+    ) -> Result<()> {
+        let params = json!({
+            "eventids": [eventid],
+            // 0 suppresses indefinitely.
+            "suppress_until": until.unwrap_or(0),
+        });
+        self.acknowledge(params, ACTION_SUPPRESS, message).await
+    }
+
+    /// Turn an event into a cause, or into a symptom of another event
+    /// (Zabbix 6.4 and later).
+    ///
+    /// # Errors
+    ///
+    /// Propagates errors coming from the underlying RPC call, including the
+    /// server's refusal on versions without event ranking.
+    pub async fn rank_event(&self, eventid: &str, rank: &EventRank) -> Result<()> {
+        let mut params = json!({
+            "eventids": [eventid],
+        });
+        let action = match rank {
+            EventRank::Cause => ACTION_CHANGE_TO_CAUSE,
+            EventRank::Symptom { cause_event_id } => {
+                params["cause_eventid"] = json!(cause_event_id);
+                ACTION_CHANGE_TO_SYMPTOM
+            }
+        };
+        self.acknowledge(params, action, None).await
     }
 
     /// Resolve host metadata for the provided events.
     ///
     /// # Errors
     ///
-    /// Returns an error when the RPC call fails for any of the events.
+    /// Returns an error when the RPC call fails for any of the events, unless
+    /// [`Self::with_host_miss_ttl`] is set: such events then have no host.
     pub async fn resolve_hosts(
         &self,
         event_ids: &[String],
@@ -76,13 +371,16 @@ impl ZbxClient {
         for (idx, event_id) in event_ids.iter().cloned().enumerate() {
             let client = self.clone();
             let semaphore = Arc::clone(&semaphore);
-            tasks.spawn(async move {
-                let Ok(_permit) = semaphore.acquire_owned().await else {
-                    return (idx, Ok(None));
-                };
-                let res = client.host_meta_for_event(&event_id).await;
-                (idx, res)
-            });
+            tasks.spawn(
+                async move {
+                    let Ok(_permit) = semaphore.acquire_owned().await else {
+                        return (idx, Ok(None));
+                    };
+                    let res = client.host_meta_or_miss(&event_id).await;
+                    (idx, res)
+                }
+                .in_current_span(),
+            );
         }
 
         let mut out: Vec<Option<HostMeta>> = vec![None; event_ids.len()];
@@ -99,25 +397,254 @@ impl ZbxClient {
         Ok(out)
     }
 
-    async fn event_update(&self, eventid: &str, ack: bool, message: Option<String>) -> Result<()> {
-        let mut params = json!({
-            "eventids": [eventid],
+    /// Look up the proxy monitoring each host, keyed by host id. Hosts
+    /// monitored directly by the server map to `"0"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the RPC call fails or the response cannot be
+    /// deserialised.
+    pub async fn host_proxies(&self, host_ids: &[String]) -> Result<HashMap<String, String>> {
+        if host_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        // `extend` rather than a field list: the proxy field name depends on
+        // the server version and unknown output fields are rejected.
+        let params = json!({
+            "output": "extend",
+            "hostids": host_ids,
         });
-        let action = if ack { 2 } else { 16 };
-        params["action"] = json!(action);
+        let raw: Vec<HostProxyRow> = self.call("host.get", params).await?;
+        Ok(raw
+            .into_iter()
+            .map(|row| (row.host_id, row.proxy_id.unwrap_or_else(|| "0".to_string())))
+            .collect())
+    }
+
+    /// Ids of the hosts monitored by `proxy_ids`; proxy id `"0"` stands for
+    /// the server itself, which Zabbix matches against hosts without a proxy.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the RPC call fails or the response cannot be
+    /// deserialised.
+    pub async fn proxy_host_ids(&self, proxy_ids: &[String]) -> Result<Vec<String>> {
+        if proxy_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let params = json!({
+            "output": ["hostid"],
+            "proxyids": proxy_ids,
+        });
+        let raw: Vec<HostIdRow> = self.call("host.get", params).await?;
+        Ok(raw.into_iter().map(|row| row.host_id).collect())
+    }
+
+    /// Look up whether each host is reachable through its interfaces, keyed
+    /// by host id.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the RPC call fails or the response cannot be
+    /// deserialised.
+    pub async fn host_availability(
+        &self,
+        host_ids: &[String],
+    ) -> Result<HashMap<String, Availability>> {
+        if host_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let params = json!({
+            "output": "extend",
+            "hostids": host_ids,
+            "selectInterfaces": ["type", "available"],
+        });
+        let raw: Vec<HostAvailabilityRow> = self.call("host.get", params).await?;
+        Ok(raw
+            .into_iter()
+            .map(|row| {
+                let availability = row.availability();
+                (row.host_id, availability)
+            })
+            .collect())
+    }
+
+    /// Map each problem event to the names of the business services it
+    /// impacts, sorted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the RPC call fails or the response cannot be
+    /// deserialised.
+    pub async fn services_by_event(&self) -> Result<HashMap<String, Vec<String>>> {
+        let params = json!({
+            "output": ["serviceid", "name"],
+            "selectProblemEvents": ["eventid"],
+        });
+        let raw: Vec<ServiceRow> = self.call("service.get", params).await?;
+        let mut by_event: HashMap<String, Vec<String>> = HashMap::new();
+        for service in raw {
+            for event in service.problem_events {
+                by_event
+                    .entry(event.event_id)
+                    .or_default()
+                    .push(service.name.clone());
+            }
+        }
+        for names in by_event.values_mut() {
+            names.sort_unstable();
+            names.dedup();
+        }
+        Ok(by_event)
+    }
+
+    /// Acknowledgements and messages of each event, oldest first, keyed by
+    /// event id.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the RPC call fails or the response cannot be
+    /// deserialised.
+    pub async fn event_updates(
+        &self,
+        event_ids: &[String],
+    ) -> Result<HashMap<String, Vec<EventUpdate>>> {
+        if event_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let params = json!({
+            "output": ["eventid"],
+            "eventids": event_ids,
+            "select_acknowledges": "extend",
+        });
+        let raw: Vec<EventUpdatesRow> = self.call("event.get", params).await?;
+        Ok(raw
+            .into_iter()
+            .map(|mut row| {
+                row.acknowledges.sort_by_key(|update| update.update_id);
+                (row.event_id, row.acknowledges)
+            })
+            .collect())
+    }
+
+    /// Look up the expression of each trigger, with host and item keys
+    /// expanded, keyed by trigger id.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the RPC call fails or the response cannot be
+    /// deserialised.
+    pub async fn trigger_expressions(
+        &self,
+        trigger_ids: &[String],
+    ) -> Result<HashMap<String, String>> {
+        if trigger_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let params = json!({
+            "output": ["triggerid", "expression"],
+            "triggerids": trigger_ids,
+            "expandExpression": true,
+        });
+        let raw: Vec<TriggerRow> = self.call("trigger.get", params).await?;
+        Ok(raw
+            .into_iter()
+            .map(|row| (row.trigger_id, row.expression))
+            .collect())
+    }
+
+    /// Find a graph showing one of the items used by `trigger_id`, for
+    /// [`Frontend::graph_png`](super::Frontend::graph_png).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when either RPC call fails or a response cannot be
+    /// deserialised.
+    pub async fn trigger_graph(&self, trigger_id: &str) -> Result<Option<String>> {
+        let params = json!({
+            "output": ["itemid"],
+            "triggerids": [trigger_id],
+        });
+        let items: Vec<ItemIdRow> = self.call("item.get", params).await?;
+        if items.is_empty() {
+            return Ok(None);
+        }
+        let item_ids: Vec<String> = items.into_iter().map(|item| item.item_id).collect();
+        let params = json!({
+            "output": ["graphid"],
+            "itemids": item_ids,
+            "sortfield": "graphid",
+            "limit": 1,
+        });
+        let graphs: Vec<GraphIdRow> = self.call("graph.get", params).await?;
+        Ok(graphs.into_iter().next().map(|graph| graph.graph_id))
+    }
+
+    async fn event_update(
+        &self,
+        eventids: &[&str],
+        action: u32,
+        message: Option<String>,
+    ) -> Result<()> {
+        let params = json!({
+            "eventids": eventids,
+        });
+        self.acknowledge(params, action, message).await
+    }
+
+    /// `event.acknowledge` with `action`, plus the message bit when a
+    /// non-empty `message` is given.
+    async fn acknowledge(
+        &self,
+        mut params: Value,
+        mut action: u32,
+        message: Option<String>,
+    ) -> Result<()> {
         if let Some(msg) = message.as_deref()
             && !msg.is_empty()
         {
             params["message"] = json!(msg);
-            params["action"] = json!(action + 4);
+            action |= ACTION_MESSAGE;
         }
+        params["action"] = json!(action);
         let _: Value = self.call("event.acknowledge", params).await?;
         Ok(())
     }
 
+    /// [`Self::host_meta_for_event`] through the miss cache: an event found
+    /// without host, or whose lookup failed, resolves to `None` until its
+    /// entry expires.
+    async fn host_meta_or_miss(&self, eventid: &str) -> Result<Option<HostMeta>> {
+        let Some(misses) = &self.host_misses else {
+            return self.host_meta_for_event(eventid).await;
+        };
+        if misses.contains(eventid) {
+            return Ok(None);
+        }
+        match self.host_meta_for_event(eventid).await {
+            Ok(Some(host)) => Ok(Some(host)),
+            Ok(None) => {
+                debug!(eventid, "event has no host");
+                misses.insert(eventid);
+                Ok(None)
+            }
+            Err(err) => {
+                warn!(
+                    eventid,
+                    error_code = err.code(),
+                    error = %err,
+                    retry_in = %humantime::format_duration(misses.ttl()),
+                    "host lookup failed"
+                );
+                misses.insert(eventid);
+                Ok(None)
+            }
+        }
+    }
+
     async fn host_meta_for_event(&self, eventid: &str) -> Result<Option<HostMeta>> {
         let params = json!({
-            "selectHosts": ["host", "name", "status"],
+            "selectHosts": ["hostid", "host", "name", "status"],
             "eventids": [eventid],
         });
         let raw: Vec<EventWithHosts> = self.call("event.get", params).await?;
@@ -128,3 +655,9 @@ impl ZbxClient {
             .map(HostMeta::from))
     }
 }
+
+/// Problems of several event sources are merged in event id order, which is
+/// the order they were raised in.
+fn event_order(problem: &Problem) -> u64 {
+    problem.event_id.parse().unwrap_or(0)
+}
@@ -0,0 +1,147 @@
+use secrecy::ExposeSecret;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::Result;
+use crate::error::{Error, ZbxError};
+use crate::types::AckFilter;
+
+use super::ZbxClient;
+
+/// Methods the API rejects when an `auth` value is supplied.
+pub(super) const UNAUTHENTICATED_METHODS: &[&str] =
+    &["apiinfo.version", "user.checkAuthentication"];
+
+/// Zabbix user type of super administrators, who bypass role rules.
+const SUPER_ADMIN: &str = "3";
+const ACK_ACTION: &str = "acknowledge_problems";
+
+/// Outcome of [`ZbxClient::preflight`].
+#[derive(Debug, Clone)]
+pub struct Preflight {
+    pub api_version: String,
+    /// Whether the token's role allows acknowledging problems. `None` when the
+    /// server does not expose the role (e.g. before Zabbix 6.0).
+    pub can_acknowledge: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuthenticatedUser {
+    #[serde(rename = "type")]
+    user_type: String,
+    roleid: String,
+    userid: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Role {
+    rules: RoleRules,
+}
+
+#[derive(Debug, Deserialize)]
+struct RoleRules {
+    #[serde(default)]
+    actions: Vec<RoleAction>,
+    #[serde(rename = "actions.default_access", default)]
+    default_access: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RoleAction {
+    name: String,
+    status: String,
+}
+
+impl ZbxClient {
+    /// Check the API is reachable and the token usable before polling.
+    ///
+    /// Calls `apiinfo.version`, a one-item `problem.get`, and looks up the
+    /// token's role to tell whether `event.acknowledge` would be allowed,
+    /// without acknowledging anything.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ZbxError::Preflight`] naming the failed step when the API
+    /// cannot be reached or the token cannot read problems.
+    pub async fn preflight(&self) -> Result<Preflight> {
+        let api_version: String = self
+            .call("apiinfo.version", json!([]))
+            .await
+            .map_err(|err| preflight_error("cannot reach the Zabbix API", err))?;
+
+        self.active_problems(1, AckFilter::All)
+            .await
+            .map_err(|err| {
+                let message = if is_auth_error(&err) {
+                    "token is invalid or expired"
+                } else {
+                    "token cannot read problems (problem.get)"
+                };
+                preflight_error(message, err)
+            })?;
+
+        Ok(Preflight {
+            api_version,
+            can_acknowledge: self.can_acknowledge().await,
+        })
+    }
+
+    /// Id of the user the token belongs to, to tell its own updates from
+    /// those of others.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when `user.checkAuthentication` fails, e.g. before
+    /// Zabbix 6.4, which cannot check API tokens.
+    pub async fn token_user_id(&self) -> Result<String> {
+        Ok(self.authenticated_user().await?.userid)
+    }
+
+    async fn authenticated_user(&self) -> Result<AuthenticatedUser> {
+        self.call(
+            "user.checkAuthentication",
+            json!({ "token": self.token.expose_secret() }),
+        )
+        .await
+    }
+
+    async fn can_acknowledge(&self) -> Option<bool> {
+        let user = self.authenticated_user().await.ok()?;
+        if user.user_type == SUPER_ADMIN {
+            return Some(true);
+        }
+
+        let roles: Vec<Role> = self
+            .call(
+                "role.get",
+                json!({
+                    "roleids": [user.roleid],
+                    "selectRules": ["actions", "actions.default_access"],
+                }),
+            )
+            .await
+            .ok()?;
+        let access = roles.into_iter().next()?.rules;
+        let allowed = access
+            .actions
+            .iter()
+            .find(|action| action.name == ACK_ACTION)
+            .map_or_else(
+                || access.default_access.as_deref() == Some("1"),
+                |action| action.status == "1",
+            );
+        Some(allowed)
+    }
+}
+
+fn is_auth_error(err: &Error) -> bool {
+    matches!(err, Error::Zabbix(zerr) if zerr.is_auth_error())
+}
+
+fn preflight_error(message: &str, source: Error) -> Error {
+    ZbxError::Preflight {
+        message: message.to_string(),
+        source: Box::new(source),
+    }
+    .into()
+}
@@ -2,6 +2,9 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 const BODY_PREVIEW_LIMIT: usize = 256;
+const REDACTED: &str = "<redacted>";
+/// Object keys whose values never reach the RPC log.
+const SECRET_KEYS: &[&str] = &["auth", "token", "password", "sessionid"];
 
 #[derive(Debug, Deserialize)]
 pub(super) struct RpcEnvelope<T> {
@@ -27,7 +30,41 @@ pub(super) struct RpcRequest<'a> {
     pub(crate) method: &'a str,
     pub(crate) params: Value,
     pub(crate) id: u64,
-    pub(crate) auth: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) auth: Option<&'a str>,
+}
+
+/// `body` as written to the RPC log: secret fields and any occurrence of
+/// `token` are masked.
+pub(super) fn redacted(body: &[u8], token: &str) -> String {
+    let rendered = serde_json::from_slice::<Value>(body).map_or_else(
+        |_| String::from_utf8_lossy(body).replace('\n', "\\n"),
+        |mut value| {
+            mask(&mut value);
+            value.to_string()
+        },
+    );
+    if token.is_empty() {
+        rendered
+    } else {
+        rendered.replace(token, REDACTED)
+    }
+}
+
+fn mask(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                if SECRET_KEYS.contains(&key.as_str()) && !field.is_null() {
+                    *field = Value::from(REDACTED);
+                } else {
+                    mask(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(mask),
+        _ => {}
+    }
 }
 
 pub(super) fn body_preview(body: &[u8]) -> String {
@@ -41,3 +78,23 @@ pub(super) fn body_preview(body: &[u8]) -> String {
     }
     preview.replace('\n', "\\n")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::redacted;
+
+    #[test]
+    fn rpc_log_masks_secrets() {
+        let request = br#"{"method":"user.login","params":{"username":"ops","password":"hunter2"},"auth":"tok-123"}"#;
+        let logged = redacted(request, "tok-123");
+        assert!(logged.contains(r#""username":"ops""#));
+        assert!(!logged.contains("hunter2"));
+        assert!(!logged.contains("tok-123"));
+
+        let response = b"<html>bad token tok-123</html>";
+        assert_eq!(
+            redacted(response, "tok-123"),
+            "<html>bad token <redacted></html>"
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde_json::Value;
+use tracing::warn;
+
+use crate::error::ZbxError;
+
+/// Whether responses are written to or served from the tape directory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum TapeMode {
+    Record,
+    Replay,
+}
+
+/// Raw JSON-RPC responses stored as `<dir>/<poll>/<method>-<params hash>.json`,
+/// one numbered directory per poll.
+#[derive(Debug)]
+pub(super) struct Tape {
+    mode: TapeMode,
+    dir: PathBuf,
+    poll: AtomicUsize,
+    recorded_polls: usize,
+}
+
+impl Tape {
+    pub(super) fn new(mode: TapeMode, dir: PathBuf) -> Self {
+        let recorded_polls = match mode {
+            TapeMode::Record => 0,
+            TapeMode::Replay => count_polls(&dir),
+        };
+        Self {
+            mode,
+            dir,
+            poll: AtomicUsize::new(0),
+            recorded_polls,
+        }
+    }
+
+    pub(super) const fn mode(&self) -> TapeMode {
+        self.mode
+    }
+
+    pub(super) fn begin_poll(&self) {
+        self.poll.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(super) fn exhausted(&self) -> bool {
+        self.mode == TapeMode::Replay && self.poll.load(Ordering::SeqCst) >= self.recorded_polls
+    }
+
+    pub(super) fn record(&self, method: &str, params: &Value, body: &[u8]) {
+        let path = self.path_for(method, params);
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(&path, body));
+        if let Err(err) = written {
+            warn!(path = %path.display(), error = %err, "failed to record zabbix response");
+        }
+    }
+
+    pub(super) fn replay(&self, method: &str, params: &Value) -> Result<Vec<u8>, ZbxError> {
+        let path = self.path_for(method, params);
+        std::fs::read(&path).map_err(|source| ZbxError::Tape { path, source })
+    }
+
+    fn path_for(&self, method: &str, params: &Value) -> PathBuf {
+        let poll = self.poll.load(Ordering::SeqCst).max(1);
+        self.dir
+            .join(format!("{poll:06}"))
+            .join(format!("{}.json", entry_name(method, params)))
+    }
+}
+
+/// File stem for a call: the method, plus a hash of its whole parameters
+/// so pages, lookups of the same ids with other fields and concurrent
+/// per-event calls map to distinct files.
+fn entry_name(method: &str, params: &Value) -> String {
+    let mut canonical = String::new();
+    write_canonical(params, &mut canonical);
+    let name = format!("{method}-{:016x}", fnv1a(canonical.as_bytes()));
+    name.chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '.' | '-' | '_') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Serialise `value` with object keys sorted, whatever the map order.
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Array(items) => {
+            out.push('[');
+            for (idx, item) in items.iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (idx, (key, item)) in entries.into_iter().enumerate() {
+                if idx > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(item, out);
+            }
+            out.push('}');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+/// 64-bit FNV-1a, stable across builds unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn count_polls(dir: &Path) -> usize {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .filter_map(std::result::Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.parse::<usize>().is_ok())
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::entry_name;
+    use serde_json::{Value, json};
+
+    #[test]
+    fn entry_name_depends_on_all_params() {
+        let first = entry_name("problem.get", &json!({"limit": 5}));
+        assert!(first.starts_with("problem.get-"));
+        assert_ne!(
+            first,
+            entry_name("problem.get", &json!({"limit": 5, "eventid_till": "41"}))
+        );
+        assert_ne!(
+            entry_name("host.get", &json!({"hostids": ["1"], "output": "extend"})),
+            entry_name(
+                "host.get",
+                &json!({"hostids": ["1"], "output": "extend", "selectInterfaces": ["type"]})
+            )
+        );
+    }
+
+    #[test]
+    fn entry_name_ignores_key_order() {
+        let mut reordered = serde_json::Map::new();
+        reordered.insert("output".into(), json!("extend"));
+        reordered.insert("hostids".into(), json!(["1"]));
+        assert_eq!(
+            entry_name("host.get", &Value::Object(reordered)),
+            entry_name("host.get", &json!({"hostids": ["1"], "output": "extend"}))
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio::time::{Instant, sleep_until};
+
+/// Client-side cap on the request rate and on requests in flight, shared by
+/// all clones of a client so toasts acknowledging at once and the poll loop
+/// draw from the same budget.
+#[derive(Debug)]
+pub(super) struct Throttle {
+    in_flight: Option<Semaphore>,
+    /// Minimum gap between two requests.
+    spacing: Option<Duration>,
+    next_slot: Mutex<Option<Instant>>,
+}
+
+impl Throttle {
+    pub(super) fn new(per_second: Option<u32>, in_flight: Option<usize>) -> Self {
+        Self {
+            in_flight: in_flight.map(|permits| Semaphore::new(permits.max(1))),
+            spacing: per_second
+                .filter(|rate| *rate > 0)
+                .map(|rate| Duration::from_secs(1) / rate),
+            next_slot: Mutex::new(None),
+        }
+    }
+
+    /// Wait for a free in-flight slot; held until the call returns.
+    pub(super) async fn permit(&self) -> Option<SemaphorePermit<'_>> {
+        match &self.in_flight {
+            // The semaphore is never closed.
+            Some(semaphore) => semaphore.acquire().await.ok(),
+            None => None,
+        }
+    }
+
+    /// Wait until the next request may be sent under the rate cap.
+    pub(super) async fn wait_turn(&self) {
+        let Some(spacing) = self.spacing else {
+            return;
+        };
+        let slot = {
+            let mut next = self
+                .next_slot
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let now = Instant::now();
+            let slot = next.map_or(now, |next| next.max(now));
+            *next = Some(slot + spacing);
+            slot
+        };
+        sleep_until(slot).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time::Instant;
+
+    use super::Throttle;
+
+    #[tokio::test]
+    async fn requests_are_spaced_by_the_rate() {
+        let throttle = Throttle::new(Some(20), Some(1));
+        let started = Instant::now();
+        for _ in 0..3 {
+            let _permit = throttle.permit().await;
+            throttle.wait_turn().await;
+        }
+        // The first request goes out at once, the next two 50ms apart.
+        assert!(started.elapsed() >= Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "test-util")]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+
+use alerting::error::{Error as AlertError, ZbxError};
+use alerting::testing::{Fault, MockProblem, MockZabbix};
+use alerting::types::{AckFilter, Severity};
+
+#[tokio::test]
+async fn serves_problems_hosts_and_acks() {
+    let zabbix = MockZabbix::start().await;
+    zabbix.set_problems(vec![
+        MockProblem::new("1", "Disk full", Severity::High, "srv01"),
+        MockProblem::new("2", "CPU load", Severity::Warning, "srv02"),
+    ]);
+    let client = zabbix.client().unwrap();
+
+    let problems = client
+        .active_problems(10, AckFilter::Unacked)
+        .await
+        .unwrap();
+    assert_eq!(problems.len(), 2);
+    let hosts = client.resolve_hosts(&["2".to_string()], 1).await.unwrap();
+    assert_eq!(hosts[0].as_ref().unwrap().display_name, "srv02");
+
+    client.ack_event("1", None).await.unwrap();
+    let unacked = client
+        .active_problems(10, AckFilter::Unacked)
+        .await
+        .unwrap();
+    assert_eq!(unacked.len(), 1);
+    assert_eq!(unacked[0].event_id, "2");
+}
+
+#[tokio::test]
+async fn only_ack_and_unack_bits_move_the_flag() {
+    let zabbix = MockZabbix::start().await;
+    zabbix.set_problems(vec![MockProblem::new(
+        "1",
+        "Disk full",
+        Severity::High,
+        "srv01",
+    )]);
+    let client = zabbix.client().unwrap();
+
+    client.ack_event("1", None).await.unwrap();
+    client.comment_event("1", "looking into it").await.unwrap();
+    assert!(zabbix.problems()[0].acknowledged);
+    client.unack_event("1", None).await.unwrap();
+    assert!(!zabbix.problems()[0].acknowledged);
+    client
+        .comment_event("1", "back to the queue")
+        .await
+        .unwrap();
+    assert!(!zabbix.problems()[0].acknowledged);
+}
+
+#[tokio::test]
+async fn injected_faults_take_precedence() {
+    let zabbix = MockZabbix::start().await;
+    zabbix
+        .inject_fault(
+            Fault::RpcError {
+                code: 42,
+                message: "Invalid token".into(),
+            },
+            1,
+        )
+        .await;
+    let client = zabbix.client().unwrap();
+
+    let err = client.active_problems(1, AckFilter::All).await.unwrap_err();
+    assert!(matches!(
+        err,
+        AlertError::Zabbix(ZbxError::Api { code: 42, .. })
+    ));
+    assert!(client.active_problems(1, AckFilter::All).await.is_ok());
+}
+
+#[tokio::test]
+async fn resolves_proxy_of_each_host() {
+    let zabbix = MockZabbix::start().await;
+    let mut remote = MockProblem::new("2", "CPU load", Severity::Warning, "srv02");
+    remote.proxy_id = "10500".into();
+    zabbix.set_problems(vec![
+        MockProblem::new("1", "Disk full", Severity::High, "srv01"),
+        remote,
+    ]);
+    let client = zabbix.client().unwrap();
+
+    let hosts = client
+        .resolve_hosts(&["1".to_string(), "2".to_string()], 2)
+        .await
+        .unwrap();
+    let host_ids: Vec<String> = hosts
+        .iter()
+        .map(|host| host.as_ref().unwrap().host_id.clone().unwrap())
+        .collect();
+    let proxies = client.host_proxies(&host_ids).await.unwrap();
+    assert_eq!(proxies["srv01"], "0");
+    assert_eq!(proxies["srv02"], "10500");
+}
+
+#[tokio::test]
+async fn problems_of_a_proxy_skip_other_hosts() {
+    let zabbix = MockZabbix::start().await;
+    let mut remote = MockProblem::new("2", "CPU load", Severity::Warning, "srv02");
+    remote.proxy_id = "10500".into();
+    zabbix.set_problems(vec![
+        MockProblem::new("1", "Disk full", Severity::High, "srv01"),
+        remote,
+    ]);
+    let client = zabbix.client().unwrap();
+
+    let host_ids = client.proxy_host_ids(&["10500".to_string()]).await.unwrap();
+    assert_eq!(host_ids, ["srv02"]);
+    let problems = client
+        .active_problems_on_hosts(&host_ids, 1, 1, AckFilter::All)
+        .await
+        .unwrap();
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].event_id, "2");
+}
@@ -3,13 +3,12 @@
 use std::time::Duration;
 
 use alerting::error::{Error as AlertError, ZbxError};
-use alerting::types::AckFilter;
-use alerting::zbx_client::ZbxClient;
+use alerting::types::{AckFilter, EventObject, EventSource, Severity};
+use alerting::zbx_client::{Availability, EventRank, Frontend, ProblemQuery, ZbxClient};
 use secrecy::SecretString;
 use serde_json::json;
-use tokio::time::timeout;
 use url::Url;
-use wiremock::matchers::{body_string_contains, method};
+use wiremock::matchers::{body_string_contains, header, method, path, query_param};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 fn client(base: &MockServer) -> ZbxClient {
@@ -23,42 +22,290 @@ fn client(base: &MockServer) -> ZbxClient {
     .expect("client")
 }
 
+/// Tests that only need problems, hosts and faults run against the shared
+/// `MockZabbix`. The rest of this file mounts hand-written responses: they
+/// pin request payloads or exercise methods (paging, internal events,
+/// roles, services, graphs, ...) the canned server does not answer.
+#[cfg(feature = "test-util")]
+mod canned {
+    use std::time::Duration;
+
+    use alerting::error::{Error as AlertError, ZbxError};
+    use alerting::testing::{Fault, MockProblem, MockZabbix};
+    use alerting::types::{AckFilter, Severity};
+    use alerting::zbx_client::ZbxClient;
+    use secrecy::SecretString;
+    use tokio::time::timeout;
+
+    #[tokio::test]
+    async fn active_problems_returns_results() {
+        let zabbix = MockZabbix::start().await;
+        zabbix.set_problems(vec![MockProblem::new(
+            "123",
+            "Disk full",
+            Severity::High,
+            "Server 01",
+        )]);
+        let client = zabbix.client().unwrap();
+
+        let problems = client
+            .active_problems(10, AckFilter::All)
+            .await
+            .expect("problems");
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].event_id, "123");
+        assert!(!problems[0].acknowledged);
+
+        let hosts = client
+            .resolve_hosts(&["123".to_string()], 2)
+            .await
+            .expect("hosts");
+        let meta = hosts[0].as_ref().expect("host meta");
+        assert_eq!(meta.display_name, "Server 01");
+    }
+
+    #[tokio::test]
+    async fn retries_exhaust_on_server_error() {
+        let zabbix = MockZabbix::start().await;
+        zabbix.inject_fault(Fault::Status(503), u64::MAX).await;
+
+        let client = zabbix.client().unwrap();
+        let err = client
+            .active_problems(1, AckFilter::All)
+            .await
+            .expect_err("should fail");
+        match err {
+            AlertError::Zabbix(ZbxError::RetryExhausted { .. }) => {}
+            other => panic!("unexpected error: {other}"),
+        }
+
+        let stats = client.call_stats();
+        assert_eq!(stats[0].method, "problem.get");
+        assert_eq!((stats[0].calls, stats[0].errors), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn returns_api_error_details() {
+        let zabbix = MockZabbix::start().await;
+        zabbix
+            .inject_fault(
+                Fault::RpcError {
+                    code: 42,
+                    message: "Invalid token".into(),
+                },
+                1,
+            )
+            .await;
+
+        let err = zabbix
+            .client()
+            .unwrap()
+            .active_problems(1, AckFilter::All)
+            .await
+            .expect_err("should fail");
+        match err {
+            AlertError::Zabbix(ZbxError::Api { code, .. }) => assert_eq!(code, 42),
+            _ => panic!("unexpected error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn timeouts_surface_as_errors() {
+        let zabbix = MockZabbix::start().await;
+        zabbix
+            .inject_fault(Fault::Delay(Duration::from_millis(1500)), u64::MAX)
+            .await;
+
+        let client = ZbxClient::new(
+            zabbix.url().unwrap(),
+            SecretString::from("token"),
+            Duration::from_millis(500),
+            Duration::from_millis(200),
+            true,
+        )
+        .unwrap();
+
+        let res = timeout(
+            Duration::from_secs(5),
+            client.active_problems(1, AckFilter::All),
+        )
+        .await;
+        let err = res.expect("timeout future").expect_err("should fail");
+        assert!(matches!(err, AlertError::Zabbix(ZbxError::Request { .. })));
+    }
+}
+
 #[tokio::test]
-async fn active_problems_returns_results() {
+async fn problem_request_payload_snapshot() {
     let server = MockServer::start().await;
 
     Mock::given(method("POST"))
-        .and(body_string_contains("problem.get"))
         .respond_with(ResponseTemplate::new(200).set_body_json(json!({
             "jsonrpc": "2.0",
-            "result": [
-                {
-                    "eventid": "123",
-                    "clock": "1700000000",
-                    "lastchange": "1700000100",
-                    "severity": "4",
-                    "name": "Disk full",
-                    "acknowledged": "0"
-                }
-            ],
+            "result": [],
             "id": 1
         })))
         .mount(&server)
         .await;
 
+    let client = client(&server);
+    let _ = client.active_problems(5, AckFilter::Acked).await;
+
+    let requests = server.received_requests().await.expect("requests");
+    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).expect("json body");
+    insta::assert_json_snapshot!("problem_get_payload", body);
+}
+
+#[tokio::test]
+async fn unreachable_url_fails_over_to_the_next_one() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("host.get"))
+        .respond_with(rpc_result(&json!([{ "hostid": "10084" }])))
+        .mount(&server)
+        .await;
+
+    // Nothing listens on a port just released by the OS.
+    let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let dead = Url::parse(&format!("http://{}", closed.local_addr().unwrap())).unwrap();
+    drop(closed);
+
+    let client = ZbxClient::new(
+        dead,
+        SecretString::from("token"),
+        Duration::from_secs(2),
+        Duration::from_secs(1),
+        true,
+    )
+    .unwrap()
+    .with_fallback_urls(&[Url::parse(&server.uri()).unwrap()])
+    .unwrap();
+    for _ in 0..2 {
+        assert_eq!(client.host_ids_by_name("db01").await.unwrap(), ["10084"]);
+    }
+    assert_eq!(server.received_requests().await.unwrap().len(), 2);
+}
+
+#[cfg(feature = "compression")]
+#[tokio::test]
+async fn compressed_responses_are_requested() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(rpc_result(&json!([{ "hostid": "10084" }])))
+        .mount(&server)
+        .await;
+
+    let ids = client(&server).host_ids_by_name("db01").await.unwrap();
+    assert_eq!(ids, ["10084"]);
+    let requests = server.received_requests().await.unwrap();
+    let encodings = requests[0].headers.get("accept-encoding").unwrap();
+    assert_eq!(encodings, "gzip, br");
+}
+
+#[tokio::test]
+async fn events_without_host_are_not_looked_up_again() {
+    let server = MockServer::start().await;
     Mock::given(method("POST"))
         .and(body_string_contains("event.get"))
+        .respond_with(rpc_result(&json!([{ "hosts": [] }])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = client(&server).with_host_miss_ttl(Duration::from_mins(5));
+    let events = ["404".to_string()];
+    for _ in 0..2 {
+        let hosts = client.resolve_hosts(&events, 1).await.unwrap();
+        assert!(hosts[0].is_none());
+    }
+}
+
+#[tokio::test]
+async fn full_pages_are_followed_by_older_ones() {
+    let server = MockServer::start().await;
+    let problem = |id: &str| json!({ "eventid": id, "clock": "1700000000", "severity": "3", "name": "Disk full" });
+    Mock::given(method("POST"))
+        .and(body_string_contains("\"eventid_till\":\"8\""))
+        .respond_with(rpc_result(&json!([problem("5")])))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("problem.get"))
+        .respond_with(rpc_result(&json!([problem("12"), problem("9")])))
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    let problems = client
+        .active_problems_paged(2, 5, AckFilter::All)
+        .await
+        .unwrap();
+    let ids: Vec<&str> = problems.iter().map(|p| p.event_id.as_str()).collect();
+    assert_eq!(ids, ["12", "9", "5"]);
+
+    let capped = client
+        .active_problems_paged(2, 1, AckFilter::All)
+        .await
+        .unwrap();
+    assert_eq!(capped.len(), 2);
+}
+
+#[tokio::test]
+async fn internal_events_are_fetched_per_object() {
+    let server = MockServer::start().await;
+    let problem = |id: &str, severity: &str, name: &str| json!({ "eventid": id, "clock": "1700000000", "severity": severity, "name": name, "objectid": "31" });
+    Mock::given(method("POST"))
+        .and(body_string_contains("\"object\":4"))
+        .respond_with(rpc_result(&json!([problem("20", "0", "Item unsupported")])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("\"source\":3"))
+        .respond_with(rpc_result(&json!([])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("problem.get"))
+        .respond_with(rpc_result(&json!([problem("25", "3", "Disk full")])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let problems = client(&server)
+        .with_event_filter(
+            &[EventSource::Trigger, EventSource::Internal],
+            &[EventObject::Trigger, EventObject::Item],
+        )
+        .active_problems(10, AckFilter::All)
+        .await
+        .unwrap();
+    let ids: Vec<&str> = problems.iter().map(|p| p.event_id.as_str()).collect();
+    assert_eq!(ids, ["25", "20"]);
+    assert_eq!(problems[1].trigger_id, None);
+    assert_eq!(problems[1].severity, Severity::Info);
+    assert_eq!(problems[0].trigger_id.as_deref(), Some("31"));
+}
+
+#[tokio::test]
+async fn replays_recorded_responses_without_server() {
+    let dir = std::env::temp_dir().join(format!("alerting-tape-{}", uuid::Uuid::new_v4()));
+    let server = MockServer::start().await;
+    let base = Url::parse(&server.uri()).unwrap();
+
+    Mock::given(method("POST"))
+        .and(body_string_contains("problem.get"))
         .respond_with(ResponseTemplate::new(200).set_body_json(json!({
             "jsonrpc": "2.0",
             "result": [
                 {
-                    "hosts": [
-                        {
-                            "host": "srv01",
-                            "name": "Server 01",
-                            "status": "0"
-                        }
-                    ]
+                    "eventid": "55",
+                    "clock": "1700000000",
+                    "lastchange": "1700000100",
+                    "severity": "5",
+                    "name": "Link down",
+                    "acknowledged": "0"
                 }
             ],
             "id": 1
@@ -66,118 +313,569 @@ async fn active_problems_returns_results() {
         .mount(&server)
         .await;
 
-    let client = client(&server);
-    let problems = client
-        .active_problems(10, AckFilter::All)
+    let recorder = client(&server).record_to(&dir);
+    recorder.begin_poll();
+    recorder.active_problems(10, AckFilter::All).await.unwrap();
+    drop(server);
+
+    let replayer = ZbxClient::new(
+        base,
+        SecretString::from("token"),
+        Duration::from_secs(2),
+        Duration::from_secs(1),
+        true,
+    )
+    .unwrap()
+    .replay_from(&dir);
+    assert!(!replayer.replay_exhausted());
+    replayer.begin_poll();
+    let problems = replayer.active_problems(10, AckFilter::All).await.unwrap();
+    assert_eq!(problems[0].event_id, "55");
+    assert!(replayer.replay_exhausted());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn replays_each_page_and_host_lookup_separately() {
+    let dir = std::env::temp_dir().join(format!("alerting-tape-{}", uuid::Uuid::new_v4()));
+    let server = MockServer::start().await;
+    let base = Url::parse(&server.uri()).unwrap();
+    let problem = |id: &str| {
+        json!({
+            "eventid": id,
+            "clock": "1700000000",
+            "lastchange": "1700000100",
+            "severity": "4",
+            "name": format!("Problem {id}"),
+            "acknowledged": "0"
+        })
+    };
+
+    Mock::given(method("POST"))
+        .and(body_string_contains("problem.get"))
+        .and(body_string_contains("eventid_till"))
+        .respond_with(rpc_result(&json!([problem("50")])))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("problem.get"))
+        .respond_with(rpc_result(&json!([problem("60")])))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("host.get"))
+        .and(body_string_contains("selectInterfaces"))
+        .respond_with(rpc_result(&json!([
+            { "hostid": "1", "interfaces": [{ "type": "1", "available": "2" }] }
+        ])))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("host.get"))
+        .respond_with(rpc_result(&json!([{ "hostid": "1", "proxyid": "7" }])))
+        .mount(&server)
+        .await;
+
+    let ids = ["1".to_string()];
+    let recorder = client(&server).record_to(&dir);
+    recorder.begin_poll();
+    recorder
+        .active_problems_paged(1, 2, AckFilter::All)
         .await
-        .expect("problems");
-    assert_eq!(problems.len(), 1);
-    assert_eq!(problems[0].event_id, "123");
-    assert!(!problems[0].acknowledged);
+        .unwrap();
+    recorder.host_proxies(&ids).await.unwrap();
+    recorder.host_availability(&ids).await.unwrap();
+    drop(server);
 
-    let hosts = client
-        .resolve_hosts(&["123".to_string()], 2)
+    let replayer = ZbxClient::new(
+        base,
+        SecretString::from("token"),
+        Duration::from_secs(2),
+        Duration::from_secs(1),
+        true,
+    )
+    .unwrap()
+    .replay_from(&dir);
+    replayer.begin_poll();
+    let problems = replayer
+        .active_problems_paged(1, 2, AckFilter::All)
         .await
-        .expect("hosts");
-    let meta = hosts[0].as_ref().expect("host meta");
-    assert_eq!(meta.display_name, "Server 01");
+        .unwrap();
+    let ids_replayed: Vec<&str> = problems.iter().map(|p| p.event_id.as_str()).collect();
+    assert_eq!(ids_replayed, ["60", "50"]);
+    let proxies = replayer.host_proxies(&ids).await.unwrap();
+    assert_eq!(proxies["1"], "7");
+    let availability = replayer.host_availability(&ids).await.unwrap();
+    assert_eq!(availability["1"], Availability::Unavailable);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+fn rpc_result(result: &serde_json::Value) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({ "jsonrpc": "2.0", "result": result, "id": 1 }))
 }
 
 #[tokio::test]
-async fn problem_request_payload_snapshot() {
+async fn preflight_reports_missing_ack_permission() {
     let server = MockServer::start().await;
+    let routes = [
+        ("apiinfo.version", json!("6.0.25")),
+        ("problem.get", json!([])),
+        (
+            "user.checkAuthentication",
+            json!({ "userid": "5", "type": "1", "roleid": "4" }),
+        ),
+        (
+            "role.get",
+            json!([{
+                "roleid": "4",
+                "rules": {
+                    "actions": [{ "name": "acknowledge_problems", "status": "0" }],
+                    "actions.default_access": "1"
+                }
+            }]),
+        ),
+    ];
+    for (rpc_method, result) in routes {
+        Mock::given(method("POST"))
+            .and(body_string_contains(rpc_method))
+            .respond_with(rpc_result(&result))
+            .mount(&server)
+            .await;
+    }
+
+    let report = client(&server).preflight().await.expect("preflight");
+    assert_eq!(report.api_version, "6.0.25");
+    assert_eq!(report.can_acknowledge, Some(false));
 
+    let requests = server.received_requests().await.expect("requests");
+    let version_call: serde_json::Value =
+        serde_json::from_slice(&requests[0].body).expect("json body");
+    assert!(version_call.get("auth").is_none());
+}
+
+#[tokio::test]
+async fn preflight_names_rejected_token() {
+    let server = MockServer::start().await;
     Mock::given(method("POST"))
+        .and(body_string_contains("apiinfo.version"))
+        .respond_with(rpc_result(&json!("7.0.0")))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("problem.get"))
         .respond_with(ResponseTemplate::new(200).set_body_json(json!({
             "jsonrpc": "2.0",
-            "result": [],
+            "error": { "code": -32602, "message": "Invalid params.", "data": "Not authorized." },
             "id": 1
         })))
         .mount(&server)
         .await;
 
-    let client = client(&server);
-    let _ = client.active_problems(5, AckFilter::Acked).await;
-
-    let requests = server.received_requests().await.expect("requests");
-    let body: serde_json::Value = serde_json::from_slice(&requests[0].body).expect("json body");
-    insta::assert_json_snapshot!("problem_get_payload", body);
+    let err = client(&server).preflight().await.expect_err("should fail");
+    match err {
+        AlertError::Zabbix(ZbxError::Preflight { message, .. }) => {
+            assert_eq!(message, "token is invalid or expired");
+        }
+        other => panic!("unexpected error: {other}"),
+    }
 }
 
 #[tokio::test]
-async fn retries_exhaust_on_server_error() {
+async fn ack_reuses_notification_correlation_id() {
     let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("event.acknowledge"))
+        .and(header("x-correlation-id", "0190-notif"))
+        .respond_with(rpc_result(&json!({ "eventids": ["7"] })))
+        .expect(1)
+        .mount(&server)
+        .await;
 
+    client(&server)
+        .with_correlation_id("0190-notif")
+        .ack_event("7", None)
+        .await
+        .expect("ack");
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_client_acks_without_a_runtime() {
+    let runtime = tokio::runtime::Runtime::new().expect("runtime");
+    let server = runtime.block_on(async {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("event.acknowledge"))
+            .and(body_string_contains("\"eventids\":[\"9\"]"))
+            .respond_with(rpc_result(&json!({ "eventids": ["9"] })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        server
+    });
+
+    let blocking = alerting::zbx_client::blocking::ZbxClient::from_async(client(&server))
+        .expect("blocking client");
+    blocking.ack_event("9", None).expect("ack");
+    runtime.block_on(server.verify());
+}
+
+#[tokio::test]
+async fn comment_sends_the_message_bit_alone() {
+    let server = MockServer::start().await;
     Mock::given(method("POST"))
-        .respond_with(ResponseTemplate::new(503))
+        .and(body_string_contains("event.acknowledge"))
+        .and(body_string_contains("\"action\":4"))
+        .and(body_string_contains("\"message\":\"looking into it\""))
+        .respond_with(rpc_result(&json!({ "eventids": ["42"] })))
+        .expect(1)
         .mount(&server)
         .await;
 
     let client = client(&server);
-    let err = client
-        .active_problems(1, AckFilter::All)
+    client
+        .comment_event("42", "looking into it")
         .await
-        .expect_err("should fail");
-    match err {
-        AlertError::Zabbix(ZbxError::RetryExhausted { .. }) => {}
-        other => panic!("unexpected error: {other}"),
-    }
+        .expect("comment");
+    client
+        .comment_event("42", "  ")
+        .await
+        .expect("empty comment");
 }
 
 #[tokio::test]
-async fn returns_api_error_details() {
+async fn suppression_carries_its_end_time() {
     let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("event.acknowledge"))
+        .and(body_string_contains("\"action\":36"))
+        .and(body_string_contains("\"suppress_until\":1700007200"))
+        .respond_with(rpc_result(&json!({ "eventids": ["42"] })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client(&server)
+        .suppress_event("42", Some(1_700_007_200), Some("maintenance".to_string()))
+        .await
+        .expect("suppress");
+}
 
+#[tokio::test]
+async fn symptom_rank_names_its_cause() {
+    let server = MockServer::start().await;
     Mock::given(method("POST"))
-        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
-            "jsonrpc": "2.0",
-            "error": {
-                "code": 42,
-                "message": "Invalid token"
+        .and(body_string_contains("event.acknowledge"))
+        .and(body_string_contains("\"action\":256"))
+        .and(body_string_contains("\"cause_eventid\":\"40\""))
+        .respond_with(rpc_result(&json!({ "eventids": ["42"] })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    client(&server)
+        .rank_event(
+            "42",
+            &EventRank::Symptom {
+                cause_event_id: "40".to_string(),
             },
-            "id": 1
-        })))
+        )
+        .await
+        .expect("rank");
+}
+
+#[tokio::test]
+async fn services_by_event_groups_service_names() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("service.get"))
+        .respond_with(rpc_result(&json!([
+            {
+                "serviceid": "1",
+                "name": "Payments",
+                "problem_events": [{ "eventid": "42" }]
+            },
+            {
+                "serviceid": "2",
+                "name": "Checkout",
+                "problem_events": [{ "eventid": "42" }, { "eventid": "43" }]
+            },
+            { "serviceid": "3", "name": "Search", "problem_events": [] }
+        ])))
         .mount(&server)
         .await;
 
-    let client = client(&server);
-    let err = client
-        .active_problems(1, AckFilter::All)
+    let services = client(&server).services_by_event().await.unwrap();
+    assert_eq!(services["42"], ["Checkout", "Payments"]);
+    assert_eq!(services["43"], ["Checkout"]);
+    assert_eq!(services.len(), 2);
+}
+
+#[tokio::test]
+async fn event_updates_are_listed_oldest_first() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("select_acknowledges"))
+        .respond_with(rpc_result(&json!([
+            {
+                "eventid": "42",
+                "acknowledges": [
+                    {
+                        "acknowledgeid": "9",
+                        "userid": "3",
+                        "clock": "1700000200",
+                        "action": "6",
+                        "message": "on it",
+                        "username": "jdoe",
+                        "name": "Jane",
+                        "surname": "Doe"
+                    },
+                    {
+                        "acknowledgeid": "7",
+                        "userid": "1",
+                        "clock": "1700000100",
+                        "action": "4",
+                        "message": "first look"
+                    }
+                ]
+            }
+        ])))
+        .mount(&server)
+        .await;
+
+    let updates = client(&server)
+        .event_updates(&["42".to_string()])
         .await
-        .expect_err("should fail");
-    match err {
-        AlertError::Zabbix(ZbxError::Api { code, .. }) => assert_eq!(code, 42),
-        _ => panic!("unexpected error"),
-    }
+        .unwrap();
+    let updates = &updates["42"];
+    assert_eq!(updates.len(), 2);
+    assert_eq!(updates[0].update_id, 7);
+    assert!(!updates[0].acknowledges());
+    assert!(updates[1].acknowledges());
+    assert_eq!(updates[1].author(), "Jane Doe");
 }
 
 #[tokio::test]
-async fn timeouts_surface_as_errors() {
+async fn cause_events_are_requested_and_parsed() {
     let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("cause_eventid"))
+        .respond_with(rpc_result(&json!([
+            {
+                "eventid": "11",
+                "clock": "1700000000",
+                "severity": "4",
+                "name": "Switch down",
+                "acknowledged": "0",
+                "cause_eventid": "0"
+            },
+            {
+                "eventid": "12",
+                "clock": "1700000001",
+                "severity": "3",
+                "name": "Host unreachable",
+                "acknowledged": "0",
+                "cause_eventid": "11"
+            }
+        ])))
+        .mount(&server)
+        .await;
+
+    let problems = client(&server)
+        .with_cause_events()
+        .active_problems(10, AckFilter::All)
+        .await
+        .unwrap();
+    assert_eq!(problems[0].cause_event_id, None);
+    assert_eq!(problems[1].cause_event_id.as_deref(), Some("11"));
+}
+
+#[tokio::test]
+async fn host_availability_combines_interfaces() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("host.get"))
+        .respond_with(rpc_result(&json!([
+            {
+                "hostid": "1",
+                "interfaces": [
+                    { "type": "1", "available": "1" },
+                    { "type": "2", "available": "2" }
+                ]
+            },
+            { "hostid": "2", "active_available": "1", "interfaces": [] },
+            { "hostid": "3", "interfaces": [{ "type": "1", "available": "0" }] }
+        ])))
+        .mount(&server)
+        .await;
+
+    let ids = ["1", "2", "3"].map(String::from);
+    let availability = client(&server).host_availability(&ids).await.unwrap();
+    assert_eq!(availability["1"], Availability::Unavailable);
+    assert_eq!(availability["2"], Availability::Available);
+    assert_eq!(availability["3"], Availability::Unknown);
+}
+
+#[tokio::test]
+async fn trigger_graph_goes_through_the_trigger_items() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("item.get"))
+        .and(body_string_contains("\"triggerids\":[\"13491\"]"))
+        .respond_with(rpc_result(&json!([{ "itemid": "23296" }])))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("graph.get"))
+        .and(body_string_contains("\"itemids\":[\"23296\"]"))
+        .respond_with(rpc_result(&json!([{ "graphid": "524" }])))
+        .mount(&server)
+        .await;
 
+    let graph = client(&server).trigger_graph("13491").await.unwrap();
+    assert_eq!(graph.as_deref(), Some("524"));
+}
+
+#[tokio::test]
+async fn frontend_signs_in_before_downloading_graph() {
+    let server = MockServer::start().await;
     Mock::given(method("POST"))
+        .and(path("/index.php"))
+        .and(body_string_contains("name=viewer"))
         .respond_with(
-            ResponseTemplate::new(200)
-                .set_body_json(json!({"jsonrpc":"2.0","result":[],"id":1}))
-                .set_delay(Duration::from_millis(1500)),
+            ResponseTemplate::new(302)
+                .insert_header("location", "zabbix.php?action=dashboard.view")
+                .insert_header("set-cookie", "zbx_session=abc123; path=/; HttpOnly"),
         )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/chart2.php"))
+        .and(query_param("graphid", "524"))
+        .and(query_param("from", "now-3600s"))
+        .and(header("cookie", "zbx_session=abc123"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(b"\x89PNG".to_vec(), "image/png"))
+        .expect(2)
         .mount(&server)
         .await;
 
-    let client = ZbxClient::new(
-        Url::parse(&server.uri()).unwrap(),
-        SecretString::from("token"),
-        Duration::from_millis(500),
-        Duration::from_millis(200),
+    let frontend = Frontend::new(
+        Url::parse(&format!("{}/api_jsonrpc.php", server.uri())).unwrap(),
+        "viewer".to_string(),
+        SecretString::from("secret"),
+        Duration::from_secs(2),
+        Duration::from_secs(1),
         true,
     )
     .unwrap();
+    for _ in 0..2 {
+        let png = frontend
+            .graph_png("524", Duration::from_hours(1))
+            .await
+            .unwrap();
+        assert_eq!(png, b"\x89PNG");
+    }
+}
 
-    let res = timeout(
-        Duration::from_secs(5),
-        client.active_problems(1, AckFilter::All),
+#[tokio::test]
+async fn frontend_reports_refused_sign_in() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/index.php"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("<form>"))
+        .mount(&server)
+        .await;
+
+    let frontend = Frontend::new(
+        Url::parse(&format!("{}/api_jsonrpc.php", server.uri())).unwrap(),
+        "viewer".to_string(),
+        SecretString::from("wrong"),
+        Duration::from_secs(2),
+        Duration::from_secs(1),
+        true,
     )
-    .await;
-    let err = res.expect("timeout future").expect_err("should fail");
-    assert!(matches!(err, AlertError::Zabbix(ZbxError::Request { .. })));
+    .unwrap();
+    let err = frontend
+        .graph_png("524", Duration::from_hours(1))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), "ZBX_FRONTEND");
+}
+
+#[tokio::test]
+async fn event_tags_and_trigger_expressions_are_fetched() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("problem.get"))
+        .and(body_string_contains("selectTags"))
+        .respond_with(rpc_result(&json!([{
+            "eventid": "7",
+            "objectid": "13491",
+            "clock": "1700000000",
+            "severity": "4",
+            "name": "Disk full",
+            "tags": [{ "tag": "scope", "value": "capacity" }, { "tag": "db" }]
+        }])))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("trigger.get"))
+        .and(body_string_contains("\"expandExpression\":true"))
+        .respond_with(rpc_result(&json!([
+            { "triggerid": "13491", "expression": "last(/db01/vfs.fs.pused)>90" }
+        ])))
+        .mount(&server)
+        .await;
+
+    let client = client(&server).with_event_tags();
+    let problems = client.active_problems(5, AckFilter::All).await.unwrap();
+    assert_eq!(problems[0].trigger_id.as_deref(), Some("13491"));
+    assert_eq!(problems[0].tags.len(), 2);
+    assert_eq!(problems[0].tags[1].value, "");
+
+    let expressions = client
+        .trigger_expressions(&["13491".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(expressions["13491"], "last(/db01/vfs.fs.pused)>90");
+}
+
+#[tokio::test]
+async fn bulk_ack_filters_problems_and_batches_the_ack() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("problem.get"))
+        .and(body_string_contains("\"severities\":[1,2]"))
+        .and(body_string_contains("\"time_till\":1700000000"))
+        .respond_with(rpc_result(&json!([])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("event.acknowledge"))
+        .and(body_string_contains("\"eventids\":[\"1\",\"2\"]"))
+        .and(body_string_contains("\"action\":6"))
+        .respond_with(rpc_result(&json!({ "eventids": [1, 2] })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    let query = ProblemQuery {
+        ack: AckFilter::Unacked,
+        event_ids: Vec::new(),
+        host_ids: Vec::new(),
+        severities: vec![Severity::Info, Severity::Warning],
+        started_before: Some(1_700_000_000),
+    };
+    assert!(client.find_problems(&query).await.unwrap().is_empty());
+    client
+        .ack_events(&["1", "2"], Some("bulk cleanup".to_string()))
+        .await
+        .unwrap();
 }